@@ -0,0 +1,131 @@
+use passmann_shared::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Backups kept unless PASSMANN_BACKUP_COUNT says otherwise
+pub const DEFAULT_BACKUP_COUNT: usize = 5;
+
+pub fn backup_count() -> usize {
+    std::env::var("PASSMANN_BACKUP_COUNT")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_BACKUP_COUNT)
+}
+
+/// `vault.enc` -> `vault.enc.<n>`, where 1 is the most recent backup
+pub fn backup_path(vault_path: &Path, n: usize) -> PathBuf {
+    let mut name = vault_path.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+/// Shift existing backups up one slot, dropping the oldest, and copy the current
+/// vault into slot 1. Returns false when there is nothing to back up yet.
+pub fn rotate(vault_path: &Path, keep: usize) -> Result<bool> {
+    if keep == 0 || !vault_path.exists() {
+        return Ok(false);
+    }
+
+    let oldest = backup_path(vault_path, keep);
+    if oldest.exists() {
+        fs::remove_file(&oldest)?;
+    }
+    for n in (1..keep).rev() {
+        let from = backup_path(vault_path, n);
+        if from.exists() {
+            fs::rename(&from, backup_path(vault_path, n + 1))?;
+        }
+    }
+
+    write_private(&backup_path(vault_path, 1), &fs::read(vault_path)?)?;
+    Ok(true)
+}
+
+/// Make backup `n` the active vault. The current vault is rotated into slot 1
+/// first, so the restore itself can be undone.
+pub fn promote(vault_path: &Path, n: usize, keep: usize) -> Result<()> {
+    let data = fs::read(backup_path(vault_path, n))?;
+    rotate(vault_path, keep)?;
+    write_private(vault_path, &data)
+}
+
+/// Undo `promote`: put slot 1 back as the active vault and shift the other
+/// backups back down.
+pub fn undo_promote(vault_path: &Path, keep: usize) -> Result<()> {
+    let newest = backup_path(vault_path, 1);
+    write_private(vault_path, &fs::read(&newest)?)?;
+    fs::remove_file(&newest)?;
+    for n in 2..=keep {
+        let from = backup_path(vault_path, n);
+        if from.exists() {
+            fs::rename(&from, backup_path(vault_path, n - 1))?;
+        }
+    }
+    Ok(())
+}
+
+fn write_private(path: &Path, data: &[u8]) -> Result<()> {
+    fs::write(path, data)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn temp_vault() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("passmann_backup_{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("vault.enc")
+    }
+
+    #[test]
+    fn test_rotate_keeps_newest_backups() {
+        let vault = temp_vault();
+        assert!(!rotate(&vault, 3).unwrap());
+
+        for version in 1..=5 {
+            fs::write(&vault, format!("v{}", version)).unwrap();
+            assert!(rotate(&vault, 3).unwrap());
+        }
+
+        assert_eq!(fs::read_to_string(backup_path(&vault, 1)).unwrap(), "v5");
+        assert_eq!(fs::read_to_string(backup_path(&vault, 3)).unwrap(), "v3");
+        assert!(!backup_path(&vault, 4).exists());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(backup_path(&vault, 1)).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let _ = fs::remove_dir_all(vault.parent().unwrap());
+    }
+
+    #[test]
+    fn test_promote_and_undo() {
+        let vault = temp_vault();
+        fs::write(&vault, "old").unwrap();
+        rotate(&vault, 5).unwrap();
+        fs::write(&vault, "current").unwrap();
+
+        promote(&vault, 1, 5).unwrap();
+        assert_eq!(fs::read_to_string(&vault).unwrap(), "old");
+        assert_eq!(fs::read_to_string(backup_path(&vault, 1)).unwrap(), "current");
+        assert_eq!(fs::read_to_string(backup_path(&vault, 2)).unwrap(), "old");
+
+        undo_promote(&vault, 5).unwrap();
+        assert_eq!(fs::read_to_string(&vault).unwrap(), "current");
+        assert_eq!(fs::read_to_string(backup_path(&vault, 1)).unwrap(), "old");
+        assert!(!backup_path(&vault, 2).exists());
+
+        let _ = fs::remove_dir_all(vault.parent().unwrap());
+    }
+}
@@ -0,0 +1,94 @@
+//! Common-password blocklist consulted by every strength check in the CLI.
+//! Organisations can supply their own list with `--blocklist <path>`.
+
+use flate2::read::GzDecoder;
+use passmann_shared::Result;
+use passmann_strength::{cap_blocked, PasswordStrength, BLOCKED_MAX_SCORE, COMMON_PASSWORDS};
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::sync::OnceLock;
+
+static ACTIVE: OnceLock<HashSet<String>> = OnceLock::new();
+
+/// Load the blocklist once per run: from `path` when given, otherwise the
+/// built-in list
+pub fn init(path: Option<&Path>) -> Result<()> {
+    let list = match path {
+        Some(path) => load(path)?,
+        None => builtin(),
+    };
+    let _ = ACTIVE.set(list);
+    Ok(())
+}
+
+/// Used when no blocklist file is given
+fn builtin() -> HashSet<String> {
+    COMMON_PASSWORDS.iter().map(|p| p.to_string()).collect()
+}
+
+/// Read one password per line, skipping blank lines and `#` comments.
+/// Files ending in `.gz` are decompressed on the fly.
+pub fn load(path: &Path) -> Result<HashSet<String>> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| format!("Could not open blocklist '{}': {}", path.display(), e))?;
+    let reader: Box<dyn Read> = if path.extension().is_some_and(|ext| ext == "gz") {
+        Box::new(GzDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+    parse(BufReader::new(reader))
+}
+
+fn parse(reader: impl BufRead) -> Result<HashSet<String>> {
+    let mut list = HashSet::new();
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim();
+        if !line.is_empty() && !line.starts_with('#') {
+            list.insert(line.to_lowercase());
+        }
+    }
+    Ok(list)
+}
+
+/// Whether `password` is on the active blocklist (case-insensitive)
+pub fn is_common(password: &str) -> bool {
+    let list = ACTIVE.get_or_init(builtin);
+    list.contains(&password.to_lowercase())
+}
+
+/// `score` capped when `password` is blocklisted, for scores computed
+/// without the blocklist (such as the one cached on each entry)
+pub fn capped_score(password: &str, score: u8) -> u8 {
+    if is_common(password) { score.min(BLOCKED_MAX_SCORE) } else { score }
+}
+
+/// The strength estimate shared with the web client, capped for
+/// passwords on the active blocklist
+pub fn estimate_password_strength(password: &str) -> PasswordStrength {
+    let mut strength = passmann_strength::estimate_password_strength(password);
+    if is_common(password) {
+        cap_blocked(&mut strength);
+    }
+    strength
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_skips_comments_and_normalizes_case() {
+        let list = parse("# top passwords\nHunter2\n\n  Sunshine  \n".as_bytes()).unwrap();
+        assert_eq!(list.len(), 2);
+        assert!(list.contains("hunter2"));
+        assert!(list.contains("sunshine"));
+    }
+
+    #[test]
+    fn test_builtin_list_is_the_default() {
+        assert!(is_common("Password"));
+        assert!(!is_common("correct horse battery staple"));
+    }
+}
@@ -0,0 +1,81 @@
+use chrono::{DateTime, Duration, Utc};
+use passmann_shared::Result;
+use reqwest::Client;
+use sha1::{Digest, Sha1};
+
+/// Have I Been Pwned k-anonymity range API; only the first five hex digits
+/// of the password's SHA-1 ever leave the machine
+const RANGE_API: &str = "https://api.pwnedpasswords.com/range/";
+
+/// Why an entry was left out of a scan
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Skip {
+    MonitoringDisabled,
+    RecentlyChecked,
+}
+
+/// Decide whether an entry should be checked again. Entries never checked,
+/// checked longer than `stale_days` ago, or modified since their last check
+/// are due; with no threshold everything is.
+pub fn needs_rescan(
+    last_checked: Option<DateTime<Utc>>,
+    modified_at: DateTime<Utc>,
+    stale_days: Option<u32>,
+    now: DateTime<Utc>,
+) -> bool {
+    let (Some(days), Some(checked)) = (stale_days, last_checked) else {
+        return true;
+    };
+    modified_at > checked || now - checked > Duration::days(days as i64)
+}
+
+/// Number of times `password` appears in known breaches
+pub async fn pwned_count(client: &Client, password: &str) -> Result<u64> {
+    let digest = hex::encode_upper(Sha1::digest(password.as_bytes()));
+    let (prefix, suffix) = digest.split_at(5);
+
+    let body = client.get(format!("{}{}", RANGE_API, prefix))
+        // Padding hides how many suffixes share the prefix
+        .header("Add-Padding", "true")
+        .header("User-Agent", "PassMann-CLI")
+        .send().await?
+        .error_for_status()?
+        .text().await?;
+    Ok(count_in_range(&body, suffix))
+}
+
+/// Find `suffix` in a range response of `SUFFIX:COUNT` lines
+fn count_in_range(body: &str, suffix: &str) -> u64 {
+    body.lines()
+        .filter_map(|line| line.trim().split_once(':'))
+        .find(|(candidate, _)| candidate.eq_ignore_ascii_case(suffix))
+        .and_then(|(_, count)| count.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_needs_rescan_respects_threshold_and_changes() {
+        let now = Utc::now();
+        let checked = now - Duration::days(3);
+        let modified = now - Duration::days(10);
+
+        assert!(needs_rescan(None, modified, Some(7), now));
+        assert!(needs_rescan(Some(checked), modified, None, now));
+        assert!(!needs_rescan(Some(checked), modified, Some(7), now));
+        assert!(needs_rescan(Some(checked), modified, Some(2), now));
+        // Password changed after the last check
+        assert!(needs_rescan(Some(checked), now - Duration::days(1), Some(7), now));
+    }
+
+    #[test]
+    fn test_count_in_range_ignores_padding_entries() {
+        let body = "0018A45C4D1DEF81644B54AB7F969B88D65:1\r\n1E4C9B93F3F0682250B6CF8331B7EE68FD8:3861493\r\n00D4F6E8FA6EECAD2A3AA415EEC418D38EC:0\r\n";
+        assert_eq!(count_in_range(body, "1E4C9B93F3F0682250B6CF8331B7EE68FD8"), 3861493);
+        assert_eq!(count_in_range(body, "00D4F6E8FA6EECAD2A3AA415EEC418D38EC"), 0);
+        assert_eq!(count_in_range(body, "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF"), 0);
+    }
+}
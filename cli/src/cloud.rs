@@ -1,12 +1,21 @@
 use anyhow::Context;
-use chrono::{DateTime, Utc};
-use reqwest::{Client, header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE}};
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+use chrono::{DateTime, SecondsFormat, Utc};
+use reqwest::{Client, RequestBuilder, Response, StatusCode, header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER}};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::env;
+use std::fmt;
+use std::io::{Read, Write};
+use std::time::Duration;
 use uuid::Uuid;
 use passmann_shared::Result;
 
+/// Attempts made for retryable requests unless PASSMANN_SYNC_RETRIES says otherwise
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+const BASE_RETRY_DELAY: Duration = Duration::from_millis(500);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
 /// Supabase cloud storage client for PassMann
 /// Provides secure cloud synchronization with zero-knowledge architecture
 #[derive(Debug, Clone)]
@@ -14,7 +23,62 @@ pub struct SupabaseClient {
     client: Client,
     base_url: String,
     anon_key: String,
+    /// The signed-in user's session token. Requests are authorised with it
+    /// rather than the project's anon key whenever it is set.
+    access_token: Option<String>,
     user_id: Option<String>,
+    max_attempts: u32,
+}
+
+/// A device that has uploaded vaults, summarised from its latest upload
+#[derive(Debug, Clone)]
+pub struct DeviceInfo {
+    pub device_id: String,
+    pub device_name: String,
+    pub version: i32,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub size_bytes: i64,
+    /// Every vault row stored for this device, newest first
+    pub vault_ids: Vec<Uuid>,
+}
+
+/// Returned when a request kept failing transiently until every retry was used up
+#[derive(Debug)]
+pub struct RetryExhaustedError {
+    pub operation: String,
+    pub attempts: u32,
+    pub last_error: String,
+}
+
+impl fmt::Display for RetryExhaustedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} failed after {} attempts: {}", self.operation, self.attempts, self.last_error)
+    }
+}
+
+impl std::error::Error for RetryExhaustedError {}
+
+/// Returned when a downloaded vault doesn't match the checksum stored alongside it
+#[derive(Debug)]
+pub struct IntegrityError {
+    pub vault_id: Option<Uuid>,
+}
+
+impl fmt::Display for IntegrityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.vault_id {
+            Some(id) => write!(f, "Cloud vault {} failed integrity verification (checksum mismatch)", id),
+            None => write!(f, "Cloud vault failed integrity verification (checksum mismatch)"),
+        }
+    }
+}
+
+impl std::error::Error for IntegrityError {}
+
+/// A failed attempt that is worth retrying (network error, 5xx or 429)
+struct TransientFailure {
+    message: String,
+    retry_after: Option<Duration>,
 }
 
 /// Encrypted vault data structure for cloud storage
@@ -32,6 +96,51 @@ pub struct CloudVault {
     pub checksum: String,
     pub compression_enabled: bool,
     pub size_bytes: i64,
+    /// Generated once by the client for each local vault. Its uploads
+    /// replace the one row stored under it, so retries and later uploads never
+    /// add rows. Rows from before it existed have none.
+    #[serde(default)]
+    pub vault_id: Option<Uuid>,
+}
+
+/// A single encrypted entry stored as its own row for incremental sync
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloudEntry {
+    pub id: Uuid,
+    pub user_id: String,
+    pub device_id: String,
+    /// Base64 ciphertext of the entry JSON, empty for deletions
+    pub encrypted_data: String,
+    pub salt: String,
+    /// Upload time, which `fetch_entries_since` filters on
+    pub updated_at: DateTime<Utc>,
+    pub deleted: bool,
+    /// When a deleted entry was actually removed. Re-uploading a deletion
+    /// moves `updated_at` forward, so merges must compare against this.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl CloudEntry {
+    /// Deletion marker for an entry removed at `deleted_at`
+    pub fn tombstone(id: Uuid, user_id: &str, device_id: &str, deleted_at: DateTime<Utc>, uploaded_at: DateTime<Utc>) -> Self {
+        CloudEntry {
+            id,
+            user_id: user_id.to_string(),
+            device_id: device_id.to_string(),
+            encrypted_data: String::new(),
+            salt: String::new(),
+            updated_at: uploaded_at,
+            deleted: true,
+            deleted_at: Some(deleted_at),
+        }
+    }
+
+    /// When the entry was deleted, for deletion rows. Rows uploaded before
+    /// `deleted_at` existed only have their upload time.
+    pub fn deletion_time(&self) -> Option<DateTime<Utc>> {
+        self.deleted.then(|| self.deleted_at.unwrap_or(self.updated_at))
+    }
 }
 
 /// Sync metadata for conflict resolution
@@ -67,6 +176,13 @@ impl SupabaseClient {
             .context("SUPABASE_URL environment variable not set")?;
         let anon_key = env::var("SUPABASE_ANON_KEY")
             .context("SUPABASE_ANON_KEY environment variable not set")?;
+        let access_token = env::var("SUPABASE_ACCESS_TOKEN").ok().filter(|token| !token.is_empty());
+        
+        let max_attempts = env::var("PASSMANN_SYNC_RETRIES")
+            .ok()
+            .and_then(|value| value.parse::<u32>().ok())
+            .unwrap_or(DEFAULT_MAX_ATTEMPTS)
+            .max(1);
         
         let client = Client::new();
         
@@ -74,7 +190,9 @@ impl SupabaseClient {
             client,
             base_url,
             anon_key,
+            access_token,
             user_id: None,
+            max_attempts,
         })
     }
     
@@ -118,20 +236,24 @@ impl SupabaseClient {
         Ok(())
     }
     
-    /// Upload encrypted vault to cloud storage
+    /// Upload encrypted vault to cloud storage, replacing the row stored
+    /// for the same `vault_id`
     pub async fn upload_vault(&self, vault: &CloudVault) -> Result<Uuid> {
         let _user_id = self.user_id.as_ref()
             .context("Must authenticate before uploading vault")?;
+        vault.vault_id.context("Vault has no vault_id to upload under")?;
         
         let url = format!("{}/rest/v1/encrypted_vaults", self.base_url);
         
-        let response = self.client
+        // Upserting on the vault id makes the upload safe to retry: an
+        // attempt whose response was lost wrote the same row again
+        let response = self.send_with_retry("Vault upload", || Ok(self.client
             .post(&url)
             .headers(self.get_headers()?)
-            .json(vault)
-            .send()
-            .await
-            .context("Failed to upload vault to Supabase")?;
+            .header("Prefer", "return=representation,resolution=merge-duplicates")
+            .query(&[("on_conflict", "user_id,vault_id")])
+            .json(vault)))
+            .await?;
         
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
@@ -161,7 +283,7 @@ impl SupabaseClient {
         
         let url = format!("{}/rest/v1/encrypted_vaults", self.base_url);
         
-        let response = self.client
+        let response = self.send_with_retry("Vault download", || Ok(self.client
             .get(&url)
             .headers(self.get_headers()?)
             .query(&[
@@ -169,10 +291,8 @@ impl SupabaseClient {
                 ("device_id", format!("eq.{}", device_id)),
                 ("order", "updated_at.desc".to_string()),
                 ("limit", "1".to_string())
-            ])
-            .send()
-            .await
-            .context("Failed to download vault from Supabase")?;
+            ])))
+            .await?;
         
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
@@ -232,16 +352,14 @@ impl SupabaseClient {
         
         let url = format!("{}/rest/v1/sync_metadata", self.base_url);
         
-        let response = self.client
+        let response = self.send_with_retry("Sync metadata fetch", || Ok(self.client
             .get(&url)
             .headers(self.get_headers()?)
             .query(&[
                 ("user_id", format!("eq.{}", user_id)),
                 ("device_id", format!("eq.{}", device_id))
-            ])
-            .send()
-            .await
-            .context("Failed to get sync metadata from Supabase")?;
+            ])))
+            .await?;
         
         if !response.status().is_success() {
             return Ok(None);
@@ -322,17 +440,15 @@ impl SupabaseClient {
         let url = format!("{}/rest/v1/audit_logs", self.base_url);
         let limit_str = limit.unwrap_or(50).to_string();
         
-        let response = self.client
+        let response = self.send_with_retry("Audit logs fetch", || Ok(self.client
             .get(&url)
             .headers(self.get_headers()?)
             .query(&[
                 ("user_id", format!("eq.{}", user_id)),
                 ("order", "created_at.desc".to_string()),
-                ("limit", limit_str)
-            ])
-            .send()
-            .await
-            .context("Failed to get audit logs from Supabase")?;
+                ("limit", limit_str.clone())
+            ])))
+            .await?;
         
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
@@ -345,8 +461,153 @@ impl SupabaseClient {
         Ok(logs)
     }
     
+    /// Upload changed entries, replacing any existing row with the same id
+    pub async fn upsert_entries(&self, entries: &[CloudEntry]) -> Result<()> {
+        let _user_id = self.user_id.as_ref()
+            .context("Must authenticate before uploading entries")?;
+        
+        if entries.is_empty() {
+            return Ok(());
+        }
+        
+        let url = format!("{}/rest/v1/encrypted_entries", self.base_url);
+        
+        // Upserting on the entry id makes the upload safe to retry
+        let response = self.send_with_retry("Entry upload", || Ok(self.client
+            .post(&url)
+            .headers(self.get_headers()?)
+            .header("Prefer", "resolution=merge-duplicates")
+            .query(&[("on_conflict", "user_id,id")])
+            .json(entries)))
+            .await?;
+        
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(format!("Entry upload failed: {}", error_text).into());
+        }
+        
+        self.log_audit_action("entries_upload", true, None, Some(json!({
+            "count": entries.len()
+        }))).await?;
+        
+        Ok(())
+    }
+    
+    /// Fetch entry rows updated after `since`, or every row when `since` is `None`
+    pub async fn fetch_entries_since(&self, since: Option<DateTime<Utc>>) -> Result<Vec<CloudEntry>> {
+        let user_id = self.user_id.as_ref()
+            .context("Must authenticate before downloading entries")?;
+        
+        let url = format!("{}/rest/v1/encrypted_entries", self.base_url);
+        
+        let mut query = vec![
+            ("user_id", format!("eq.{}", user_id)),
+            ("order", "updated_at.asc".to_string()),
+        ];
+        if let Some(since) = since {
+            query.push(("updated_at", format!("gt.{}", since.to_rfc3339_opts(SecondsFormat::Micros, true))));
+        }
+        
+        let response = self.send_with_retry("Entry download", || Ok(self.client
+            .get(&url)
+            .headers(self.get_headers()?)
+            .query(&query)))
+            .await?;
+        
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(format!("Entry download failed: {}", error_text).into());
+        }
+        
+        let entries: Vec<CloudEntry> = response.json().await
+            .context("Failed to parse entry download response")?;
+        
+        Ok(entries)
+    }
+    
+    /// Whether this user has any per-entry rows yet, i.e. has migrated off the single blob
+    pub async fn has_entry_rows(&self) -> Result<bool> {
+        let user_id = self.user_id.as_ref()
+            .context("Must authenticate before checking entries")?;
+        
+        let url = format!("{}/rest/v1/encrypted_entries", self.base_url);
+        
+        let response = self.send_with_retry("Entry lookup", || Ok(self.client
+            .get(&url)
+            .headers(self.get_headers()?)
+            .query(&[
+                ("select", "id".to_string()),
+                ("user_id", format!("eq.{}", user_id)),
+                ("limit", "1".to_string())
+            ])))
+            .await?;
+        
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(format!("Entry lookup failed: {}", error_text).into());
+        }
+        
+        let rows: Vec<Value> = response.json().await
+            .context("Failed to parse entry lookup response")?;
+        
+        Ok(!rows.is_empty())
+    }
+    
+    /// List devices that have synced a vault, most recently updated first
+    pub async fn list_devices(&self) -> Result<Vec<DeviceInfo>> {
+        #[derive(Deserialize)]
+        struct DeviceVaultRow {
+            id: Uuid,
+            device_id: String,
+            device_name: String,
+            version: i32,
+            updated_at: Option<DateTime<Utc>>,
+            size_bytes: i64,
+        }
+        
+        let user_id = self.user_id.as_ref()
+            .context("Must authenticate before listing devices")?;
+        
+        let url = format!("{}/rest/v1/encrypted_vaults", self.base_url);
+        
+        let response = self.send_with_retry("Device listing", || Ok(self.client
+            .get(&url)
+            .headers(self.get_headers()?)
+            .query(&[
+                ("select", "id,device_id,device_name,version,updated_at,size_bytes".to_string()),
+                ("user_id", format!("eq.{}", user_id)),
+                ("order", "updated_at.desc".to_string())
+            ])))
+            .await?;
+        
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(format!("Device listing failed: {}", error_text).into());
+        }
+        
+        let rows: Vec<DeviceVaultRow> = response.json().await
+            .context("Failed to parse device listing response")?;
+        
+        // Rows are newest first, so the first row seen for a device describes it
+        let mut devices: Vec<DeviceInfo> = Vec::new();
+        for row in rows {
+            match devices.iter_mut().find(|d| d.device_id == row.device_id) {
+                Some(device) => device.vault_ids.push(row.id),
+                None => devices.push(DeviceInfo {
+                    device_id: row.device_id,
+                    device_name: row.device_name,
+                    version: row.version,
+                    updated_at: row.updated_at,
+                    size_bytes: row.size_bytes,
+                    vault_ids: vec![row.id],
+                }),
+            }
+        }
+        
+        Ok(devices)
+    }
+    
     /// Delete vault from cloud storage
-    #[allow(dead_code)]
     pub async fn delete_vault(&self, vault_id: Uuid) -> Result<()> {
         let url = format!("{}/rest/v1/encrypted_vaults", self.base_url);
         
@@ -371,13 +632,73 @@ impl SupabaseClient {
         Ok(())
     }
     
+    /// Send an idempotent request, retrying transient failures with exponential backoff
+    async fn send_with_retry<F>(&self, operation: &str, build: F) -> Result<Response>
+    where
+        F: Fn() -> Result<RequestBuilder>,
+    {
+        let mut attempt = 1;
+        loop {
+            match Self::try_send(build()?).await {
+                Ok(response) => return Ok(response),
+                Err(failure) => self.wait_before_retry(operation, attempt, failure).await?,
+            }
+            attempt += 1;
+        }
+    }
+    
+    /// Send a request once. Non-transient error statuses are returned as a normal response.
+    async fn try_send(request: RequestBuilder) -> std::result::Result<Response, TransientFailure> {
+        let response = request.send().await.map_err(|e| TransientFailure {
+            message: e.to_string(),
+            retry_after: None,
+        })?;
+        
+        let status = response.status();
+        if !status.is_server_error() && status != StatusCode::TOO_MANY_REQUESTS {
+            return Ok(response);
+        }
+        
+        let retry_after = response.headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .map(|secs| Duration::from_secs(secs).min(MAX_RETRY_DELAY));
+        let body = response.text().await.unwrap_or_default();
+        
+        Err(TransientFailure {
+            message: format!("HTTP {}: {}", status, body),
+            retry_after,
+        })
+    }
+    
+    /// Sleep before the next attempt, or give up once all attempts are used
+    async fn wait_before_retry(&self, operation: &str, attempt: u32, failure: TransientFailure) -> Result<()> {
+        if attempt >= self.max_attempts {
+            return Err(RetryExhaustedError {
+                operation: operation.to_string(),
+                attempts: attempt,
+                last_error: failure.message,
+            }.into());
+        }
+        
+        let delay = failure.retry_after.unwrap_or_else(|| backoff_delay(attempt));
+        log::warn!(
+            "{} failed (attempt {}/{}): {}. Retrying in {:?}",
+            operation, attempt, self.max_attempts, failure.message, delay
+        );
+        tokio::time::sleep(delay).await;
+        
+        Ok(())
+    }
+    
     /// Generate HTTP headers for Supabase API requests
     fn get_headers(&self) -> Result<HeaderMap> {
         let mut headers = HeaderMap::new();
         
         headers.insert(
             AUTHORIZATION,
-            HeaderValue::from_str(&format!("Bearer {}", self.anon_key))
+            HeaderValue::from_str(&format!("Bearer {}", self.access_token.as_deref().unwrap_or(&self.anon_key)))
                 .context("Invalid authorization header")?
         );
         
@@ -395,3 +716,283 @@ impl SupabaseClient {
         Ok(headers)
     }
 }
+
+/// Check data against a hex BLAKE3 checksum. `blake3::Hash` compares in constant time.
+pub fn verify_checksum(expected_hex: &str, data: &[u8]) -> bool {
+    blake3::Hash::from_hex(expected_hex)
+        .map(|expected| expected == blake3::hash(data))
+        .unwrap_or(false)
+}
+
+/// Gzip-compress vault plaintext before it is encrypted for upload
+pub fn compress_payload(data: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+pub fn decompress_payload(data: &[u8]) -> Result<Vec<u8>> {
+    let mut decompressed = Vec::new();
+    GzDecoder::new(data)
+        .read_to_end(&mut decompressed)
+        .context("Failed to decompress cloud vault")?;
+    Ok(decompressed)
+}
+
+/// Whether a decrypted payload starts with the gzip magic bytes
+pub fn is_compressed(data: &[u8]) -> bool {
+    data.starts_with(&[0x1f, 0x8b])
+}
+
+/// Exponential backoff with up to 50% random jitter
+fn backoff_delay(attempt: u32) -> Duration {
+    let exponential = BASE_RETRY_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+        .min(MAX_RETRY_DELAY);
+    
+    let mut bytes = [0u8; 8];
+    let jitter_ms = match getrandom::getrandom(&mut bytes) {
+        Ok(()) => u64::from_le_bytes(bytes) % (exponential.as_millis() as u64 / 2 + 1),
+        Err(_) => 0,
+    };
+    
+    exponential + Duration::from_millis(jitter_ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merge;
+    use passmann_shared::Entry;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn test_tombstone_keeps_deletion_time_and_loses_to_newer_edit() {
+        let deleted_at = Utc::now() - chrono::Duration::hours(2);
+        let mut edited = Entry::new("github".to_string(), "me".to_string(), "rotated".to_string());
+        edited.modified_at = deleted_at + chrono::Duration::hours(1);
+
+        // Uploaded by a later sync, then read back by the other device
+        let row = CloudEntry::tombstone(edited.id, "user", "laptop", deleted_at, Utc::now());
+        let row: CloudEntry = serde_json::from_value(serde_json::to_value(&row).unwrap()).unwrap();
+        assert_eq!(row.deletion_time(), Some(deleted_at));
+
+        let local_tombstones = merge::Tombstones::from([(edited.id, row.deletion_time().unwrap())]);
+        let none = merge::Tombstones::new();
+        let result = merge::merge(
+            merge::MergeSide { entries: &[], tombstones: &local_tombstones },
+            merge::MergeSide { entries: std::slice::from_ref(&edited), tombstones: &none },
+        );
+        assert_eq!(result.entries.len(), 1);
+        assert!(result.tombstones.is_empty());
+
+        // Rows from before `deleted_at` fall back to the upload time
+        let legacy = CloudEntry { deleted_at: None, ..row };
+        assert_eq!(legacy.deletion_time(), Some(legacy.updated_at));
+    }
+
+    #[test]
+    fn test_compress_payload_round_trip() {
+        let entries: Vec<Value> = (0..500)
+            .map(|i| json!({
+                "id": Uuid::new_v4(),
+                "service": format!("service-{}", i),
+                "username": "user@example.com",
+                "password": "correct-horse-battery-staple",
+                "tags": ["work", "email"],
+            }))
+            .collect();
+        let vault_json = serde_json::to_vec(&json!({ "entries": entries })).unwrap();
+
+        let compressed = compress_payload(&vault_json).unwrap();
+        assert!(is_compressed(&compressed));
+        assert!(compressed.len() * 3 < vault_json.len(), "expected at least 3x compression");
+        assert_eq!(decompress_payload(&compressed).unwrap(), vault_json);
+
+        // Plain JSON from older uploads is never mistaken for compressed data
+        assert!(!is_compressed(&vault_json));
+    }
+
+    /// Just enough of PostgREST for vault uploads: inserts that honour
+    /// `on_conflict` and `Prefer: resolution=...`, `eq.` filters on reads, and
+    /// audit log writes. With `drop_first_upload` the first vault insert is
+    /// stored but its connection closed before any response is sent.
+    async fn mock_rest(drop_first_upload: bool) -> (String, Arc<Mutex<Vec<Value>>>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let rows = Arc::new(Mutex::new(Vec::new()));
+        let table = rows.clone();
+
+        tokio::spawn(async move {
+            let mut drop_next_upload = drop_first_upload;
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let (method, target, headers, body) = read_request(&mut socket).await;
+                if !["authorization: bearer user-token", "authorization: bearer anon"].iter().any(|auth| headers.contains(auth)) {
+                    let response = "HTTP/1.1 401 Unauthorized\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+                    socket.write_all(response.as_bytes()).await.unwrap();
+                    continue;
+                }
+                let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+                let params: Vec<(String, String)> = query.split('&')
+                    .filter_map(|pair| pair.split_once('='))
+                    .map(|(key, value)| (key.to_string(), value.replace("%2C", ",")))
+                    .collect();
+
+                let reply = match (method.as_str(), path) {
+                    ("POST", "/rest/v1/encrypted_vaults") => {
+                        let mut row: Value = serde_json::from_slice(&body).unwrap();
+                        let conflict = params.iter().find(|(key, _)| key == "on_conflict").map(|(_, cols)| cols.clone()).unwrap_or_default();
+                        let mut rows = table.lock().unwrap();
+                        let existing = rows.iter().position(|stored: &Value| {
+                            !conflict.is_empty() && conflict.split(',').all(|col| stored[col] == row[col])
+                        });
+                        let written = match existing {
+                            Some(_) if headers.contains("resolution=ignore-duplicates") => None,
+                            Some(index) => {
+                                row["id"] = rows[index]["id"].clone();
+                                rows[index] = row.clone();
+                                Some(row)
+                            }
+                            None => {
+                                row["id"] = json!(Uuid::new_v4());
+                                rows.push(row.clone());
+                                Some(row)
+                            }
+                        };
+                        if std::mem::take(&mut drop_next_upload) {
+                            continue;
+                        }
+                        json!(written.into_iter().collect::<Vec<_>>())
+                    }
+                    ("GET", "/rest/v1/encrypted_vaults") => {
+                        let filters: Vec<(String, String)> = params.iter()
+                            .filter_map(|(key, value)| value.strip_prefix("eq.").map(|value| (key.clone(), value.to_string())))
+                            .collect();
+                        let rows = table.lock().unwrap();
+                        json!(rows.iter()
+                            .filter(|row| filters.iter().all(|(col, value)| row[col.as_str()].as_str() == Some(value)))
+                            .collect::<Vec<_>>())
+                    }
+                    ("POST", "/rest/v1/audit_logs") => json!([]),
+                    _ => panic!("unexpected request {} {}", method, target),
+                };
+                let body = reply.to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(), body
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (url, rows)
+    }
+
+    /// Method, target, lowercased headers and body of one HTTP/1.1 request
+    async fn read_request(socket: &mut tokio::net::TcpStream) -> (String, String, String, Vec<u8>) {
+        let mut data = Vec::new();
+        let mut buf = [0u8; 4096];
+        let header_end = loop {
+            let read = socket.read(&mut buf).await.unwrap();
+            data.extend_from_slice(&buf[..read]);
+            if let Some(end) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+                break end + 4;
+            }
+        };
+        let head = String::from_utf8_lossy(&data[..header_end]).to_lowercase();
+        let length: usize = head.lines()
+            .find_map(|line| line.strip_prefix("content-length:"))
+            .map_or(0, |value| value.trim().parse().unwrap());
+        while data.len() < header_end + length {
+            let read = socket.read(&mut buf).await.unwrap();
+            data.extend_from_slice(&buf[..read]);
+        }
+        let request_line = String::from_utf8_lossy(&data[..header_end]).lines().next().unwrap().to_string();
+        let mut parts = request_line.split(' ');
+        let (method, target) = (parts.next().unwrap().to_string(), parts.next().unwrap().to_string());
+        (method, target, head, data[header_end..header_end + length].to_vec())
+    }
+
+    fn mock_client(base_url: String) -> SupabaseClient {
+        SupabaseClient {
+            client: Client::new(),
+            base_url,
+            anon_key: "anon".to_string(),
+            access_token: Some("user-token".to_string()),
+            user_id: Some("user".to_string()),
+            max_attempts: 3,
+        }
+    }
+
+    fn cloud_vault(vault_id: Uuid, encrypted_data: &str) -> CloudVault {
+        CloudVault {
+            id: None,
+            user_id: "user".to_string(),
+            encrypted_data: encrypted_data.to_string(),
+            salt: "salt".to_string(),
+            device_id: "laptop".to_string(),
+            device_name: "Laptop".to_string(),
+            version: 1,
+            created_at: None,
+            updated_at: None,
+            checksum: blake3::hash(encrypted_data.as_bytes()).to_hex().to_string(),
+            compression_enabled: false,
+            size_bytes: encrypted_data.len() as i64,
+            vault_id: Some(vault_id),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_upload_retried_after_lost_response_writes_one_row() {
+        let (url, rows) = mock_rest(true).await;
+        let client = mock_client(url);
+
+        let vault = cloud_vault(Uuid::new_v4(), "ciphertext");
+        let row_id = client.upload_vault(&vault).await.unwrap();
+
+        let rows = rows.lock().unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0]["id"], json!(row_id));
+        assert_eq!(rows[0]["vault_id"], json!(vault.vault_id));
+    }
+
+    #[tokio::test]
+    async fn test_uploads_replace_the_vaults_row() {
+        let (url, rows) = mock_rest(false).await;
+        let client = mock_client(url);
+        let (vault_id, other_vault_id) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let first = client.upload_vault(&cloud_vault(vault_id, "first")).await.unwrap();
+        let second = client.upload_vault(&cloud_vault(vault_id, "second")).await.unwrap();
+        assert_eq!(first, second);
+        client.upload_vault(&cloud_vault(other_vault_id, "other")).await.unwrap();
+
+        let rows = rows.lock().unwrap();
+        let stored: Vec<&str> = rows.iter().map(|row| row["encrypted_data"].as_str().unwrap()).collect();
+        assert_eq!(stored, ["second", "other"]);
+    }
+
+    #[tokio::test]
+    async fn test_upload_authorises_with_the_session_or_the_anon_key() {
+        let (url, rows) = mock_rest(false).await;
+
+        let expired = SupabaseClient { access_token: Some("expired".to_string()), ..mock_client(url.clone()) };
+        assert!(expired.upload_vault(&cloud_vault(Uuid::new_v4(), "ciphertext")).await.is_err());
+        assert!(rows.lock().unwrap().is_empty());
+
+        let anon = SupabaseClient { access_token: None, ..mock_client(url) };
+        anon.upload_vault(&cloud_vault(Uuid::new_v4(), "ciphertext")).await.unwrap();
+        assert_eq!(rows.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_verify_checksum_detects_tampering() {
+        let payload = b"encrypted vault payload";
+        let checksum = blake3::hash(payload).to_hex().to_string();
+
+        assert!(verify_checksum(&checksum, payload));
+        assert!(!verify_checksum(&checksum, b"encrypted vault pay1oad"));
+        assert!(!verify_checksum("not-a-checksum", payload));
+    }
+}
@@ -0,0 +1,27 @@
+use clap::CommandFactory;
+use clap_complete::{generate, Shell};
+use std::io::Write;
+
+/// Name completions are registered for, matching the `passmann ...` usage hints
+const BIN_NAME: &str = "passmann";
+
+/// Write the completion script for `shell`
+pub fn write_completions(shell: Shell, out: &mut dyn Write) {
+    let mut command = crate::Cli::command();
+    generate(shell, &mut command, BIN_NAME, out);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::ValueEnum;
+
+    #[test]
+    fn test_completions_generate_for_every_shell() {
+        for shell in Shell::value_variants() {
+            let mut script = Vec::new();
+            write_completions(*shell, &mut script);
+            assert!(!script.is_empty(), "empty completions for {}", shell);
+        }
+    }
+}
@@ -31,6 +31,15 @@ impl Default for DbConfig {
     }
 }
 
+/// Result of re-decrypting every stored entry
+#[derive(Debug, Default)]
+pub struct EntryVerifyReport {
+    /// Number of entries that decrypted and parsed successfully
+    pub verified: usize,
+    /// Ids of entries that failed to decrypt or parse
+    pub failed: Vec<String>,
+}
+
 impl SecureDb {
     /// Open a database with proper SQLCipher encryption or fallback to application-level encryption
     pub fn open(path: &Path, master_password: &str) -> Result<Self, PassMannError> {
@@ -153,6 +162,10 @@ impl SecureDb {
         conn.execute_batch("CREATE TEMP TABLE test_encryption (x INTEGER); DROP TABLE test_encryption;")
             .map_err(|e| PassMannError::Other(format!("SQLCipher encryption test failed: {}", e)))?;
         
+        // Overwrite deleted entry data instead of leaving it in free pages
+        conn.pragma_update(None, "secure_delete", "ON")
+            .map_err(|e| PassMannError::Other(format!("Failed to enable secure delete: {}", e)))?;
+        
         info!("SQLCipher configured successfully with {} KDF iterations", config.kdf_iterations);
         Ok(())
     }
@@ -221,7 +234,8 @@ impl SecureDb {
         let entry_json = serde_json::to_string(entry)
             .map_err(|e| PassMannError::Serialization(e))?;
         
-        let now = chrono::Utc::now().timestamp();
+        let updated_at = entry.modified_at.timestamp();
+        let accessed_at = entry.last_accessed.map(|t| t.timestamp());
         
         if self.sqlcipher {
             self.conn.execute(
@@ -234,8 +248,8 @@ impl SecureDb {
                     entry_json.as_bytes(),
                     "{}".to_string(), // Empty metadata for now
                     entry.created_at.timestamp(),
-                    now,
-                    None::<i64> // Remove accessed_at field reference
+                    updated_at,
+                    accessed_at
                 ],
             ).map_err(|e| PassMannError::Other(format!("Failed to store entry: {}", e)))?;
         } else {
@@ -255,8 +269,8 @@ impl SecureDb {
                     encrypted_data,
                     salt,
                     entry.created_at.timestamp(),
-                    now,
-                    None::<i64> // Remove accessed_at field reference
+                    updated_at,
+                    accessed_at
                 ],
             ).map_err(|e| PassMannError::Other(format!("Failed to store encrypted entry: {}", e)))?;
         }
@@ -264,8 +278,19 @@ impl SecureDb {
         Ok(())
     }
 
-    /// Retrieve a vault entry by ID
+    /// Retrieve a vault entry by ID, recording the access time
     pub fn get_entry(&self, id: &str, master_password: &str) -> Result<Option<passmann_shared::Entry>, PassMannError> {
+        let entry = self.read_entry(id, master_password)?;
+        match entry {
+            Some(mut entry) => {
+                entry.last_accessed = Some(self.touch_entry(id)?);
+                Ok(Some(entry))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn read_entry(&self, id: &str, master_password: &str) -> Result<Option<passmann_shared::Entry>, PassMannError> {
         if self.sqlcipher {
             let result: Option<Vec<u8>> = self.conn.query_row(
                 "SELECT password_data FROM vault_entries WHERE id = ?1",
@@ -303,6 +328,18 @@ impl SecureDb {
         }
     }
 
+    /// Record that an entry was just read. Returns the recorded time.
+    pub fn touch_entry(&self, id: &str) -> Result<chrono::DateTime<chrono::Utc>, PassMannError> {
+        let table = if self.sqlcipher { "vault_entries" } else { "encrypted_vault_entries" };
+        let now = chrono::Utc::now();
+        self.conn.execute(
+            &format!("UPDATE {} SET accessed_at = ?1 WHERE id = ?2", table),
+            params![now.timestamp(), id],
+        ).map_err(|e| PassMannError::Other(format!("Failed to update access time: {}", e)))?;
+        
+        Ok(now)
+    }
+
     /// List all vault entries
     pub fn list_entries(&self, master_password: &str) -> Result<Vec<passmann_shared::Entry>, PassMannError> {
         let mut entries = Vec::new();
@@ -362,7 +399,7 @@ impl SecureDb {
         Ok(rows_affected > 0)
     }
 
-    /// Search entries by service or username
+    /// Search entries by service or username, recording the access time of each match
     pub fn search_entries(&self, query: &str, master_password: &str) -> Result<Vec<passmann_shared::Entry>, PassMannError> {
         let mut entries = Vec::new();
         let search_pattern = format!("%{}%", query);
@@ -408,9 +445,239 @@ impl SecureDb {
             }
         }
         
+        for entry in &mut entries {
+            entry.last_accessed = Some(self.touch_entry(&entry.id.to_string())?);
+        }
+        
+        Ok(entries)
+    }
+
+    /// Re-decrypt and parse every stored entry without modifying anything.
+    /// Failures are collected per entry instead of aborting on the first one.
+    pub fn verify_entries(&self, master_password: &str) -> Result<EntryVerifyReport, PassMannError> {
+        let mut report = EntryVerifyReport::default();
+
+        if self.sqlcipher {
+            let mut stmt = self.conn.prepare(
+                "SELECT id, password_data FROM vault_entries ORDER BY service, username"
+            ).map_err(|e| PassMannError::Other(format!("Failed to prepare statement: {}", e)))?;
+
+            let rows = stmt.query_map([], |row| {
+                let id: String = row.get(0)?;
+                let data: Vec<u8> = row.get(1)?;
+                Ok((id, data))
+            }).map_err(|e| PassMannError::Other(format!("Failed to query entries: {}", e)))?;
+
+            for row in rows {
+                let (id, data) = row.map_err(|e| PassMannError::Other(format!("Failed to read entry: {}", e)))?;
+                match Self::parse_entry(data) {
+                    Some(_) => report.verified += 1,
+                    None => report.failed.push(id),
+                }
+            }
+        } else {
+            let mut stmt = self.conn.prepare(
+                "SELECT id, encrypted_data, salt FROM encrypted_vault_entries ORDER BY service, username"
+            ).map_err(|e| PassMannError::Other(format!("Failed to prepare statement: {}", e)))?;
+
+            let rows = stmt.query_map([], |row| {
+                let id: String = row.get(0)?;
+                let encrypted_data: Vec<u8> = row.get(1)?;
+                let salt: Vec<u8> = row.get(2)?;
+                Ok((id, encrypted_data, salt))
+            }).map_err(|e| PassMannError::Other(format!("Failed to query encrypted entries: {}", e)))?;
+
+            for row in rows {
+                let (id, encrypted_data, salt) = row.map_err(|e| PassMannError::Other(format!("Failed to read encrypted entry: {}", e)))?;
+                let derived = derive_key(master_password, &salt);
+                match decrypt(&derived, &encrypted_data).ok().and_then(Self::parse_entry) {
+                    Some(_) => report.verified += 1,
+                    None => report.failed.push(id),
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    fn parse_entry(data: Vec<u8>) -> Option<passmann_shared::Entry> {
+        let entry_json = String::from_utf8(data).ok()?;
+        serde_json::from_str(&entry_json).ok()
+    }
+
+    /// Move entries written under the other encryption mode into this mode's table.
+    ///
+    /// Runs in a single transaction: the old table is only dropped once every entry
+    /// has been re-stored and the counts match. Returns the number of entries moved.
+    pub fn migrate_encryption(&self, master_password: &str) -> Result<usize, PassMannError> {
+        let (source, target) = if self.sqlcipher {
+            ("encrypted_vault_entries", "vault_entries")
+        } else {
+            ("vault_entries", "encrypted_vault_entries")
+        };
+        
+        let source_exists: bool = self.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = ?1",
+            params![source],
+            |r| r.get(0),
+        ).map_err(|e| PassMannError::Other(format!("Failed to inspect schema: {}", e)))?;
+        if !source_exists {
+            return Ok(0);
+        }
+        
+        let tx = self.conn.unchecked_transaction()
+            .map_err(|e| PassMannError::Other(format!("Failed to start migration: {}", e)))?;
+        
+        let entries = self.read_source_entries(source, master_password)?;
+        for entry in &entries {
+            self.store_entry(entry, master_password)?;
+        }
+        
+        let source_count: usize = self.conn.query_row(
+            &format!("SELECT COUNT(*) FROM {}", source),
+            [],
+            |r| r.get(0),
+        ).map_err(|e| PassMannError::Other(format!("Failed to count entries: {}", e)))?;
+        
+        let mut migrated = 0;
+        for entry in &entries {
+            let exists: bool = self.conn.query_row(
+                &format!("SELECT COUNT(*) > 0 FROM {} WHERE id = ?1", target),
+                params![entry.id.to_string()],
+                |r| r.get(0),
+            ).map_err(|e| PassMannError::Other(format!("Failed to verify migration: {}", e)))?;
+            if exists {
+                migrated += 1;
+            }
+        }
+        
+        if migrated != source_count {
+            // Dropping tx rolls everything back
+            return Err(PassMannError::Other(format!(
+                "Migration incomplete: {} of {} entries copied, old table kept", migrated, source_count
+            )));
+        }
+        
+        self.conn.execute_batch(&format!("DROP TABLE {}", source))
+            .map_err(|e| PassMannError::Other(format!("Failed to drop {}: {}", source, e)))?;
+        tx.commit()
+            .map_err(|e| PassMannError::Other(format!("Failed to commit migration: {}", e)))?;
+        
+        info!("Migrated {} entries from {} to {}", migrated, source, target);
+        Ok(migrated)
+    }
+
+    /// Encrypt a database file written without SQLCipher and move its entries
+    /// into the SQLCipher table.
+    ///
+    /// A connection with the key set can't read a plaintext file, so it is
+    /// attached with an empty key and copied into a new encrypted file with
+    /// `sqlcipher_export`, which then replaces the original. Returns the number
+    /// of entries moved: 0 when the file is already encrypted or SQLCipher is
+    /// not available to encrypt it.
+    pub fn migrate_plaintext_file(path: &Path, master_password: &str) -> Result<usize, PassMannError> {
+        if !Self::is_plaintext_db(path)? {
+            return Ok(0);
+        }
+        
+        let mut encrypted_name = path.as_os_str().to_owned();
+        encrypted_name.push(".encrypting");
+        let encrypted_path = std::path::PathBuf::from(encrypted_name);
+        let _ = std::fs::remove_file(&encrypted_path);
+        
+        match Self::export_encrypted(path, &encrypted_path, master_password) {
+            Ok(true) => {}
+            Ok(false) => {
+                let _ = std::fs::remove_file(&encrypted_path);
+                return Ok(0);
+            }
+            Err(e) => {
+                let _ = std::fs::remove_file(&encrypted_path);
+                return Err(e);
+            }
+        }
+        std::fs::rename(&encrypted_path, path)
+            .map_err(|e| PassMannError::Other(format!("Failed to replace {}: {}", path.display(), e)))?;
+        
+        info!("Encrypted {} with SQLCipher", path.display());
+        Self::open(path, master_password)?.migrate_encryption(master_password)
+    }
+
+    /// Whether the file starts with the header of an unencrypted SQLite database
+    fn is_plaintext_db(path: &Path) -> Result<bool, PassMannError> {
+        use std::io::Read;
+        
+        let mut header = [0u8; 16];
+        let mut file = std::fs::File::open(path)
+            .map_err(|e| PassMannError::Other(format!("Failed to open {}: {}", path.display(), e)))?;
+        Ok(file.read_exact(&mut header).is_ok() && &header == b"SQLite format 3\0")
+    }
+
+    /// Copy the plaintext database at `source` into a new SQLCipher file at
+    /// `target`. Returns false when SQLCipher is not available.
+    fn export_encrypted(source: &Path, target: &Path, master_password: &str) -> Result<bool, PassMannError> {
+        let conn = Connection::open(target)
+            .map_err(|e| PassMannError::Other(format!("Failed to create {}: {}", target.display(), e)))?;
+        if !Self::detect_sqlcipher(&conn) {
+            return Ok(false);
+        }
+        Self::setup_sqlcipher(&conn, master_password, &DbConfig::default())?;
+        
+        conn.execute("ATTACH DATABASE ?1 AS plaintext KEY ''", params![source.to_string_lossy()])
+            .map_err(|e| PassMannError::Other(format!("Failed to attach {}: {}", source.display(), e)))?;
+        conn.query_row("SELECT sqlcipher_export('main', 'plaintext')", [], |_| Ok(()))
+            .map_err(|e| PassMannError::Other(format!("Failed to encrypt {}: {}", source.display(), e)))?;
+        conn.execute_batch("DETACH DATABASE plaintext;")
+            .map_err(|e| PassMannError::Other(format!("Failed to detach {}: {}", source.display(), e)))?;
+        Ok(true)
+    }
+
+    /// Read every entry from a table in either storage layout, keeping access times
+    fn read_source_entries(&self, table: &str, master_password: &str) -> Result<Vec<passmann_shared::Entry>, PassMannError> {
+        let encrypted = table == "encrypted_vault_entries";
+        let sql = if encrypted {
+            "SELECT encrypted_data, salt, accessed_at FROM encrypted_vault_entries"
+        } else {
+            "SELECT password_data, NULL, accessed_at FROM vault_entries"
+        };
+        
+        let mut stmt = self.conn.prepare(sql)
+            .map_err(|e| PassMannError::Other(format!("Failed to prepare statement: {}", e)))?;
+        let rows = stmt.query_map([], |row| {
+            let data: Vec<u8> = row.get(0)?;
+            let salt: Option<Vec<u8>> = row.get(1)?;
+            let accessed_at: Option<i64> = row.get(2)?;
+            Ok((data, salt, accessed_at))
+        }).map_err(|e| PassMannError::Other(format!("Failed to query {}: {}", table, e)))?;
+        
+        let mut entries = Vec::new();
+        for row in rows {
+            let (data, salt, accessed_at) = row.map_err(|e| PassMannError::Other(format!("Failed to read entry: {}", e)))?;
+            let data = match salt {
+                Some(salt) => decrypt(&derive_key(master_password, &salt), &data)
+                    .map_err(|e| PassMannError::Crypto(format!("Failed to decrypt entry: {}", e)))?,
+                None => data,
+            };
+            let mut entry = Self::parse_entry(data)
+                .ok_or_else(|| PassMannError::Other(format!("Unreadable entry in {}", table)))?;
+            entry.last_accessed = accessed_at.and_then(|t| chrono::DateTime::from_timestamp(t, 0));
+            entries.push(entry);
+        }
+        
         Ok(entries)
     }
 
+    /// Rebuild the database file so space freed by deleted entries is released.
+    /// `secure_delete` is switched on first so the freed pages are zeroed too.
+    pub fn compact(&self) -> Result<(), PassMannError> {
+        self.conn.pragma_update(None, "secure_delete", "ON")
+            .map_err(|e| PassMannError::Other(format!("Failed to enable secure delete: {}", e)))?;
+        self.conn.execute_batch("VACUUM;")
+            .map_err(|e| PassMannError::Other(format!("Failed to compact database: {}", e)))?;
+        
+        Ok(())
+    }
+
     /// Get database statistics
     pub fn get_stats(&self) -> Result<(usize, bool), PassMannError> {
         let table = if self.sqlcipher { "vault_entries" } else { "encrypted_vault_entries" };
@@ -567,4 +834,223 @@ mod tests {
 
         let _ = fs::remove_file(&fname);
     }
+
+    #[test]
+    fn test_securedb_reads_update_accessed_at() {
+        let tmp = env::temp_dir();
+        let fname = tmp.join(format!("passmann_test_accessed_{}.db", Uuid::new_v4()));
+        let master = "test_master_password";
+        let _ = fs::remove_file(&fname);
+
+        let db = SecureDb::open(&fname, master).expect("open db");
+        let entry = passmann_shared::Entry::new(
+            "accessed_service".to_string(),
+            "user".to_string(),
+            "password".to_string(),
+        );
+        db.store_entry(&entry, master).expect("store entry");
+
+        let table = if db.sqlcipher { "vault_entries" } else { "encrypted_vault_entries" };
+        let accessed_at = || -> Option<i64> {
+            db.conn.query_row(
+                &format!("SELECT accessed_at FROM {} WHERE id = ?1", table),
+                params![entry.id.to_string()],
+                |r| r.get(0),
+            ).expect("query accessed_at")
+        };
+        assert_eq!(accessed_at(), None);
+
+        let first = db.get_entry(&entry.id.to_string(), master).expect("get").expect("exists");
+        let first_access = accessed_at().expect("accessed_at set on read");
+        assert_eq!(first.last_accessed.map(|t| t.timestamp()), Some(first_access));
+
+        // accessed_at has second resolution
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+        let found = db.search_entries("accessed_service", master).expect("search");
+        assert_eq!(found.len(), 1);
+        let second_access = accessed_at().expect("accessed_at set on search");
+        assert!(second_access > first_access);
+
+        let _ = fs::remove_file(&fname);
+    }
+
+    #[test]
+    fn test_securedb_migrate_encryption_moves_other_mode_entries() {
+        let tmp = env::temp_dir();
+        let fname = tmp.join(format!("passmann_test_migrate_{}.db", Uuid::new_v4()));
+        let master = "test_master_password";
+        let _ = fs::remove_file(&fname);
+
+        let db = SecureDb::open(&fname, master).expect("open db");
+        assert_eq!(db.migrate_encryption(master).expect("nothing to migrate"), 0);
+
+        // Write entries the way the other encryption mode would have
+        let entries: Vec<_> = ["alpha", "beta"].iter()
+            .map(|s| passmann_shared::Entry::new(s.to_string(), "user".to_string(), "pw".to_string()))
+            .collect();
+        let source = if db.sqlcipher {
+            db.conn.execute_batch(
+                "CREATE TABLE encrypted_vault_entries (id TEXT PRIMARY KEY, service TEXT NOT NULL, username TEXT NOT NULL,
+                 encrypted_data BLOB NOT NULL, salt BLOB NOT NULL, created_at INTEGER NOT NULL, updated_at INTEGER NOT NULL, accessed_at INTEGER);"
+            ).expect("create legacy table");
+            for entry in &entries {
+                let salt = vec![7u8; 32];
+                let data = encrypt(&derive_key(master, &salt), serde_json::to_string(entry).unwrap().as_bytes());
+                db.conn.execute(
+                    "INSERT INTO encrypted_vault_entries VALUES (?1, ?2, ?3, ?4, ?5, 0, 0, NULL)",
+                    params![entry.id.to_string(), entry.service, entry.username, data, salt],
+                ).expect("insert legacy entry");
+            }
+            "encrypted_vault_entries"
+        } else {
+            db.conn.execute_batch(
+                "CREATE TABLE vault_entries (id TEXT PRIMARY KEY, service TEXT NOT NULL, username TEXT NOT NULL,
+                 password_data BLOB NOT NULL, metadata TEXT, created_at INTEGER NOT NULL, updated_at INTEGER NOT NULL, accessed_at INTEGER);"
+            ).expect("create legacy table");
+            for entry in &entries {
+                db.conn.execute(
+                    "INSERT INTO vault_entries VALUES (?1, ?2, ?3, ?4, '{}', 0, 0, NULL)",
+                    params![entry.id.to_string(), entry.service, entry.username, serde_json::to_string(entry).unwrap().as_bytes()],
+                ).expect("insert legacy entry");
+            }
+            "vault_entries"
+        };
+
+        assert_eq!(db.migrate_encryption(master).expect("migrate"), 2);
+        let listed = db.list_entries(master).expect("list entries");
+        assert_eq!(listed.len(), 2);
+
+        let source_left: bool = db.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = ?1",
+            params![source],
+            |r| r.get(0),
+        ).expect("query schema");
+        assert!(!source_left);
+
+        let _ = fs::remove_file(&fname);
+    }
+
+    #[test]
+    fn test_securedb_migrate_plaintext_file_encrypts_it() {
+        let tmp = env::temp_dir();
+        let fname = tmp.join(format!("passmann_test_plaintext_{}.db", Uuid::new_v4()));
+        let master = "test_master_password";
+        let _ = fs::remove_file(&fname);
+
+        // A database written by a build without SQLCipher: no key, entries
+        // encrypted by the application
+        let entries: Vec<_> = ["alpha", "beta"].iter()
+            .map(|s| passmann_shared::Entry::new(s.to_string(), "user".to_string(), "pw".to_string()))
+            .collect();
+        {
+            let conn = Connection::open(&fname).expect("create plaintext db");
+            conn.execute_batch(
+                "CREATE TABLE encrypted_vault_entries (id TEXT PRIMARY KEY, service TEXT NOT NULL, username TEXT NOT NULL,
+                 encrypted_data BLOB NOT NULL, salt BLOB NOT NULL, created_at INTEGER NOT NULL, updated_at INTEGER NOT NULL, accessed_at INTEGER);"
+            ).expect("create legacy table");
+            for entry in &entries {
+                let salt = vec![7u8; 32];
+                let data = encrypt(&derive_key(master, &salt), serde_json::to_string(entry).unwrap().as_bytes());
+                conn.execute(
+                    "INSERT INTO encrypted_vault_entries VALUES (?1, ?2, ?3, ?4, ?5, 0, 0, NULL)",
+                    params![entry.id.to_string(), entry.service, entry.username, data, salt],
+                ).expect("insert legacy entry");
+            }
+        }
+        assert!(SecureDb::is_plaintext_db(&fname).unwrap());
+
+        let migrated = SecureDb::migrate_plaintext_file(&fname, master).expect("migrate");
+        if !SecureDb::detect_sqlcipher(&Connection::open_in_memory().unwrap()) {
+            // Without SQLCipher the plaintext layout is the native one
+            assert_eq!(migrated, 0);
+            assert_eq!(SecureDb::open(&fname, master).unwrap().list_entries(master).unwrap().len(), 2);
+            let _ = fs::remove_file(&fname);
+            return;
+        }
+
+        assert_eq!(migrated, 2);
+        assert!(!SecureDb::is_plaintext_db(&fname).unwrap());
+        let db = SecureDb::open(&fname, master).expect("open encrypted db");
+        let mut services: Vec<String> = db.list_entries(master).expect("list").into_iter().map(|e| e.service).collect();
+        services.sort();
+        assert_eq!(services, vec!["alpha", "beta"]);
+        drop(db);
+        assert_eq!(SecureDb::migrate_plaintext_file(&fname, master).expect("second run"), 0);
+
+        let _ = fs::remove_file(&fname);
+    }
+
+    #[test]
+    fn test_securedb_compact_shrinks_file() {
+        let tmp = env::temp_dir();
+        let fname = tmp.join(format!("passmann_test_compact_{}.db", Uuid::new_v4()));
+        let master = "test_master_password";
+        let _ = fs::remove_file(&fname);
+
+        let db = SecureDb::open(&fname, master).expect("open db");
+        let mut ids = Vec::new();
+        for i in 0..200 {
+            let entry = passmann_shared::Entry::new(
+                format!("service_{}", i),
+                "user".to_string(),
+                "x".repeat(512),
+            );
+            db.store_entry(&entry, master).expect("store entry");
+            ids.push(entry.id.to_string());
+        }
+        for id in &ids {
+            db.delete_entry(id).expect("delete entry");
+        }
+
+        let before = fs::metadata(&fname).expect("metadata").len();
+        db.compact().expect("compact");
+        let after = fs::metadata(&fname).expect("metadata").len();
+        assert!(after < before, "expected file to shrink: {} -> {}", before, after);
+
+        let _ = fs::remove_file(&fname);
+    }
+
+    #[test]
+    fn test_securedb_verify_entries_reports_corruption() {
+        let tmp = env::temp_dir();
+        let fname = tmp.join(format!("passmann_test_verify_{}.db", Uuid::new_v4()));
+        let master = "test_master_password";
+        let _ = fs::remove_file(&fname);
+
+        let db = SecureDb::open(&fname, master).expect("open db");
+
+        let good = passmann_shared::Entry::new(
+            "good_service".to_string(),
+            "user".to_string(),
+            "password_one".to_string(),
+        );
+        let bad = passmann_shared::Entry::new(
+            "bad_service".to_string(),
+            "user".to_string(),
+            "password_two".to_string(),
+        );
+        db.store_entry(&good, master).expect("store good entry");
+        db.store_entry(&bad, master).expect("store bad entry");
+
+        let report = db.verify_entries(master).expect("verify");
+        assert_eq!(report.verified, 2);
+        assert!(report.failed.is_empty());
+
+        // Corrupt one row in whichever table the current mode uses
+        let (table, column) = if db.sqlcipher {
+            ("vault_entries", "password_data")
+        } else {
+            ("encrypted_vault_entries", "encrypted_data")
+        };
+        db.conn.execute(
+            &format!("UPDATE {} SET {} = ?1 WHERE id = ?2", table, column),
+            rusqlite::params![vec![0u8; 48], bad.id.to_string()],
+        ).expect("corrupt entry");
+
+        let report = db.verify_entries(master).expect("verify after corruption");
+        assert_eq!(report.verified, 1);
+        assert_eq!(report.failed, vec![bad.id.to_string()]);
+
+        let _ = fs::remove_file(&fname);
+    }
 }
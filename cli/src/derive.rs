@@ -0,0 +1,105 @@
+//! Stateless site passwords for `passmann derive`: the same master password,
+//! site and counter always give the same password, so nothing is stored or
+//! synced. Changing the master password changes every derived password.
+//!
+//! Argon2id turns `(master password, site, counter)` into a key with fixed
+//! parameters, independent of the vault's own KDF settings. A BLAKE3 stream
+//! keyed from it is then mapped onto the character set by rejection sampling.
+
+use crate::generator::{DIGITS, LOWERCASE, SYMBOLS, UPPERCASE};
+use passmann_shared::{derive_key_with_config, Argon2Config, Result};
+
+/// Bumping this changes every derived password, so it never should
+const DERIVE_CONTEXT: &str = "passmann derive v1";
+pub const MIN_LENGTH: usize = 8;
+pub const MAX_LENGTH: usize = 128;
+/// Candidates drawn before giving up on one containing every class; each
+/// one fails only when a class is missing, so this is never reached in practice
+const MAX_CANDIDATES: usize = 100;
+
+/// Fixed so derived passwords never change with the vault's tuning
+fn argon2_config() -> Argon2Config {
+    Argon2Config {
+        memory_cost: 64 * 1024,
+        time_cost: 3,
+        parallelism: 4,
+        hash_length: Some(32),
+    }
+}
+
+/// The password for `site`. Sites are compared case-insensitively and
+/// without surrounding whitespace, so `GitHub.com` and `github.com` match.
+pub fn derive_password(master_password: &str, site: &str, counter: u32, length: usize, symbols: bool) -> Result<String> {
+    if !(MIN_LENGTH..=MAX_LENGTH).contains(&length) {
+        return Err(format!("Derived passwords must be {} to {} characters long", MIN_LENGTH, MAX_LENGTH).into());
+    }
+    let site = site.trim().to_lowercase();
+    if site.is_empty() {
+        return Err("Site cannot be empty".into());
+    }
+    let salt = format!("{}\0{}\0{}", DERIVE_CONTEXT, site, counter);
+    let key = derive_key_with_config(master_password, salt.as_bytes(), &argon2_config());
+    password_from_key(key.as_ref(), length, symbols)
+}
+
+/// Map `key` onto a password of `length` characters containing at least one
+/// lowercase letter, uppercase letter, digit and (if enabled) symbol
+fn password_from_key(key: &[u8], length: usize, symbols: bool) -> Result<String> {
+    let mut classes: Vec<Vec<char>> = [LOWERCASE, UPPERCASE, DIGITS].iter().map(|set| set.chars().collect()).collect();
+    if symbols {
+        classes.push(SYMBOLS.chars().collect());
+    }
+    let charset: Vec<char> = classes.concat();
+    // Largest multiple of the charset size that fits in a byte, to avoid modulo bias
+    let zone = 256 - 256 % charset.len();
+
+    let mut hasher = blake3::Hasher::new_derive_key(DERIVE_CONTEXT);
+    hasher.update(key);
+    let mut stream = hasher.finalize_xof();
+    let mut next_char = || loop {
+        let mut byte = [0u8; 1];
+        stream.fill(&mut byte);
+        if (byte[0] as usize) < zone {
+            return charset[byte[0] as usize % charset.len()];
+        }
+    };
+
+    for _ in 0..MAX_CANDIDATES {
+        let candidate: String = (0..length).map(|_| next_char()).collect();
+        if classes.iter().all(|class| candidate.chars().any(|c| class.contains(&c))) {
+            return Ok(candidate);
+        }
+    }
+    Err("Could not derive a password containing every character class".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_inputs_give_the_same_password() {
+        let first = derive_password("master", "GitHub.com ", 1, 20, true).unwrap();
+        assert_eq!(first, derive_password("master", "github.com", 1, 20, true).unwrap());
+        assert_ne!(first, derive_password("master", "github.com", 2, 20, true).unwrap());
+        assert_ne!(first, derive_password("other master", "github.com", 1, 20, true).unwrap());
+    }
+
+    #[test]
+    fn test_length_and_charset_constraints_hold() {
+        for seed in 0u8..50 {
+            let key = [seed; 32];
+            let password = password_from_key(&key, MIN_LENGTH, false).unwrap();
+            assert_eq!(password.chars().count(), MIN_LENGTH);
+            assert!(password.chars().all(|c| c.is_ascii_alphanumeric()));
+            assert!(password.chars().any(|c| c.is_ascii_lowercase()));
+            assert!(password.chars().any(|c| c.is_ascii_uppercase()));
+            assert!(password.chars().any(|c| c.is_ascii_digit()));
+
+            let password = password_from_key(&key, 32, true).unwrap();
+            assert!(password.chars().any(|c| SYMBOLS.contains(c)));
+        }
+        assert!(derive_password("master", "site", 1, MIN_LENGTH - 1, true).is_err());
+        assert!(derive_password("master", "  ", 1, 20, true).is_err());
+    }
+}
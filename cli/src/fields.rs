@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+
+/// Secret fields are stored with this prefix on the key so every client can tell
+/// them apart without a schema change
+const SECRET_PREFIX: &str = "secret:";
+
+/// Shown in place of a secret field's value
+pub const MASK: &str = "••••••••";
+
+/// Key a field is stored under in `Entry::custom_fields`
+pub fn storage_key(name: &str, secret: bool) -> String {
+    if secret {
+        format!("{}{}", SECRET_PREFIX, name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// Strip the secret marker from a stored key: `(name, secret)`
+pub fn parse_key(key: &str) -> (&str, bool) {
+    match key.strip_prefix(SECRET_PREFIX) {
+        Some(name) => (name, true),
+        None => (key, false),
+    }
+}
+
+/// Stored key for the field called `name`, whether or not it is secret
+pub fn find_key<'a>(fields: &'a HashMap<String, String>, name: &str) -> Option<&'a String> {
+    fields.keys().find(|key| parse_key(key).0 == name)
+}
+
+/// Fields sorted by name for display, with secret values masked
+pub fn display_fields(fields: &HashMap<String, String>) -> Vec<(&str, &str)> {
+    let mut shown: Vec<(&str, &str)> = fields.iter()
+        .map(|(key, value)| match parse_key(key) {
+            (name, true) => (name, MASK),
+            (name, false) => (name, value.as_str()),
+        })
+        .collect();
+    shown.sort();
+    shown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_fields_are_found_and_masked() {
+        let mut fields = HashMap::new();
+        fields.insert(storage_key("pin", false), "1234".to_string());
+        fields.insert(storage_key("recovery", true), "abcd-efgh".to_string());
+
+        assert_eq!(find_key(&fields, "recovery").map(String::as_str), Some("secret:recovery"));
+        assert_eq!(find_key(&fields, "pin").map(String::as_str), Some("pin"));
+        assert!(find_key(&fields, "missing").is_none());
+
+        assert_eq!(display_fields(&fields), vec![("pin", "1234"), ("recovery", MASK)]);
+    }
+}
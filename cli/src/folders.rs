@@ -0,0 +1,130 @@
+//! Folders are `/`-separated prefixes of the service name (`work/aws`), so
+//! they need no storage of their own.
+
+use passmann_shared::Entry;
+use std::collections::BTreeMap;
+
+pub const DELIMITER: char = '/';
+
+/// Folder and leaf name of an entry's service
+pub trait ServicePath {
+    /// Folder part, e.g. `work` for `work/aws`; `None` at the top level
+    fn folder(&self) -> Option<&str>;
+    /// Last segment, e.g. `aws` for `work/aws`
+    fn leaf_name(&self) -> &str;
+}
+
+impl ServicePath for Entry {
+    fn folder(&self) -> Option<&str> {
+        split(&self.service).0
+    }
+
+    fn leaf_name(&self) -> &str {
+        split(&self.service).1
+    }
+}
+
+/// Split a service into folder and leaf, ignoring leading and trailing slashes
+pub fn split(service: &str) -> (Option<&str>, &str) {
+    let trimmed = service.trim_matches(DELIMITER);
+    match trimmed.rsplit_once(DELIMITER) {
+        Some((folder, leaf)) => (Some(folder.trim_end_matches(DELIMITER)), leaf),
+        None => (None, trimmed),
+    }
+}
+
+/// Whether `service` is inside `folder` or one of its subfolders (case-insensitive)
+pub fn in_folder(service: &str, folder: &str) -> bool {
+    let folder = folder.trim_matches(DELIMITER).to_lowercase();
+    if folder.is_empty() {
+        return true;
+    }
+    split(service).0
+        .map(str::to_lowercase)
+        .is_some_and(|parent| parent == folder || parent.starts_with(&format!("{}{}", folder, DELIMITER)))
+}
+
+/// One folder level of the `tree` view
+#[derive(Debug, Default)]
+pub struct FolderNode {
+    pub folders: BTreeMap<String, FolderNode>,
+    /// `(leaf name, username)` of the entries directly in this folder
+    pub entries: Vec<(String, String)>,
+}
+
+impl FolderNode {
+    pub fn build<'a>(entries: impl IntoIterator<Item = &'a Entry>) -> Self {
+        let mut root = FolderNode::default();
+        for entry in entries {
+            let mut node = &mut root;
+            for segment in entry.folder().into_iter().flat_map(|f| f.split(DELIMITER)).filter(|s| !s.is_empty()) {
+                node = node.folders.entry(segment.to_string()).or_default();
+            }
+            node.entries.push((entry.leaf_name().to_string(), entry.username.clone()));
+        }
+        root.sort();
+        root
+    }
+
+    fn sort(&mut self) {
+        self.entries.sort();
+        self.folders.values_mut().for_each(FolderNode::sort);
+    }
+
+    /// Lines of the tree with box-drawing connectors, folders first
+    pub fn render(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        self.render_into("", &mut lines);
+        lines
+    }
+
+    fn render_into(&self, indent: &str, lines: &mut Vec<String>) {
+        let count = self.folders.len() + self.entries.len();
+        let mut index = 0;
+        for (name, child) in &self.folders {
+            index += 1;
+            let (branch, next) = if index == count { ("└── ", "    ") } else { ("├── ", "│   ") };
+            lines.push(format!("{}{}📁 {}", indent, branch, name));
+            child.render_into(&format!("{}{}", indent, next), lines);
+        }
+        for (leaf, username) in &self.entries {
+            index += 1;
+            let branch = if index == count { "└── " } else { "├── " };
+            lines.push(format!("{}{}🔑 {} ({})", indent, branch, leaf, username));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(service: &str) -> Entry {
+        Entry::new(service.to_string(), "me".to_string(), "pw".to_string())
+    }
+
+    #[test]
+    fn test_split_and_in_folder() {
+        assert_eq!(split("work/aws"), (Some("work"), "aws"));
+        assert_eq!(split("/work/cloud/aws/"), (Some("work/cloud"), "aws"));
+        assert_eq!(split("github"), (None, "github"));
+
+        assert!(in_folder("work/cloud/aws", "work"));
+        assert!(in_folder("Work/aws", "work/"));
+        assert!(!in_folder("workshop/aws", "work"));
+        assert!(!in_folder("work", "work"));
+    }
+
+    #[test]
+    fn test_tree_renders_nested_folders() {
+        let entries = [entry("work/github"), entry("work/cloud/aws"), entry("bank")];
+        let lines = FolderNode::build(&entries).render();
+        assert_eq!(lines, vec![
+            "├── 📁 work",
+            "│   ├── 📁 cloud",
+            "│   │   └── 🔑 aws (me)",
+            "│   └── 🔑 github (me)",
+            "└── 🔑 bank (me)",
+        ]);
+    }
+}
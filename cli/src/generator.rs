@@ -0,0 +1,291 @@
+use passmann_shared::Result;
+use serde::{Deserialize, Serialize};
+
+pub const LOWERCASE: &str = "abcdefghijklmnopqrstuvwxyz";
+pub const UPPERCASE: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+pub const DIGITS: &str = "0123456789";
+pub const SYMBOLS: &str = "!@#$%^&*()-_=+[]{};:,.<>?";
+
+/// Characters that are easily confused when read or typed by hand
+const AMBIGUOUS: &str = "lI1O0o|`'\"";
+
+/// Minimum number of characters required from each class. Every enabled class
+/// always gets at least one, so zero means "no extra requirement".
+#[derive(Debug, Clone, Default)]
+pub struct PasswordPolicy {
+    pub min_lower: usize,
+    pub min_upper: usize,
+    pub min_digits: usize,
+    pub min_symbols: usize,
+}
+
+/// A character class after exclusions, with how many of it must appear
+struct CharClass {
+    chars: Vec<char>,
+    required: usize,
+}
+
+/// Which characters a generated password may contain
+#[derive(Debug, Clone)]
+pub struct GeneratorOptions {
+    pub uppercase: bool,
+    pub digits: bool,
+    pub symbols: bool,
+    /// Leave out look-alike characters such as `l`, `1`, `I`, `O` and `0`
+    pub exclude_ambiguous: bool,
+    /// Extra characters to leave out
+    pub exclude: String,
+    pub policy: PasswordPolicy,
+}
+
+impl Default for GeneratorOptions {
+    fn default() -> Self {
+        Self {
+            uppercase: true,
+            digits: true,
+            symbols: true,
+            exclude_ambiguous: false,
+            exclude: String::new(),
+            policy: PasswordPolicy::default(),
+        }
+    }
+}
+
+impl GeneratorOptions {
+    /// The enabled character classes after exclusions. Classes left empty are
+    /// dropped, unless the policy requires them.
+    fn classes(&self) -> Result<Vec<CharClass>> {
+        let candidates = [
+            ("lowercase letters", true, LOWERCASE, self.policy.min_lower),
+            ("uppercase letters", self.uppercase, UPPERCASE, self.policy.min_upper),
+            ("digits", self.digits, DIGITS, self.policy.min_digits),
+            ("symbols", self.symbols, SYMBOLS, self.policy.min_symbols),
+        ];
+
+        let mut classes = Vec::new();
+        for (name, enabled, chars, min) in candidates {
+            let chars: Vec<char> = if enabled {
+                chars.chars()
+                    .filter(|c| !(self.exclude_ambiguous && AMBIGUOUS.contains(*c)))
+                    .filter(|c| !self.exclude.contains(*c))
+                    .collect()
+            } else {
+                Vec::new()
+            };
+
+            if chars.is_empty() {
+                if min > 0 {
+                    return Err(format!("Policy requires {} {} but none are allowed", min, name).into());
+                }
+                continue;
+            }
+            classes.push(CharClass { chars, required: min.max(1) });
+        }
+        Ok(classes)
+    }
+}
+
+/// Generate a password meeting the policy minimums, with at least one character
+/// from each enabled class. Required characters are placed first, the rest is
+/// filled from every class, then the whole password is shuffled.
+pub fn generate_password_opts(length: usize, options: &GeneratorOptions) -> Result<String> {
+    let classes = options.classes()?;
+    if classes.is_empty() {
+        return Err("No characters left to generate a password from".into());
+    }
+    let required: usize = classes.iter().map(|class| class.required).sum();
+    if length < required {
+        return Err(format!("Length {} is too short: the character requirements need at least {}", length, required).into());
+    }
+
+    let pool: Vec<char> = classes.iter().flat_map(|class| class.chars.iter().copied()).collect();
+    let mut password = Vec::with_capacity(length);
+    for class in &classes {
+        for _ in 0..class.required {
+            password.push(class.chars[random_index(class.chars.len())?]);
+        }
+    }
+    while password.len() < length {
+        password.push(pool[random_index(pool.len())?]);
+    }
+    shuffle(&mut password)?;
+
+    Ok(password.into_iter().collect())
+}
+
+/// Limits a site puts on its passwords, stored per entry by `passmann policy`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GenerationPolicy {
+    pub length: usize,
+    pub uppercase: bool,
+    pub digits: bool,
+    pub symbols: bool,
+    /// Characters the site rejects
+    pub exclude: String,
+}
+
+/// Generator options for an entry's stored policy. Policies only restrict
+/// classes, so the per-class minimums stay at their defaults.
+pub fn options_for_policy(policy: &GenerationPolicy) -> GeneratorOptions {
+    GeneratorOptions {
+        uppercase: policy.uppercase,
+        digits: policy.digits,
+        symbols: policy.symbols,
+        exclude: policy.exclude.clone(),
+        ..GeneratorOptions::default()
+    }
+}
+
+/// Fail if no password could ever satisfy `policy`, so it is never stored
+pub fn validate_policy(policy: &GenerationPolicy) -> Result<()> {
+    generate_password_opts(policy.length, &options_for_policy(policy)).map(|_| ())
+}
+
+/// Compatibility wrapper for the original generator signature. Lengths too
+/// short to fit every class are raised to fit them.
+pub fn generate_password(length: usize, symbols: bool) -> Result<String> {
+    let options = GeneratorOptions { symbols, ..GeneratorOptions::default() };
+    generate_password_opts(length.max(4), &options)
+}
+
+/// Shortest PIN worth generating
+pub const MIN_PIN_LENGTH: usize = 3;
+const MAX_PIN_ATTEMPTS: usize = 1000;
+
+/// Generate a numeric PIN, rejecting obvious ones such as `0000` or `1234`
+pub fn generate_pin(length: usize) -> Result<String> {
+    if length < MIN_PIN_LENGTH {
+        return Err(format!("PIN length must be at least {}", MIN_PIN_LENGTH).into());
+    }
+
+    let digits: Vec<char> = DIGITS.chars().collect();
+    for _ in 0..MAX_PIN_ATTEMPTS {
+        let pin = (0..length)
+            .map(|_| Ok(digits[random_index(digits.len())?]))
+            .collect::<Result<Vec<char>>>()?;
+        if !is_obvious_pin(&pin) {
+            return Ok(pin.into_iter().collect());
+        }
+    }
+    Err("Could not generate a non-obvious PIN".into())
+}
+
+/// All one digit, or a run stepping up or down by one (e.g. 1234, 9876)
+fn is_obvious_pin(pin: &[char]) -> bool {
+    let values: Vec<i32> = pin.iter().filter_map(|c| c.to_digit(10)).map(|d| d as i32).collect();
+    let steps: Vec<i32> = values.windows(2).map(|pair| pair[1] - pair[0]).collect();
+    [0, 1, -1].iter().any(|step| steps.iter().all(|s| s == step))
+}
+
+fn shuffle(chars: &mut [char]) -> Result<()> {
+    for i in (1..chars.len()).rev() {
+        let j = random_index(i + 1)?;
+        chars.swap(i, j);
+    }
+    Ok(())
+}
+
+/// Uniform index in `0..bound` from the OS CSPRNG, using rejection sampling to avoid modulo bias
+fn random_index(bound: usize) -> Result<usize> {
+    let bound = bound as u64;
+    let zone = u64::MAX - (u64::MAX % bound);
+    loop {
+        let mut bytes = [0u8; 8];
+        getrandom::getrandom(&mut bytes).map_err(|e| format!("OS RNG failed: {}", e))?;
+        let value = u64::from_le_bytes(bytes);
+        if value < zone {
+            return Ok((value % bound) as usize);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_options_and_validation() {
+        let policy = GenerationPolicy { length: 12, uppercase: true, digits: true, symbols: false, exclude: "0123456789".to_string() };
+        let password = generate_password_opts(policy.length, &options_for_policy(&policy)).unwrap();
+        assert_eq!(password.len(), 12);
+        assert!(password.chars().all(|c| c.is_ascii_alphabetic()));
+
+        let too_short = GenerationPolicy { length: 1, ..policy.clone() };
+        assert!(validate_policy(&too_short).is_err());
+        let nothing_left = GenerationPolicy { exclude: format!("{}{}", LOWERCASE, UPPERCASE), ..policy };
+        assert!(validate_policy(&GenerationPolicy { digits: false, ..nothing_left }).is_err());
+    }
+
+    #[test]
+    fn test_every_enabled_class_is_present() {
+        let options = GeneratorOptions::default();
+        for _ in 0..200 {
+            let password = generate_password_opts(8, &options).unwrap();
+            assert_eq!(password.chars().count(), 8);
+            assert!(password.chars().any(|c| c.is_ascii_lowercase()));
+            assert!(password.chars().any(|c| c.is_ascii_uppercase()));
+            assert!(password.chars().any(|c| c.is_ascii_digit()));
+            assert!(password.chars().any(|c| SYMBOLS.contains(c)));
+        }
+    }
+
+    #[test]
+    fn test_exclusions_are_respected() {
+        let options = GeneratorOptions {
+            symbols: false,
+            exclude_ambiguous: true,
+            exclude: "xyz".to_string(),
+            ..GeneratorOptions::default()
+        };
+        for _ in 0..200 {
+            let password = generate_password_opts(32, &options).unwrap();
+            assert!(!password.chars().any(|c| AMBIGUOUS.contains(c) || "xyz".contains(c)));
+            assert!(!password.chars().any(|c| SYMBOLS.contains(c)));
+        }
+    }
+
+    #[test]
+    fn test_policy_minimums_hold_over_many_samples() {
+        let options = GeneratorOptions {
+            policy: PasswordPolicy { min_lower: 0, min_upper: 3, min_digits: 2, min_symbols: 2 },
+            ..GeneratorOptions::default()
+        };
+        for _ in 0..500 {
+            let password = generate_password_opts(10, &options).unwrap();
+            assert!(password.chars().filter(|c| c.is_ascii_uppercase()).count() >= 3);
+            assert!(password.chars().filter(|c| c.is_ascii_digit()).count() >= 2);
+            assert!(password.chars().filter(|c| SYMBOLS.contains(*c)).count() >= 2);
+            assert!(password.chars().any(|c| c.is_ascii_lowercase()));
+        }
+
+        // 1 lower + 3 upper + 2 digits + 2 symbols = 8 required
+        assert!(generate_password_opts(7, &options).is_err());
+
+        let disabled = GeneratorOptions { digits: false, ..options };
+        assert!(generate_password_opts(16, &disabled).is_err());
+    }
+
+    #[test]
+    fn test_pin_is_numeric_and_not_obvious() {
+        for _ in 0..500 {
+            let pin = generate_pin(4).unwrap();
+            assert_eq!(pin.len(), 4);
+            assert!(pin.chars().all(|c| c.is_ascii_digit()));
+            assert!(!is_obvious_pin(&pin.chars().collect::<Vec<_>>()));
+        }
+
+        for obvious in ["0000", "1234", "9876", "555"] {
+            assert!(is_obvious_pin(&obvious.chars().collect::<Vec<_>>()), "{}", obvious);
+        }
+        assert!(!is_obvious_pin(&"1243".chars().collect::<Vec<_>>()));
+
+        assert!(generate_pin(2).is_err());
+    }
+
+    #[test]
+    fn test_too_short_for_classes_is_rejected() {
+        assert!(generate_password_opts(3, &GeneratorOptions::default()).is_err());
+        assert_eq!(generate_password(16, false).unwrap().len(), 16);
+        assert_eq!(generate_password(2, true).unwrap().len(), 4);
+    }
+}
@@ -0,0 +1,150 @@
+//! Vault statistics over time. Each save that changes the vault appends a
+//! compact snapshot to `stats_history.enc` next to the vault, encrypted with a
+//! key derived from the master password. `passmann trend` reads it back.
+
+use crate::sealed_file;
+use crate::stats::{self, DEFAULT_AGE_THRESHOLD_DAYS};
+use chrono::{DateTime, Utc};
+use passmann_shared::{Entry, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Snapshots kept; the oldest are dropped first
+pub const MAX_SNAPSHOTS: usize = 100;
+/// Scores below this count as weak, as in `passmann stats`
+const WEAK_SCORE: u8 = 60;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub timestamp: DateTime<Utc>,
+    pub total_entries: usize,
+    pub weak: usize,
+    /// Entries sharing their password with at least one other entry
+    pub reused: usize,
+    /// Passwords unchanged for longer than the default age threshold
+    pub old: usize,
+    pub avg_strength: f32,
+}
+
+impl Snapshot {
+    pub fn of(entries: &[Entry], timestamp: DateTime<Utc>) -> Self {
+        let scores = stats::strength_scores(entries);
+        let mut uses: HashMap<&str, usize> = HashMap::new();
+        for entry in entries {
+            *uses.entry(entry.password.as_str()).or_default() += 1;
+        }
+        Snapshot {
+            timestamp,
+            total_entries: entries.len(),
+            weak: scores.iter().filter(|score| **score < WEAK_SCORE).count(),
+            reused: entries.iter().filter(|e| uses[e.password.as_str()] > 1).count(),
+            old: entries.iter().filter(|e| e.get_password_age_days() as i64 > DEFAULT_AGE_THRESHOLD_DAYS).count(),
+            avg_strength: if scores.is_empty() {
+                0.0
+            } else {
+                scores.iter().map(|score| *score as f32).sum::<f32>() / scores.len() as f32
+            },
+        }
+    }
+
+    /// Same metrics, ignoring when they were taken
+    fn same_metrics(&self, other: &Snapshot) -> bool {
+        (self.total_entries, self.weak, self.reused, self.old) == (other.total_entries, other.weak, other.reused, other.old)
+            && (self.avg_strength - other.avg_strength).abs() < 0.05
+    }
+}
+
+/// Every stored snapshot, oldest first. A missing file is an empty history.
+pub fn load(path: &Path, master_password: &str) -> Result<Vec<Snapshot>> {
+    Ok(sealed_file::load(path, master_password)?.unwrap_or_default())
+}
+
+/// Append `snapshot` unless it matches the latest one, keeping at most
+/// `MAX_SNAPSHOTS`. Returns whether anything was written.
+pub fn record(path: &Path, master_password: &str, snapshot: Snapshot) -> Result<bool> {
+    let mut history = load(path, master_password)?;
+    if !push_capped(&mut history, snapshot, MAX_SNAPSHOTS) {
+        return Ok(false);
+    }
+    sealed_file::store(path, master_password, &history)?;
+    Ok(true)
+}
+
+fn push_capped(history: &mut Vec<Snapshot>, snapshot: Snapshot, max: usize) -> bool {
+    if history.last().is_some_and(|last| last.same_metrics(&snapshot)) {
+        return false;
+    }
+    history.push(snapshot);
+    let overflow = history.len().saturating_sub(max);
+    history.drain(..overflow);
+    true
+}
+
+/// One-line ASCII chart of `values`, scaled between their minimum and maximum
+pub fn sparkline(values: &[f32]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    values.iter()
+        .map(|value| {
+            if (max - min).abs() < f32::EPSILON {
+                BARS[BARS.len() / 2]
+            } else {
+                BARS[(((value - min) / (max - min)) * (BARS.len() - 1) as f32).round() as usize]
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(weak: usize) -> Snapshot {
+        Snapshot { timestamp: Utc::now(), total_entries: 10, weak, reused: 0, old: 0, avg_strength: 70.0 }
+    }
+
+    #[test]
+    fn test_snapshot_counts_reused_passwords() {
+        let entries = vec![
+            Entry::new("a".to_string(), "me".to_string(), "shared-Secret-1".to_string()),
+            Entry::new("b".to_string(), "me".to_string(), "shared-Secret-1".to_string()),
+            Entry::new("c".to_string(), "me".to_string(), "different-Secret-2".to_string()),
+        ];
+        let snapshot = Snapshot::of(&entries, Utc::now());
+        assert_eq!(snapshot.total_entries, 3);
+        assert_eq!(snapshot.reused, 2);
+        assert_eq!(Snapshot::of(&[], Utc::now()).avg_strength, 0.0);
+    }
+
+    #[test]
+    fn test_push_capped_skips_unchanged_and_drops_oldest() {
+        let mut history = Vec::new();
+        assert!(push_capped(&mut history, snapshot(3), 3));
+        assert!(!push_capped(&mut history, snapshot(3), 3));
+        for weak in [2, 1, 0] {
+            assert!(push_capped(&mut history, snapshot(weak), 3));
+        }
+        assert_eq!(history.iter().map(|s| s.weak).collect::<Vec<_>>(), vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn test_record_round_trips_encrypted() {
+        let path = std::env::temp_dir().join(format!("passmann-history-{}.enc", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let saved = snapshot(3);
+        assert!(record(&path, "master", saved.clone()).unwrap());
+        assert_eq!(load(&path, "master").unwrap(), vec![saved]);
+        assert!(load(&path, "wrong").is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[0.0, 50.0, 100.0]), "▁▅█");
+        assert_eq!(sparkline(&[4.0, 4.0]), "▅▅");
+        assert_eq!(sparkline(&[]), "");
+    }
+}
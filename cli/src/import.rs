@@ -0,0 +1,307 @@
+use passmann_shared::Result;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::io::{Read, Seek};
+
+/// One credential read from another password manager's export
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedRow {
+    pub service: String,
+    pub username: String,
+    pub password: String,
+    pub url: Option<String>,
+    pub notes: Option<String>,
+    pub tags: Vec<String>,
+    /// TOTP secret or `otpauth://` URI, if the source had one
+    pub totp: Option<String>,
+}
+
+/// Rows ready to add, plus what was dropped on the way
+#[derive(Debug, Default)]
+pub struct ImportBatch {
+    pub rows: Vec<ImportedRow>,
+    /// Rows without a password
+    pub skipped_empty: usize,
+    /// Later rows repeating an earlier row's URL and username
+    pub deduplicated: usize,
+    /// Secure notes, documents and other items that aren't logins
+    pub skipped_non_login: usize,
+}
+
+/// Parse a Chrome or Edge password export (`name,url,username,password`,
+/// newer versions add `note`). Columns are found by header name.
+pub fn parse_chrome_csv<R: Read>(reader: R) -> Result<ImportBatch> {
+    let mut csv = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+    let headers = csv.headers()?.clone();
+    let column = |name: &str| headers.iter().position(|header| header.trim().eq_ignore_ascii_case(name));
+    let (Some(url_col), Some(username_col), Some(password_col)) = (column("url"), column("username"), column("password")) else {
+        return Err("Not a Chrome password export: expected url, username and password columns".into());
+    };
+    let name_col = column("name");
+    let note_col = column("note");
+
+    let mut batch = ImportBatch::default();
+    let mut seen = HashSet::new();
+    for record in csv.records() {
+        let record = record?;
+        let field = |col: Option<usize>| col.and_then(|i| record.get(i)).unwrap_or("").trim().to_string();
+
+        let password = field(Some(password_col));
+        if password.is_empty() {
+            batch.skipped_empty += 1;
+            continue;
+        }
+        let url = field(Some(url_col));
+        let username = field(Some(username_col));
+        if !seen.insert((url.clone(), username.clone())) {
+            batch.deduplicated += 1;
+            continue;
+        }
+
+        let name = field(name_col);
+        let service = if name.is_empty() { host_of(&url).unwrap_or(&url).to_string() } else { name };
+        let notes = field(note_col);
+        batch.rows.push(ImportedRow {
+            service,
+            username,
+            password,
+            url: (!url.is_empty()).then_some(url),
+            notes: (!notes.is_empty()).then_some(notes),
+            tags: Vec::new(),
+            totp: None,
+        });
+    }
+    Ok(batch)
+}
+
+/// Parse a 1Password CSV export (`Title,Url,Username,Password,Notes`, plus
+/// `OTPAuth`, `Tags`, `Vault` and `Type` when present)
+pub fn parse_1password_csv<R: Read>(reader: R) -> Result<ImportBatch> {
+    let mut csv = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+    let headers = csv.headers()?.clone();
+    let column = |names: &[&str]| headers.iter()
+        .position(|header| names.iter().any(|name| header.trim().eq_ignore_ascii_case(name)));
+    let (Some(title_col), Some(password_col)) = (column(&["title"]), column(&["password"])) else {
+        return Err("Not a 1Password export: expected Title and Password columns".into());
+    };
+    let url_col = column(&["url", "website"]);
+    let username_col = column(&["username"]);
+    let notes_col = column(&["notes", "notesplain"]);
+    let otp_col = column(&["otpauth", "one-time password"]);
+    let tags_col = column(&["tags"]);
+    let vault_col = column(&["vault"]);
+    let type_col = column(&["type", "category"]);
+
+    let mut batch = ImportBatch::default();
+    for record in csv.records() {
+        let record = record?;
+        let field = |col: Option<usize>| col.and_then(|i| record.get(i)).unwrap_or("").trim().to_string();
+
+        let kind = field(type_col);
+        let (url, username, password) = (field(url_col), field(username_col), field(Some(password_col)));
+        let is_login = if kind.is_empty() {
+            // Without a type column, rows with nothing to log in with are secure notes
+            !(url.is_empty() && username.is_empty() && password.is_empty())
+        } else {
+            kind.eq_ignore_ascii_case("login")
+        };
+        if !is_login {
+            batch.skipped_non_login += 1;
+            continue;
+        }
+        if password.is_empty() {
+            batch.skipped_empty += 1;
+            continue;
+        }
+
+        let mut tags: Vec<String> = field(tags_col).split(',')
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        let vault = field(vault_col);
+        if !vault.is_empty() && !tags.contains(&vault) {
+            tags.push(vault);
+        }
+        let notes = field(notes_col);
+        let totp = field(otp_col);
+        batch.rows.push(ImportedRow {
+            service: field(Some(title_col)),
+            username,
+            password,
+            url: (!url.is_empty()).then_some(url),
+            notes: (!notes.is_empty()).then_some(notes),
+            tags,
+            totp: (!totp.is_empty()).then_some(totp),
+        });
+    }
+    Ok(batch)
+}
+
+/// 1Password's category id for logins; every other category is skipped
+const LOGIN_CATEGORY: &str = "001";
+
+/// Parse a 1Password `.1pux` export, a zip archive holding `export.data` JSON
+pub fn parse_1pux<R: Read + Seek>(reader: R) -> Result<ImportBatch> {
+    let mut archive = zip::ZipArchive::new(reader)?;
+    let data: Value = serde_json::from_reader(archive.by_name("export.data")?)?;
+    let text = |value: &Value| value.as_str().unwrap_or("").trim().to_string();
+
+    let mut batch = ImportBatch::default();
+    let accounts = data["accounts"].as_array().map(Vec::as_slice).unwrap_or_default();
+    for vault in accounts.iter().flat_map(|account| account["vaults"].as_array().into_iter().flatten()) {
+        let vault_name = text(&vault["attrs"]["name"]);
+        for item in vault["items"].as_array().into_iter().flatten() {
+            // Items may be nested under "item" depending on the export version
+            let item = item.get("item").unwrap_or(item);
+            if item["categoryUuid"].as_str() != Some(LOGIN_CATEGORY) {
+                batch.skipped_non_login += 1;
+                continue;
+            }
+
+            let login_field = |designation: &str| item["details"]["loginFields"].as_array().into_iter().flatten()
+                .find(|field| field["designation"].as_str() == Some(designation))
+                .map(|field| text(&field["value"]))
+                .unwrap_or_default();
+            let password = login_field("password");
+            if password.is_empty() {
+                batch.skipped_empty += 1;
+                continue;
+            }
+
+            let overview = &item["overview"];
+            let mut tags: Vec<String> = overview["tags"].as_array().into_iter().flatten()
+                .map(text)
+                .filter(|tag| !tag.is_empty())
+                .collect();
+            if !vault_name.is_empty() && !tags.contains(&vault_name) {
+                tags.push(vault_name.clone());
+            }
+            let totp = item["details"]["sections"].as_array().into_iter().flatten()
+                .flat_map(|section| section["fields"].as_array().into_iter().flatten())
+                .find_map(|field| field["value"]["totp"].as_str())
+                .map(str::to_string);
+            let url = text(&overview["url"]);
+            let notes = text(&item["details"]["notesPlain"]);
+            batch.rows.push(ImportedRow {
+                service: text(&overview["title"]),
+                username: login_field("username"),
+                password,
+                url: (!url.is_empty()).then_some(url),
+                notes: (!notes.is_empty()).then_some(notes),
+                tags,
+                totp,
+            });
+        }
+    }
+    Ok(batch)
+}
+
+/// The host part of a URL, used as the service when a row has no name
+fn host_of(url: &str) -> Option<&str> {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let host = rest.split(['/', '?', '#']).next()?;
+    let host = host.rsplit_once('@').map_or(host, |(_, host)| host);
+    (!host.is_empty()).then_some(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHROME_EXPORT: &str = include_str!("../tests/fixtures/chrome_passwords.csv");
+
+    #[test]
+    fn test_parse_chrome_csv_fixture() {
+        let batch = parse_chrome_csv(CHROME_EXPORT.as_bytes()).unwrap();
+
+        assert_eq!(batch.rows.len(), 4);
+        assert_eq!(batch.skipped_empty, 1);
+        assert_eq!(batch.deduplicated, 1);
+
+        let github = &batch.rows[0];
+        assert_eq!(github.service, "github.com");
+        assert_eq!(github.username, "octocat");
+        assert_eq!(github.password, "hunter2,with\"quotes\"");
+        assert_eq!(github.url.as_deref(), Some("https://github.com/login"));
+
+        // Quoted URL with commas survives intact
+        let bank = &batch.rows[1];
+        assert_eq!(bank.url.as_deref(), Some("https://bank.example.com/login?next=/a,b&lang=en"));
+        assert_eq!(bank.notes.as_deref(), Some("PIN is in the safe"));
+
+        // No name: the service falls back to the URL's host
+        assert_eq!(batch.rows[3].service, "android.example.org");
+    }
+
+    #[test]
+    fn test_parse_chrome_csv_rejects_other_formats() {
+        assert!(parse_chrome_csv("service,login,secret\na,b,c\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_parse_1password_csv_fixture() {
+        let batch = parse_1password_csv(include_str!("../tests/fixtures/1password.csv").as_bytes()).unwrap();
+
+        assert_eq!(batch.rows.len(), 2);
+        assert_eq!(batch.skipped_non_login, 2);
+        assert_eq!(batch.skipped_empty, 1);
+
+        let email = &batch.rows[0];
+        assert_eq!(email.service, "Email");
+        assert_eq!(email.url.as_deref(), Some("https://mail.example.com"));
+        assert_eq!(email.tags, vec!["work", "email", "Shared"]);
+        assert_eq!(email.totp.as_deref(), Some("otpauth://totp/Email:jane?secret=JBSWY3DPEHPK3PXP"));
+        assert_eq!(email.notes.as_deref(), Some("Recovery email: jane@backup.example"));
+        assert_eq!(batch.rows[1].totp, None);
+    }
+
+    #[test]
+    fn test_parse_1pux_skips_non_logins() {
+        let export = serde_json::json!({
+            "accounts": [{ "vaults": [{
+                "attrs": { "name": "Personal" },
+                "items": [
+                    { "item": {
+                        "categoryUuid": "001",
+                        "overview": { "title": "Forum", "url": "https://forum.example.com", "tags": ["social"] },
+                        "details": {
+                            "loginFields": [
+                                { "designation": "username", "value": "jane" },
+                                { "designation": "password", "value": "s3cret-Forum" }
+                            ],
+                            "notesPlain": "",
+                            "sections": [{ "fields": [{ "value": { "totp": "JBSWY3DPEHPK3PXP" } }] }]
+                        }
+                    }},
+                    { "item": { "categoryUuid": "003", "overview": { "title": "Wifi notes" }, "details": {} } },
+                    { "item": { "categoryUuid": "006", "overview": { "title": "Passport scan" }, "details": {} } }
+                ]
+            }]}]
+        });
+
+        let mut buffer = std::io::Cursor::new(Vec::new());
+        {
+            let mut zip = zip::ZipWriter::new(&mut buffer);
+            zip.start_file("export.data", zip::write::SimpleFileOptions::default()).unwrap();
+            serde_json::to_writer(&mut zip, &export).unwrap();
+            zip.finish().unwrap();
+        }
+        buffer.set_position(0);
+
+        let batch = parse_1pux(buffer).unwrap();
+        assert_eq!(batch.skipped_non_login, 2);
+        assert_eq!(batch.rows.len(), 1);
+        let forum = &batch.rows[0];
+        assert_eq!((forum.service.as_str(), forum.username.as_str()), ("Forum", "jane"));
+        assert_eq!(forum.tags, vec!["social", "Personal"]);
+        assert_eq!(forum.totp.as_deref(), Some("JBSWY3DPEHPK3PXP"));
+        assert_eq!(forum.notes, None);
+    }
+
+    #[test]
+    fn test_host_of() {
+        assert_eq!(host_of("https://user@mail.example.com:443/inbox"), Some("mail.example.com:443"));
+        assert_eq!(host_of("example.com/path"), Some("example.com"));
+        assert_eq!(host_of(""), None);
+    }
+}
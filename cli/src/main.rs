@@ -1,19 +1,48 @@
 // Import modules from the shared library
 use passmann_shared::{
     Entry, Vault,
-    generate_password, encrypt, derive_key, Result, crypto::{
-        benchmark_key_derivation, estimate_password_strength, generate_salt
+    encrypt, derive_key, Result, crypto::{
+        benchmark_key_derivation, generate_salt
     }
 };
 
+mod audit;
+mod backup;
+mod blocklist;
+mod breach;
 mod cloud;
+mod completions;
 mod db;
+mod derive;
+mod fields;
+mod folders;
+mod generator;
+mod history;
+mod import;
 mod local_vault;
+mod merge;
+mod notes;
+mod output;
+mod pending;
+mod policies;
+mod reuse;
+mod sealed_file;
+mod search_index;
+mod shellenv;
+mod shred;
+mod stats;
+#[cfg(feature = "tui")]
+mod tui;
+mod vault_store;
+mod verify;
 
 use clap::{Parser, Subcommand, Args};
 use std::process;
 use std::io::{self, Write};
+use crate::generator::{generate_password, generate_password_opts, GenerationPolicy, GeneratorOptions, PasswordPolicy};
 use crate::local_vault::LocalVaultManager;
+use crate::blocklist::estimate_password_strength;
+use crate::search_index::{searchable_fields, SearchIndex};
 
 #[derive(Parser)]
 #[command(name = "PassMann")]
@@ -31,6 +60,32 @@ struct Cli {
     /// Skip master password prompt (use with environment variable)
     #[arg(long, global = true)]
     no_prompt: bool,
+
+    /// Print machine-readable JSON (list, find, stats, status, generate)
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Accept a weak master password when creating a vault or changing its password
+    #[arg(long, global = true)]
+    weak_master_ok: bool,
+
+    /// File of common passwords (one per line, optionally .gz) that strength checks reject
+    #[arg(long, global = true, value_name = "PATH")]
+    blocklist: Option<std::path::PathBuf>,
+}
+
+/// Lowest strength score ("Fair") accepted for a new master password unless
+/// PASSMANN_MIN_MASTER_SCORE says otherwise
+const DEFAULT_MIN_MASTER_SCORE: u64 = 60;
+
+/// Seconds before a copied password is wiped from the clipboard unless
+/// PASSMANN_CLIPBOARD_CLEAR_SECONDS or `--clear-after` says otherwise (0 keeps it)
+const DEFAULT_CLIPBOARD_CLEAR_SECONDS: u64 = 30;
+/// Longest auto-lock timeout `create-local --timeout` accepts
+const MAX_LOCK_TIMEOUT_MINUTES: u32 = 24 * 60;
+
+fn lock_timeout_parser() -> clap::builder::RangedU64ValueParser<u32> {
+    clap::builder::RangedU64ValueParser::<u32>::new().range(1..=MAX_LOCK_TIMEOUT_MINUTES as u64)
 }
 
 #[derive(Subcommand)]
@@ -39,14 +94,44 @@ enum Commands {
     Add(AddArgs),
     /// List all password entries
     List(ListArgs),
+    /// Show entries grouped by folder (services like "work/aws")
+    Tree,
     /// Search for specific entries
     Find(FindArgs),
+    /// Show the password of a single entry
+    Reveal(RevealArgs),
+    /// Open an entry's URL in the default browser
+    Open(OpenArgs),
+    /// Print a shell statement exporting an entry's password (for `eval`)
+    Env(EnvArgs),
+    /// Show when an entry was last used
+    Usage(UsageArgs),
+    /// Rename an entry's service, keeping its id and history
+    Rename(RenameArgs),
+    /// Edit an entry's notes in $EDITOR
+    Note { service: String },
+    /// Find entries with the same service and username and remove or merge them
+    Dedupe(DedupeArgs),
+    /// Require the master password again before an entry's password is shown
+    Protect { service: String },
+    /// Remove the extra master password check from an entry
+    Unprotect { service: String },
+    /// Manage an entry's custom fields
+    Field(FieldArgs),
+    /// Set the password rules an entry's site enforces, used when regenerating it
+    Policy(PolicyArgs),
     /// Delete entries matching a pattern
     Delete(DeleteArgs),
     /// Show vault status and statistics
     Status,
+    /// List expired and soon-to-expire passwords
+    Expiring,
     /// Show recent audit logs
     Logs(LogsArgs),
+    /// Export or summarize security events from the audit log
+    Audit(AuditArgs),
+    /// Check passwords against known breaches (Have I Been Pwned)
+    AuditBreaches(AuditBreachesArgs),
     /// Generate secure passwords
     Generate(GenerateArgs),
     /// Export vault data
@@ -58,7 +143,21 @@ enum Commands {
     /// Benchmark crypto performance
     Benchmark,
     /// Show vault statistics and health
-    Stats,
+    Stats(StatsArgs),
+    /// Show how vault health has changed across recent saves
+    Trend(TrendArgs),
+    /// Recompute every entry's stored password strength
+    ///
+    /// Strength scores are cached on each entry and reused by list and stats.
+    /// Run this after upgrading PassMann if the scoring has changed.
+    RefreshStrength,
+    /// Compute a site password from the master password without storing it
+    ///
+    /// Derived passwords are recomputed on demand and never touch the vault.
+    /// Changing the master password changes every derived password.
+    Derive(DeriveArgs),
+    /// Replace weak passwords with generated ones
+    FixWeak(FixWeakArgs),
     /// Check password strength
     CheckStrength { password: String },
     /// Cloud synchronization commands
@@ -66,13 +165,44 @@ enum Commands {
     /// Upload vault to cloud storage
     Upload,
     /// Download vault from cloud storage
-    Download,
+    Download(DownloadArgs),
     /// Show cloud sync status
     CloudStatus,
+    /// List devices that have synced with the cloud
+    Devices(DevicesArgs),
     /// Create ultra-secure local vault
     CreateLocal(CreateLocalArgs),
     /// Use local vault (offline mode)
     Local(LocalArgs),
+    /// Reclaim space left by deleted entries in the vault database
+    Compact(CompactArgs),
+    /// Restore the vault from an automatic backup
+    Restore(RestoreArgs),
+    /// Check that the vault file is intact, decrypts and its entries are consistent
+    Verify,
+    /// Interactive terminal session
+    #[cfg(feature = "tui")]
+    Tui,
+    /// Overwrite a file with random data, then delete it (e.g. a plaintext export)
+    ///
+    /// Best-effort only: SSDs and copy-on-write filesystems may keep the old data.
+    Shred {
+        file: std::path::PathBuf,
+        /// Number of overwrite passes
+        #[arg(long, default_value_t = shred::DEFAULT_PASSES)]
+        passes: usize,
+        /// Skip confirmation prompt
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Print a shell completion script
+    Completions {
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+    /// Empty the clipboard after a delay; started in the background by `-c`
+    #[command(hide = true)]
+    ClearClipboard { seconds: u64 },
 }
 
 #[derive(Args)]
@@ -89,6 +219,12 @@ struct AddArgs {
     /// Copy password to clipboard after adding
     #[arg(short, long)]
     clipboard: bool,
+    /// Expire the password after this many days
+    #[arg(long, value_name = "DAYS")]
+    set_expiration: Option<u32>,
+    /// Don't check the password against the ones other entries use
+    #[arg(long)]
+    allow_reuse: bool,
 }
 
 #[derive(Args)]
@@ -102,24 +238,180 @@ struct ListArgs {
     /// Sort by service name
     #[arg(long)]
     sort: bool,
+    /// Only entries with this tag
+    #[arg(long)]
+    tag: Option<String>,
+    /// Only entries in this folder or its subfolders (e.g. "work")
+    #[arg(long)]
+    folder: Option<String>,
+    /// Show at most this many entries
+    #[arg(long)]
+    limit: Option<usize>,
+    /// Skip this many entries
+    #[arg(long, conflicts_with = "page")]
+    offset: Option<usize>,
+    /// Show this page (1-based) of --limit entries, 20 by default
+    #[arg(long, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    page: Option<usize>,
 }
 
+/// Page size for `list --page` without `--limit`
+const DEFAULT_PAGE_SIZE: usize = 20;
+
 #[derive(Args)]
 struct FindArgs {
-    /// Search term (searches in service and username)
+    /// Search term (searches service, username, URL, notes and tags)
     query: String,
     /// Case-sensitive search
     #[arg(short, long)]
     case_sensitive: bool,
+    /// Fuzzy match and rank results by score (e.g. "githb" finds github)
+    #[arg(short, long)]
+    fuzzy: bool,
     /// Show passwords in results
     #[arg(short, long)]
     show_passwords: bool,
 }
 
+#[derive(Args)]
+struct RevealArgs {
+    /// Service to reveal (matched like `find`)
+    service: String,
+    /// Copy the password to the clipboard instead of printing it
+    #[arg(long)]
+    clip: bool,
+    /// Clear the password from the terminal after this many seconds
+    #[arg(short, long, value_name = "SECONDS")]
+    timeout: Option<u64>,
+}
+
+#[derive(Args)]
+struct EnvArgs {
+    /// Service to export (matched like `find`)
+    service: String,
+    /// Variable name (defaults to one derived from the service, e.g. GITHUB_PASSWORD)
+    #[arg(long, value_name = "NAME")]
+    var: Option<String>,
+    /// Shell syntax to print
+    #[arg(long, value_enum, default_value = "sh")]
+    format: shellenv::ShellFormat,
+    /// Confirm that the password may be printed and placed in the environment
+    #[arg(long)]
+    expose: bool,
+}
+
+#[derive(Args)]
+struct UsageArgs {
+    /// Service to report on (matched like `find`)
+    service: String,
+}
+
+#[derive(Args)]
+struct OpenArgs {
+    /// Service to open (matched like `find`)
+    service: String,
+    /// Copy the password to the clipboard before opening
+    #[arg(short, long)]
+    copy: bool,
+}
+
+#[derive(Args)]
+struct RenameArgs {
+    /// Current service name (matched like `find`)
+    old_service: String,
+    /// New service name
+    new_service: String,
+    /// Pick the entry with this username when several match
+    #[arg(short, long)]
+    username: Option<String>,
+    /// Merge without asking if the new service already has this username
+    #[arg(short, long)]
+    force: bool,
+}
+
+#[derive(Args)]
+struct DedupeArgs {
+    /// Keep the newest entry of each group without asking
+    #[arg(long)]
+    auto: bool,
+}
+
+/// What to do with one group of duplicates
+enum DedupeChoice {
+    /// Keep the entry at this index and delete the others
+    Keep(usize),
+    /// Fold every entry into the newest one
+    Merge,
+    Skip,
+}
+
+#[derive(Args)]
+struct FieldArgs {
+    #[command(subcommand)]
+    command: FieldCommands,
+}
+
+#[derive(Subcommand)]
+enum FieldCommands {
+    /// Add or replace a custom field
+    Set {
+        service: String,
+        key: String,
+        value: String,
+        /// Mask the value in listings (e.g. recovery codes)
+        #[arg(long)]
+        secret: bool,
+    },
+    /// Print a custom field's value
+    Get { service: String, key: String },
+    /// Remove a custom field
+    Remove { service: String, key: String },
+}
+
+#[derive(Args)]
+struct PolicyArgs {
+    #[command(subcommand)]
+    command: PolicyCommands,
+}
+
+#[derive(Subcommand)]
+enum PolicyCommands {
+    /// Store a generator policy on an entry
+    Set {
+        service: String,
+        /// Length of generated passwords
+        #[arg(short, long, default_value = "20")]
+        length: usize,
+        /// Leave out symbols
+        #[arg(long)]
+        no_symbols: bool,
+        /// Leave out digits
+        #[arg(long)]
+        no_digits: bool,
+        /// Leave out uppercase letters
+        #[arg(long)]
+        no_uppercase: bool,
+        /// Characters the site rejects
+        #[arg(long, default_value = "")]
+        exclude: String,
+    },
+    /// Show an entry's generator policy
+    Show { service: String },
+    /// Go back to the global generator defaults for an entry
+    Clear { service: String },
+}
+
 #[derive(Args)]
 struct DeleteArgs {
     /// Service or pattern to delete
-    pattern: String,
+    #[arg(required_unless_present = "tag", conflicts_with = "tag")]
+    pattern: Option<String>,
+    /// Delete every entry with this tag instead (preview with `list --tag`)
+    #[arg(long)]
+    tag: Option<String>,
+    /// List the entries that would be deleted without changing the vault
+    #[arg(long)]
+    dry_run: bool,
     /// Skip confirmation prompt
     #[arg(short, long)]
     force: bool,
@@ -135,17 +427,158 @@ struct LogsArgs {
     since: Option<String>,
 }
 
+#[derive(Args)]
+struct AuditArgs {
+    #[command(subcommand)]
+    command: AuditCommands,
+}
+
+#[derive(Subcommand)]
+enum AuditCommands {
+    /// Export security events (metadata only, never secrets)
+    Export {
+        /// Export format (json, csv)
+        #[arg(short, long, default_value = "json")]
+        format: String,
+        /// Only events newer than this (e.g., "1h", "30m", "7d")
+        #[arg(short, long)]
+        since: Option<String>,
+        /// Lowest severity to include (info, low, medium, high, critical)
+        #[arg(long)]
+        min_severity: Option<String>,
+        /// Only events of this type (e.g., data_access)
+        #[arg(short = 't', long = "type")]
+        event_type: Option<String>,
+        /// Write to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Check the audit log's integrity chain for tampering
+    Verify,
+    /// Summarize recent security events
+    Report {
+        /// Hours of history to cover
+        #[arg(long, default_value = "24")]
+        hours: u32,
+    },
+}
+
+#[derive(Args)]
+struct AuditBreachesArgs {
+    /// Only re-check entries last checked more than this many days ago
+    /// (entries changed since their last check are always re-checked)
+    #[arg(long, value_name = "DAYS")]
+    stale: Option<u32>,
+}
+
+#[derive(Args)]
+struct StatsArgs {
+    /// Passwords unchanged for more than this many days count as old
+    #[arg(long, default_value_t = stats::DEFAULT_AGE_THRESHOLD_DAYS)]
+    age_threshold: i64,
+    /// Number of oldest passwords to list
+    #[arg(long, default_value = "5")]
+    oldest: usize,
+}
+
+#[derive(Args)]
+struct DeriveArgs {
+    /// Site the password is for (case-insensitive), e.g. github.com
+    site: String,
+    /// Bump to rotate the password for this site
+    #[arg(long, default_value = "1")]
+    counter: u32,
+    /// Password length
+    #[arg(short, long, default_value = "20")]
+    length: usize,
+    /// Leave out symbols
+    #[arg(long)]
+    no_symbols: bool,
+    /// Copy the password to the clipboard instead of printing it
+    #[arg(short, long)]
+    clipboard: bool,
+}
+
+#[derive(Args)]
+struct TrendArgs {
+    /// Number of most recent snapshots to show
+    #[arg(short = 'n', long, default_value = "20")]
+    last: usize,
+}
+
+#[derive(Args)]
+struct FixWeakArgs {
+    /// Passwords scoring below this (0-100) count as weak
+    #[arg(long, default_value = "60")]
+    threshold: u32,
+    /// Length of the generated replacements (entries with a policy use its length)
+    #[arg(long, default_value = "20")]
+    min_length: usize,
+    /// Include symbols in the replacements (unless an entry's policy forbids them)
+    #[arg(short, long)]
+    symbols: bool,
+    /// Replace every weak password without asking
+    #[arg(short, long)]
+    yes: bool,
+    /// Don't check replacements against the passwords other entries use
+    #[arg(long)]
+    allow_reuse: bool,
+}
+
 #[derive(Args)]
 struct SyncArgs {
     /// Force sync even if there are conflicts
     #[arg(short, long)]
     force: bool,
+    /// Replace local entries with the cloud vault instead of merging (asks first)
+    #[arg(long)]
+    replace_local: bool,
     /// Dry run - show what would be synced
     #[arg(short, long)]
     dry_run: bool,
     /// Specific device ID to sync with
     #[arg(short, long)]
     device: Option<String>,
+    /// Skip checksum verification of the cloud vault (debugging only)
+    #[arg(long)]
+    no_verify: bool,
+    /// Sync only entries changed since the last sync, one encrypted row per entry
+    #[arg(long)]
+    delta: bool,
+    /// How to settle entries edited on both sides (remembered for later syncs)
+    #[arg(long, value_enum)]
+    strategy: Option<merge::ConflictStrategy>,
+}
+
+#[derive(Args)]
+struct DownloadArgs {
+    /// Skip checksum verification of the cloud vault (debugging only)
+    #[arg(long)]
+    no_verify: bool,
+    /// Show what the cloud vault would change and ask before applying it
+    #[arg(long)]
+    preview: bool,
+    /// Apply the previewed changes without asking
+    #[arg(short, long, requires = "preview")]
+    yes: bool,
+}
+
+#[derive(Args)]
+struct DevicesArgs {
+    #[command(subcommand)]
+    command: Option<DeviceCommands>,
+}
+
+#[derive(Subcommand)]
+enum DeviceCommands {
+    /// Delete a device's cloud vault so it no longer syncs
+    Revoke {
+        /// Device ID as shown by `passmann devices`
+        device_id: String,
+        /// Skip confirmation prompt
+        #[arg(short, long)]
+        force: bool,
+    },
 }
 
 #[derive(Args)]
@@ -156,9 +589,29 @@ struct CreateLocalArgs {
     /// Security level (standard, high, military, paranoid)
     #[arg(short, long, default_value = "high")]
     security: String,
-    /// Auto-lock timeout in minutes
-    #[arg(short, long, default_value = "15")]
-    timeout: u64,
+    /// Auto-lock timeout in minutes (1-1440)
+    #[arg(short, long, default_value = "15", value_parser = lock_timeout_parser())]
+    timeout: u32,
+}
+
+#[derive(Args)]
+struct RestoreArgs {
+    /// Backup to restore (1 is the most recent)
+    #[arg(long)]
+    from: usize,
+    /// Skip confirmation prompt
+    #[arg(short, long)]
+    force: bool,
+}
+
+#[derive(Args)]
+struct CompactArgs {
+    /// Local vault whose database to compact
+    #[arg(short, long)]
+    path: Option<String>,
+    /// Vault database to compact (defaults to the local vault's database)
+    #[arg(long)]
+    db: Option<String>,
 }
 
 #[derive(Args)]
@@ -191,6 +644,21 @@ enum LocalCommands {
     Unlock,
     /// Create backup
     Backup,
+    /// Verify vault integrity
+    Verify {
+        /// Re-decrypt every entry instead of only checking the file checksum
+        #[arg(long)]
+        deep: bool,
+        /// Vault database to check (defaults to the local vault's database)
+        #[arg(long)]
+        db: Option<String>,
+    },
+    /// Encrypt a vault database written without SQLCipher
+    Migrate {
+        /// Vault database to migrate (defaults to the local vault's database)
+        #[arg(long)]
+        db: Option<String>,
+    },
 }
 
 #[derive(Args)]
@@ -201,12 +669,57 @@ struct GenerateArgs {
     /// Include symbols
     #[arg(short, long)]
     symbols: bool,
+    /// Leave out symbols (the default unless --symbols is given)
+    #[arg(long, conflicts_with = "symbols")]
+    no_symbols: bool,
+    /// Leave out digits
+    #[arg(long)]
+    no_digits: bool,
+    /// Leave out uppercase letters
+    #[arg(long)]
+    no_uppercase: bool,
+    /// Leave out look-alike characters (l, 1, I, O, 0, ...)
+    #[arg(long)]
+    no_ambiguous: bool,
+    /// Extra characters to leave out
+    #[arg(long, default_value = "")]
+    exclude: String,
+    /// Minimum number of lowercase letters
+    #[arg(long, default_value = "0")]
+    min_lower: usize,
+    /// Minimum number of uppercase letters
+    #[arg(long, default_value = "0")]
+    min_upper: usize,
+    /// Minimum number of digits
+    #[arg(long, default_value = "0")]
+    min_digits: usize,
+    /// Minimum number of symbols
+    #[arg(long, default_value = "0")]
+    min_symbols: usize,
+    /// Generate a numeric PIN instead of a password
+    #[arg(long)]
+    pin: bool,
+    /// PIN length (used with --pin)
+    #[arg(long, default_value = "6")]
+    pin_length: usize,
     /// Copy to clipboard
     #[arg(short, long)]
     clipboard: bool,
     /// Number of passwords to generate
     #[arg(short, long, default_value = "1")]
     count: usize,
+    /// Which generated password to copy (1-based)
+    #[arg(long, default_value = "1", requires = "clipboard", conflicts_with = "copy_all")]
+    index: usize,
+    /// Copy every generated password, one per line
+    #[arg(long, requires = "clipboard")]
+    copy_all: bool,
+    /// Don't print the passwords, only copy them
+    #[arg(short, long, requires = "clipboard")]
+    quiet: bool,
+    /// Seconds before the clipboard is cleared (0 keeps it)
+    #[arg(long, value_name = "SECONDS", requires = "clipboard")]
+    clear_after: Option<u64>,
 }
 
 #[derive(Args)]
@@ -216,13 +729,16 @@ struct ExportArgs {
     /// Export format (json, csv)
     #[arg(short, long, default_value = "json")]
     format: String,
+    /// Shred the export after this many seconds, for temporary exports
+    #[arg(long, value_name = "SECONDS")]
+    shred_after: Option<u64>,
 }
 
 #[derive(Args)]
 struct ImportArgs {
     /// Input file path
     input: String,
-    /// Input format (json, csv)
+    /// Input format (json, csv, chrome, 1password)
     #[arg(short, long, default_value = "json")]
     format: String,
     /// Skip confirmation
@@ -249,6 +765,28 @@ fn main() {
 }
 
 async fn run_command(cli: Cli) -> Result<()> {
+    blocklist::init(cli.blocklist.as_deref())?;
+
+    // Completions don't touch the vault, so don't ask for the master password
+    if let Commands::Completions { shell } = cli.command {
+        completions::write_completions(shell, &mut io::stdout());
+        return Ok(());
+    }
+    if let Commands::Shred { file, passes, force } = cli.command {
+        return handle_shred(&file, passes, force);
+    }
+    // These never open the main vault, so its master password isn't needed
+    // and no vault is created with it
+    match cli.command {
+        Commands::Generate(args) => return handle_generate(args, cli.json),
+        Commands::CheckStrength { password } => return handle_check_strength(&password),
+        Commands::Benchmark => return handle_benchmark(),
+        Commands::CreateLocal(args) => return handle_create_local(args, cli.weak_master_ok).await,
+        Commands::Local(args) => return handle_local_commands(args, cli.weak_master_ok).await,
+        Commands::ClearClipboard { seconds } => return handle_clear_clipboard(seconds),
+        _ => {}
+    }
+
     let master_password = if cli.no_prompt {
         get_password_from_env()?
     } else {
@@ -259,58 +797,124 @@ async fn run_command(cli: Cli) -> Result<()> {
         return Err("Master password cannot be empty".into());
     }
 
-    let mut vault = Vault::load(&master_password).unwrap_or_else(|err| {
-        if cli.verbose {
-            eprintln!("⚠️  Could not load existing vault ({}), creating new one", err);
+    // Verify must see load failures rather than fall back to a fresh vault
+    if let Commands::Verify = cli.command {
+        return handle_verify(&master_password);
+    }
+
+    // Derived passwords depend only on the master password, not the vault
+    if let Commands::Derive(args) = cli.command {
+        return handle_derive(args, &master_password);
+    }
+
+    // No vault yet, so this password is about to become the master password.
+    // Existing vaults are never checked, so nobody gets locked out.
+    if !Vault::vault_path().exists() {
+        check_master_password_strength(&master_password, cli.weak_master_ok)?;
+    }
+
+    // Only what this command appends to the audit log is trusted unchecked
+    let audit_log_start = audit::log_len(&audit_log_path());
+    let mut vault = match Vault::load(&master_password) {
+        Ok(vault) => vault,
+        Err(err) => {
+            // A vault that exists but can't be opened must not be replaced by a new one
+            if let Some(guidance) = verify::load_failure_guidance(err.as_ref()) {
+                eprintln!("💡 {}", guidance);
+                return Err(err);
+            }
+            if cli.verbose {
+                eprintln!("⚠️  Could not load existing vault ({}), creating new one", err);
+            }
+            Vault::new(900) // 15 minute timeout
         }
-        Vault::new(900) // 15 minute timeout
-    });
+    };
 
     // Check vault lock status
     if vault.check_and_handle_lock() {
         return Err("Vault is locked due to inactivity. Please restart the application.".into());
     }
 
+    // Sync and download bring the vault up to date with the cloud; every other
+    // change has to be uploaded later
+    let mut store = vault_store::VaultStore::new(&vault, !matches!(cli.command, Commands::Sync(_) | Commands::Download(_)))?;
+
+    let json = cli.json;
     match cli.command {
         Commands::Add(args) => handle_add(&mut vault, args)?,
-        Commands::List(args) => handle_list(&vault, args)?,
-        Commands::Find(args) => handle_find(&vault, args)?,
+        Commands::List(args) => handle_list(&mut vault, args, json, &master_password)?,
+        Commands::Tree => handle_tree(&vault)?,
+        Commands::Find(args) => handle_find(&mut vault, args, json, &master_password)?,
+        Commands::Reveal(args) => handle_reveal(&mut vault, args, &master_password)?,
+        Commands::Open(args) => handle_open(&mut vault, args, &master_password)?,
+        Commands::Env(args) => handle_env(&mut vault, args, &master_password)?,
+        Commands::Usage(args) => handle_usage(&vault, args)?,
+        Commands::Rename(args) => handle_rename(&mut vault, args)?,
+        Commands::Note { service } => handle_note(&mut vault, &service)?,
+        Commands::Dedupe(args) => handle_dedupe(&mut vault, args)?,
+        Commands::Protect { service } => handle_protect(&mut vault, &service, true, &master_password)?,
+        Commands::Unprotect { service } => handle_protect(&mut vault, &service, false, &master_password)?,
+        Commands::Field(args) => handle_field(&mut vault, args)?,
+        Commands::Policy(args) => handle_policy(&mut vault, args, &master_password)?,
         Commands::Delete(args) => handle_delete(&mut vault, args)?,
-        Commands::Status => handle_status(&vault)?,
+        Commands::Status => handle_status(&vault, json)?,
+        Commands::Expiring => handle_expiring(&vault)?,
         Commands::Logs(args) => handle_logs(&vault, args)?,
-        Commands::Generate(args) => handle_generate(args)?,
+        Commands::Audit(args) => handle_audit(&vault, args, &master_password)?,
+        Commands::AuditBreaches(args) => handle_audit_breaches(&mut vault, args).await?,
         Commands::Export(args) => handle_export(&vault, args)?,
         Commands::Import(args) => handle_import(&mut vault, args)?,
         Commands::ChangePassword => handle_change_password(&mut vault, &master_password)?,
-        Commands::Benchmark => handle_benchmark()?,
-        Commands::Stats => handle_stats(&vault)?,
-        Commands::CheckStrength { password } => handle_check_strength(&password)?,
+        Commands::Stats(args) => handle_stats(&vault, args, json)?,
+        Commands::Trend(args) => handle_trend(args, &master_password)?,
+        Commands::RefreshStrength => handle_refresh_strength(&mut vault)?,
+        Commands::FixWeak(args) => handle_fix_weak(&mut vault, args, &master_password)?,
         Commands::Sync(args) => handle_sync(&mut vault, args, &master_password).await?,
         Commands::Upload => handle_upload(&vault, &master_password).await?,
-        Commands::Download => handle_download(&mut vault, &master_password).await?,
-        Commands::CloudStatus => handle_cloud_status().await?,
-        Commands::CreateLocal(args) => handle_create_local(args).await?,
-        Commands::Local(args) => handle_local_commands(args).await?,
+        Commands::Download(args) => handle_download(&mut vault, &master_password, args).await?,
+        Commands::CloudStatus => handle_cloud_status(&master_password).await?,
+        Commands::Devices(args) => handle_devices(args).await?,
+        Commands::Compact(args) => handle_compact(args, &master_password)?,
+        Commands::Restore(args) => handle_restore(&mut vault, &mut store, args, &master_password)?,
+        Commands::Verify | Commands::Completions { .. } | Commands::Shred { .. } | Commands::Derive(_)
+            | Commands::Generate(_) | Commands::CheckStrength { .. } | Commands::Benchmark
+            | Commands::CreateLocal(_) | Commands::Local(_) | Commands::ClearClipboard { .. } => unreachable!("handled before the vault is loaded"),
+        #[cfg(feature = "tui")]
+        Commands::Tui => tui::run(&mut vault, &mut store, &master_password)?,
     }
 
     // Save vault
-    if let Err(err) = vault.save(&master_password) {
-        eprintln!("❌ Error saving vault: {}", err);
-    } else if cli.verbose {
-        println!("💾 Vault saved successfully.");
+    match store.save(&mut vault, &master_password) {
+        Err(err) => eprintln!("❌ Error saving vault: {}", err),
+        Ok(_) if cli.verbose => eprintln!("💾 Vault saved successfully."),
+        Ok(_) => {}
     }
 
     // Persist audit log
     if let Err(err) = vault.persist_audit_log() {
         eprintln!("⚠️  Warning: Could not save audit log: {}", err);
+    } else {
+        match audit::seal(&audit_log_path(), &master_password, audit::audit_encryption_enabled(), audit_log_start) {
+            Err(err) => eprintln!("⚠️  Warning: Could not seal audit log: {}", err),
+            Ok(outcome) => {
+                if let Some(quarantine) = outcome.quarantine {
+                    eprintln!("🚨 Warning: Audit log failed its integrity check; {} event(s) moved to {}",
+                        quarantine.events, quarantine.path.display());
+                }
+                if let Err(err) = audit::rotate_if_needed(&audit_log_path(), audit::max_log_bytes(), audit::log_archives()) {
+                    eprintln!("⚠️  Warning: Could not rotate audit log: {}", err);
+                }
+            }
+        }
     }
 
     Ok(())
 }
 
 fn get_secure_master_password() -> Result<String> {
-    print!("🔐 Enter master password: ");
-    io::stdout().flush()?;
+    // Prompt on stderr so stdout stays clean for --json
+    eprint!("🔐 Enter master password: ");
+    io::stderr().flush()?;
     
     let password = passmann_shared::security::get_secure_password("");
     
@@ -321,14 +925,41 @@ fn get_secure_master_password() -> Result<String> {
     Ok(password)
 }
 
+/// Reject a weak new master password, explaining how to improve it
+fn check_master_password_strength(password: &str, weak_master_ok: bool) -> Result<()> {
+    let min_score = std::env::var("PASSMANN_MIN_MASTER_SCORE")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_MIN_MASTER_SCORE);
+    let strength = estimate_password_strength(password);
+    if (strength.score as u64) >= min_score {
+        return Ok(());
+    }
+
+    if weak_master_ok {
+        eprintln!("⚠️  Using a weak master password ({}/100) because of --weak-master-ok", strength.score);
+        return Ok(());
+    }
+    eprintln!("❌ Master password is too weak: {}/100 ({}), at least {} required", strength.score, strength.level, min_score);
+    for suggestion in &strength.feedback {
+        eprintln!("  • {}", suggestion);
+    }
+    Err("Choose a stronger master password, or pass --weak-master-ok to accept it".into())
+}
+
 fn get_password_from_env() -> Result<String> {
     std::env::var("PASSMANN_MASTER_PASSWORD")
         .map_err(|_| "PASSMANN_MASTER_PASSWORD environment variable not set".into())
 }
 
 fn handle_add(vault: &mut Vault, args: AddArgs) -> Result<()> {
+    let entries = vault.get_entries().map(|entries| entries.as_slice()).unwrap_or_default();
     let password = if args.generate || args.password.is_none() {
-        let generated = generate_password(16, true);
+        let generated = if args.allow_reuse {
+            generate_password(16, true)?
+        } else {
+            reuse::generate_unused(entries, None, || generate_password(16, true))?
+        };
         println!("🔑 Generated password: {}", generated);
         
         if args.clipboard {
@@ -337,74 +968,182 @@ fn handle_add(vault: &mut Vault, args: AddArgs) -> Result<()> {
         }
         generated
     } else {
-        args.password.unwrap()
+        let password = args.password.unwrap();
+        let used = reuse::used_by(entries, &password, None);
+        if !args.allow_reuse && !used.is_empty() {
+            println!("⚠️  This password is already used by: {}", used.join(", "));
+            println!("💡 A breach of one of those sites would expose this account too");
+        }
+        password
     };
 
     vault.add_entry(args.service.clone(), args.username, password);
     println!("✅ Entry added for '{}'", args.service);
     
+    if let Some(days) = args.set_expiration {
+        if let Some(entry) = vault.get_entries_mut().and_then(|entries| entries.last_mut()) {
+            entry.set_expiration(days);
+            println!("⏳ Password expires in {} day(s)", days);
+        }
+    }
+    
     Ok(())
 }
 
-fn handle_list(vault: &Vault, args: ListArgs) -> Result<()> {
-    if let Some(entries) = vault.get_entries() {
-        if entries.is_empty() {
-            println!("📭 No entries found in vault.");
-            return Ok(());
-        }
-
-        let mut sorted_entries = entries.clone();
-        if args.sort {
-            sorted_entries.sort_by(|a, b| a.service.cmp(&b.service));
-        }
-
-        println!("🔐 Vault Entries ({} total):", sorted_entries.len());
-        println!("{:=<90}", "");
+fn handle_list(vault: &mut Vault, args: ListArgs, json: bool, master_password: &str) -> Result<()> {
+    // Filter, then sort, then paginate
+    let mut listed: Vec<&Entry> = vault.get_entries()
+        .map(|entries| entries.iter()
+            .filter(|e| args.tag.as_deref().is_none_or(|tag| has_tag(e, tag)))
+            .filter(|e| args.folder.as_deref().is_none_or(|folder| folders::in_folder(&e.service, folder)))
+            .collect())
+        .unwrap_or_default();
+    if args.sort {
+        listed.sort_by(|a, b| a.service.cmp(&b.service));
+    }
+    let total = listed.len();
+    let range = page_range(total, args.limit, args.offset, args.page);
+    let ids: Vec<uuid::Uuid> = listed[range.clone()].iter().map(|e| e.id).collect();
+    
+    let unlocked = unlock_protected(vault, master_password, args.show_passwords, &ids)?;
+    let show = |entry: &Entry| args.show_passwords && (unlocked || !entry.settings.require_master_password);
+    let all_entries: &[Entry] = vault.get_entries().map(|entries| entries.as_slice()).unwrap_or_default();
+    let page = entries_by_id(all_entries, &ids);
+    
+    if json {
+        return output::print_json(&output::ListOutput {
+            count: page.len(),
+            total,
+            entries: page.iter().map(|e| output::EntryOutput::new(e, show(e))).collect(),
+        });
+    }
+    
+    if all_entries.is_empty() {
+        println!("📭 No entries found in vault.");
+        return Ok(());
+    }
+    if let Some(tag) = &args.tag && total == 0 {
+        println!("📭 No entries tagged '{}'.", tag);
+        return Ok(());
+    }
+    if let Some(folder) = &args.folder && total == 0 {
+        println!("📭 No entries in folder '{}'.", folder);
+        return Ok(());
+    }
 
-        for (i, entry) in sorted_entries.iter().enumerate() {
-            let password_display = if args.show_passwords {
-                &entry.password
-            } else {
-                "••••••••"
-            };
+    println!("🔐 Vault Entries ({} total):", total);
+    println!("{:=<90}", "");
 
-            if args.detailed {
-                println!("{:3}. 🌐 Service: {}", i + 1, entry.service);
-                println!("     👤 User:    {}", entry.username);
-                println!("     🔑 Pass:    {}", password_display);
-                println!("     📊 Strength: {}", get_password_strength_indicator(&entry.password));
-                println!("{:-<90}", "");
-            } else {
-                println!("{:3}. 🌐 {} | 👤 {} | 🔑 {}", 
-                    i + 1, entry.service, entry.username, password_display);
+    for (i, entry) in page.iter().enumerate() {
+        let number = range.start + i + 1;
+        let password_display = if show(entry) {
+            &entry.password
+        } else {
+            "••••••••"
+        };
+
+        if args.detailed {
+            println!("{:3}. 🌐 Service: {}", number, entry.service);
+            println!("     👤 User:    {}", entry.username);
+            println!("     🔑 Pass:    {}", password_display);
+            println!("     📊 Strength: {}", get_password_strength_indicator(stats::strength_score(entry, chrono::Utc::now())));
+            for (name, value) in fields::display_fields(&entry.custom_fields) {
+                println!("     🏷️  {}: {}", name, value);
             }
+            println!("{:-<90}", "");
+        } else {
+            println!("{:3}. 🌐 {} | 👤 {} | 🔑 {}", 
+                number, entry.service, entry.username, password_display);
         }
-        
-        if !args.show_passwords {
-            println!("\n💡 Use --show-passwords to reveal passwords");
+    }
+    
+    if args.limit.is_some() || args.offset.is_some() || args.page.is_some() {
+        if page.is_empty() {
+            println!("\n📄 Nothing to show past entry {}", total);
+        } else {
+            println!("\n📄 Showing {}–{} of {}", range.start + 1, range.end, total);
         }
     }
+    if !args.show_passwords {
+        println!("\n💡 Use --show-passwords to reveal passwords");
+    }
     
     Ok(())
 }
 
-fn handle_find(vault: &Vault, args: FindArgs) -> Result<()> {
+/// Entries to show out of `total`. `--page` is 1-based and uses `--limit` as
+/// its page size; with no flags everything is shown.
+fn page_range(total: usize, limit: Option<usize>, offset: Option<usize>, page: Option<usize>) -> std::ops::Range<usize> {
+    let size = limit.unwrap_or(if page.is_some() { DEFAULT_PAGE_SIZE } else { total });
+    let start = match page {
+        Some(page) => page.saturating_sub(1).saturating_mul(size),
+        None => offset.unwrap_or(0),
+    }.min(total);
+    start..start.saturating_add(size).min(total)
+}
+
+fn handle_tree(vault: &Vault) -> Result<()> {
+    let entries = vault.get_entries().map(|entries| entries.as_slice()).unwrap_or_default();
+    if entries.is_empty() {
+        println!("📭 No entries found in vault.");
+        return Ok(());
+    }
+
+    println!("🗂️  Vault ({} entries)", entries.len());
+    for line in folders::FolderNode::build(entries).render() {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+fn handle_find(vault: &mut Vault, args: FindArgs, json: bool, master_password: &str) -> Result<()> {
+    let entries: &[Entry] = vault.get_entries().map(|entries| entries.as_slice()).unwrap_or_default();
+    // Best match first when fuzzy, vault order otherwise
+    let ids: Vec<uuid::Uuid> = if args.fuzzy {
+        use fuzzy_matcher::{FuzzyMatcher, skim::SkimMatcherV2};
+        
+        let matcher = if args.case_sensitive {
+            SkimMatcherV2::default().respect_case()
+        } else {
+            SkimMatcherV2::default().ignore_case()
+        };
+        let mut scored: Vec<(i64, uuid::Uuid)> = entries.iter()
+            .filter_map(|entry| searchable_fields(entry)
+                .filter_map(|field| matcher.fuzzy_match(field, &args.query))
+                .max()
+                .map(|score| (score, entry.id)))
+            .collect();
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        scored.into_iter().map(|(_, id)| id).collect()
+    } else if args.case_sensitive {
+        entries.iter()
+            .filter(|entry| searchable_fields(entry).any(|field| field.contains(&args.query)))
+            .map(|entry| entry.id)
+            .collect()
+    } else {
+        let query = args.query.to_lowercase();
+        let hits = SearchIndex::build(entries).search(&query);
+        entries.iter()
+            .filter(|entry| match &hits {
+                Some(hits) => hits.contains(&entry.id),
+                None => searchable_fields(entry).any(|field| field.to_lowercase().contains(&query)),
+            })
+            .map(|entry| entry.id)
+            .collect()
+    };
+    let unlocked = unlock_protected(vault, master_password, args.show_passwords, &ids)?;
+    let show = |entry: &Entry| args.show_passwords && (unlocked || !entry.settings.require_master_password);
+    
     if let Some(entries) = vault.get_entries() {
-        let matches: Vec<_> = entries.iter().filter(|entry| {
-            let service_match = if args.case_sensitive {
-                entry.service.contains(&args.query)
-            } else {
-                entry.service.to_lowercase().contains(&args.query.to_lowercase())
-            };
-            
-            let username_match = if args.case_sensitive {
-                entry.username.contains(&args.query)
-            } else {
-                entry.username.to_lowercase().contains(&args.query.to_lowercase())
-            };
-            
-            service_match || username_match
-        }).collect();
+        let matches = entries_by_id(entries, &ids);
+
+        if json {
+            return output::print_json(&output::FindOutput {
+                count: matches.len(),
+                entries: matches.iter().map(|e| output::EntryOutput::new(e, show(e))).collect(),
+                query: args.query,
+            });
+        }
 
         if matches.is_empty() {
             println!("🔍 No entries found matching '{}'", args.query);
@@ -413,7 +1152,7 @@ fn handle_find(vault: &Vault, args: FindArgs) -> Result<()> {
             println!("{:-<80}", "");
             
             for (i, entry) in matches.iter().enumerate() {
-                let password_display = if args.show_passwords {
+                let password_display = if show(entry) {
                     &entry.password
                 } else {
                     "••••••••"
@@ -423,58 +1162,596 @@ fn handle_find(vault: &Vault, args: FindArgs) -> Result<()> {
                     i + 1, entry.service, entry.username, password_display);
             }
         }
+    } else if json {
+        return output::print_json(&output::FindOutput { query: args.query, count: 0, entries: Vec::new() });
     }
     
     Ok(())
 }
 
-fn handle_delete(vault: &mut Vault, args: DeleteArgs) -> Result<()> {
-    if !args.force {
-        print!("⚠️  Are you sure you want to delete entries matching '{}'? (y/N): ", args.pattern);
-        io::stdout().flush()?;
-        
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
-        
-        if !input.trim().to_lowercase().starts_with('y') {
-            println!("❌ Delete operation cancelled.");
-            return Ok(());
+/// The entries with `ids`, in that order, looked up through a map rather
+/// than a scan per id
+fn entries_by_id<'a>(entries: &'a [Entry], ids: &[uuid::Uuid]) -> Vec<&'a Entry> {
+    let by_id: std::collections::HashMap<uuid::Uuid, &Entry> = entries.iter().map(|e| (e.id, e)).collect();
+    ids.iter().filter_map(|id| by_id.get(id).copied()).collect()
+}
+
+/// Find the single entry whose service matches `query`, asking the user to
+/// pick one when several do
+fn select_entry<'a>(vault: &'a Vault, query: &str) -> Result<&'a Entry> {
+    select_entry_by(vault, query, None)
+}
+
+/// `select_entry`, optionally narrowed to one username
+fn select_entry_by<'a>(vault: &'a Vault, query: &str, username: Option<&str>) -> Result<&'a Entry> {
+    let needle = query.to_lowercase();
+    let matches: Vec<&Entry> = vault.get_entries()
+        .map(|entries| entries.iter()
+            .filter(|e| e.service.to_lowercase().contains(&needle))
+            .filter(|e| username.is_none_or(|username| e.username == username))
+            .collect())
+        .unwrap_or_default();
+
+    match matches.len() {
+        0 => Err(format!("No entry found matching '{}'", query).into()),
+        1 => Ok(matches[0]),
+        _ => {
+            println!("🎯 {} entries match '{}':", matches.len(), query);
+            for (i, entry) in matches.iter().enumerate() {
+                println!("{:2}. 🌐 {} | 👤 {}", i + 1, entry.service, entry.username);
+            }
+            print!("Select entry (1-{}): ", matches.len());
+            io::stdout().flush()?;
+
+            let mut input = String::new();
+            io::stdin().read_line(&mut input)?;
+            match input.trim().parse::<usize>() {
+                Ok(n) if (1..=matches.len()).contains(&n) => Ok(matches[n - 1]),
+                _ => Err("Invalid selection".into()),
+            }
         }
     }
+}
 
-    let removed = vault.remove_entries(&args.pattern);
-    if removed > 0 {
-        println!("🗑️  Deleted {} entry(ies) matching '{}'", removed, args.pattern);
-    } else {
-        println!("❌ No entries found matching '{}'", args.pattern);
+/// Re-prompt for the master password before showing a protected entry.
+/// A wrong answer is recorded as a security violation.
+fn confirm_master_password(vault: &mut Vault, master_password: &str, reason: &str) -> Result<bool> {
+    eprint!("🔐 {}; re-enter master password: ", reason);
+    io::stderr().flush()?;
+    let confirmation = passmann_shared::security::get_secure_password("");
+    // Compare digests so the check doesn't leak timing
+    if blake3::hash(confirmation.as_bytes()) == blake3::hash(master_password.as_bytes()) {
+        return Ok(true);
     }
-    
-    Ok(())
+    record_audit(vault, "security_violation", &format!("Failed master password confirmation: {}", reason));
+    Ok(false)
 }
 
-fn handle_status(vault: &Vault) -> Result<()> {
-    if let Some(time_left) = vault.get_lock_status() {
-        let minutes = time_left.as_secs() / 60;
-        let seconds = time_left.as_secs() % 60;
-        println!("🔓 Vault Status: UNLOCKED");
-        println!("⏰ Auto-lock in: {}m {}s", minutes, seconds);
-    } else {
-        println!("🔒 Vault Status: No auto-lock configured");
+/// Whether passwords of protected entries may be shown: asks once, and only
+/// when `show` is set and one of `entries` is protected
+fn unlock_protected(vault: &mut Vault, master_password: &str, show: bool, entries: &[uuid::Uuid]) -> Result<bool> {
+    let wanted: std::collections::HashSet<&uuid::Uuid> = entries.iter().collect();
+    let protected = vault.get_entries()
+        .map(|all| all.iter().any(|e| e.settings.require_master_password && wanted.contains(&e.id)))
+        .unwrap_or(false);
+    if !show || !protected {
+        return Ok(true);
     }
+    let unlocked = confirm_master_password(vault, master_password, "Some entries are protected")?;
+    if !unlocked {
+        eprintln!("⚠️  Incorrect master password: protected entries stay masked");
+    }
+    Ok(unlocked)
+}
 
-    let stats = vault.get_vault_stats();
-    println!("📊 Total entries: {}", stats.total_entries);
-    println!("🏢 Unique services: {}", stats.unique_services);
-    
-    if stats.has_duplicates {
-        println!("⚠️  Duplicate services detected");
+fn handle_reveal(vault: &mut Vault, args: RevealArgs, master_password: &str) -> Result<()> {
+    let entry = select_entry(vault, &args.service)?;
+    let (id, service) = (entry.id, entry.service.clone());
+    let password = entry.password.clone();
+
+    if entry.settings.require_master_password
+        && !confirm_master_password(vault, master_password, &format!("'{}' is protected", service))?
+    {
+        return Err("Incorrect master password".into());
     }
 
-    // Show crypto benchmark
-    let benchmark_time = benchmark_key_derivation();
-    println!("⚡ Key derivation time: {}ms", benchmark_time.as_millis());
-    
-    if benchmark_time.as_millis() < 100 {
+    if args.clip {
+        copy_to_clipboard(&password)?;
+        println!("📋 Password for '{}' copied to clipboard", service);
+    } else if let Some(seconds) = args.timeout {
+        print!("🔑 {}: {}", service, password);
+        io::stdout().flush()?;
+        std::thread::sleep(std::time::Duration::from_secs(seconds));
+        // Return to the start of the line and erase it
+        print!("\r\x1b[2K");
+        println!("🔒 Password for '{}' hidden", service);
+    } else {
+        println!("🔑 {}: {}", service, password);
+    }
+
+    touch_entry(vault, id);
+    record_audit(vault, "data_access", &format!("Revealed password for '{}'", service));
+    Ok(())
+}
+
+fn handle_open(vault: &mut Vault, args: OpenArgs, master_password: &str) -> Result<()> {
+    let entry = select_entry(vault, &args.service)?;
+    let (id, service) = (entry.id, entry.service.clone());
+    let password = entry.password.clone();
+    let protected = entry.settings.require_master_password;
+    let Some(url) = entry.url.clone() else {
+        println!("🌐 '{}' has no URL saved", service);
+        return Ok(());
+    };
+
+    if args.copy && protected && !confirm_master_password(vault, master_password, &format!("'{}' is protected", service))? {
+        return Err("Incorrect master password".into());
+    }
+    if args.copy {
+        copy_to_clipboard(&password)?;
+        println!("📋 Password for '{}' copied to clipboard", service);
+        touch_entry(vault, id);
+    }
+
+    open::that(&url).map_err(|e| format!("Could not open '{}': {}", url, e))?;
+    println!("🌐 Opened {}", url);
+
+    let details = if args.copy { "Opened URL and copied password" } else { "Opened URL" };
+    record_audit(vault, "data_access", &format!("{} for '{}'", details, service));
+    Ok(())
+}
+
+fn handle_env(vault: &mut Vault, args: EnvArgs, master_password: &str) -> Result<()> {
+    if !args.expose {
+        eprintln!("⚠️  `passmann env` prints the password and puts it in the environment of every process");
+        eprintln!("   started from that shell. Re-run with --expose if that is what you want, e.g.");
+        eprintln!("   eval \"$(passmann env {} --expose)\"", args.service);
+        return Err("Refusing to print a password without --expose".into());
+    }
+
+    let entry = select_entry(vault, &args.service)?;
+    let (id, service) = (entry.id, entry.service.clone());
+    let password = entry.password.clone();
+    if entry.settings.require_master_password
+        && !confirm_master_password(vault, master_password, &format!("'{}' is protected", service))?
+    {
+        return Err("Incorrect master password".into());
+    }
+
+    let name = args.var.unwrap_or_else(|| shellenv::default_var_name(&service));
+    shellenv::validate_var_name(&name)?;
+    // Only the statement goes to stdout so it can be sourced directly
+    println!("{}", shellenv::export_line(args.format, &name, &password));
+    eprintln!("⚠️  {} now holds the password for '{}' wherever this output is sourced", name, service);
+
+    touch_entry(vault, id);
+    record_audit(vault, "data_access", &format!("Exported password for '{}' as ${}", service, name));
+    Ok(())
+}
+
+/// Record that an entry's password was revealed or copied
+fn touch_entry(vault: &mut Vault, id: uuid::Uuid) {
+    if let Some(entry) = vault.get_entries_mut().and_then(|entries| entries.iter_mut().find(|e| e.id == id)) {
+        stats::record_access(entry, chrono::Utc::now());
+    }
+}
+
+fn handle_policy(vault: &mut Vault, args: PolicyArgs, master_password: &str) -> Result<()> {
+    let service = match &args.command {
+        PolicyCommands::Set { service, .. } | PolicyCommands::Show { service } | PolicyCommands::Clear { service } => service,
+    };
+    let entry = select_entry(vault, service)?;
+    let (id, service) = (entry.id, entry.service.clone());
+    let path = generation_policies_path();
+    let mut policies = policies::load(&path, master_password)?;
+    let entries = vault.get_entries().map(|entries| entries.as_slice()).unwrap_or_default();
+
+    match args.command {
+        PolicyCommands::Set { length, no_symbols, no_digits, no_uppercase, exclude, .. } => {
+            let policy = GenerationPolicy {
+                length,
+                uppercase: !no_uppercase,
+                digits: !no_digits,
+                symbols: !no_symbols,
+                exclude,
+            };
+            generator::validate_policy(&policy)
+                .map_err(|e| format!("No password can satisfy this policy: {}", e))?;
+            policies.insert(id, policy);
+            policies::store(&path, master_password, policies, entries)?;
+            println!("✅ Generator policy set for '{}'", service);
+            record_audit(vault, "data_modification", &format!("Generator policy set for '{}'", service));
+        }
+        PolicyCommands::Show { .. } => match policies.get(&id) {
+            Some(policy) => {
+                let yes_no = |allowed: bool| if allowed { "yes" } else { "no" };
+                println!("📏 Generator policy for '{}'", service);
+                println!("{:-<60}", "");
+                println!("Length:    {}", policy.length);
+                println!("Uppercase: {}", yes_no(policy.uppercase));
+                println!("Digits:    {}", yes_no(policy.digits));
+                println!("Symbols:   {}", yes_no(policy.symbols));
+                if !policy.exclude.is_empty() {
+                    println!("Excluded:  {}", policy.exclude);
+                }
+            }
+            None => println!("ℹ️  '{}' uses the global generator defaults", service),
+        },
+        PolicyCommands::Clear { .. } => {
+            if policies.remove(&id).is_none() {
+                println!("ℹ️  '{}' has no generator policy", service);
+                return Ok(());
+            }
+            policies::store(&path, master_password, policies, entries)?;
+            println!("✅ Generator policy cleared for '{}'", service);
+            record_audit(vault, "data_modification", &format!("Generator policy cleared for '{}'", service));
+        }
+    }
+    Ok(())
+}
+
+fn handle_usage(vault: &Vault, args: UsageArgs) -> Result<()> {
+    let entry = select_entry(vault, &args.service)?;
+
+    println!("📈 Usage for '{}' ({})", entry.service, entry.username);
+    println!("{:-<60}", "");
+    match entry.last_accessed {
+        Some(at) => println!("Last used:   {}", at.format("%Y-%m-%d %H:%M UTC")),
+        None => println!("Last used:   never"),
+    }
+    Ok(())
+}
+
+fn handle_rename(vault: &mut Vault, args: RenameArgs) -> Result<()> {
+    let entry = select_entry_by(vault, &args.old_service, args.username.as_deref())?;
+    let (id, old_service, username) = (entry.id, entry.service.clone(), entry.username.clone());
+    let duplicate = vault.get_entries().and_then(|entries| entries.iter()
+        .find(|e| e.id != id && e.service.eq_ignore_ascii_case(&args.new_service) && e.username == username)
+        .map(|e| e.id));
+
+    let Some(target_id) = duplicate else {
+        if let Some(entry) = vault.get_entries_mut().and_then(|entries| entries.iter_mut().find(|e| e.id == id)) {
+            entry.service = args.new_service.clone();
+            entry.modified_at = chrono::Utc::now();
+        }
+        println!("✏️  Renamed '{}' to '{}'", old_service, args.new_service);
+        record_audit(vault, "data_modification", &format!("Renamed '{}' to '{}'", old_service, args.new_service));
+        return Ok(());
+    };
+
+    // Renaming would create a duplicate, so fold this entry into the existing one
+    if !args.force && !confirm_action(&format!("'{}' already has an entry for {}. Merge into it?", args.new_service, username))? {
+        println!("❌ Rename cancelled.");
+        return Ok(());
+    }
+    if let Some(entries) = vault.get_entries_mut()
+        && let Some(index) = entries.iter().position(|e| e.id == id)
+    {
+        let source = entries.remove(index);
+        if let Some(target) = entries.iter_mut().find(|e| e.id == target_id) {
+            merge::merge_entry_fields(target, &source);
+        }
+    }
+    merge::record_deletions([id])?;
+    println!("🔀 Merged '{}' into '{}' ({})", old_service, args.new_service, username);
+    record_audit(vault, "data_modification", &format!("Merged '{}' into '{}'", old_service, args.new_service));
+    Ok(())
+}
+
+fn handle_note(vault: &mut Vault, service: &str) -> Result<()> {
+    let entry = select_entry(vault, service)?;
+    let (id, service, current) = (entry.id, entry.service.clone(), entry.notes.clone().unwrap_or_default());
+
+    let edited = match notes::configured_editor() {
+        Some(editor) => {
+            println!("⚠️  Notes are stored encrypted, but are written to a temporary file while you edit them");
+            notes::edit_in_editor(&editor, &current)?
+        }
+        None => {
+            println!("📝 No $EDITOR set. Type the new notes for '{}', then press Ctrl-D:", service);
+            notes::read_from_stdin()?
+        }
+    };
+    let edited = edited.trim_end().to_string();
+
+    if edited == current.trim_end() {
+        println!("ℹ️  Notes for '{}' unchanged", service);
+        return Ok(());
+    }
+    if let Some(entry) = vault.get_entries_mut().and_then(|entries| entries.iter_mut().find(|e| e.id == id)) {
+        entry.notes = (!edited.is_empty()).then_some(edited);
+        entry.modified_at = chrono::Utc::now();
+    }
+    println!("✅ Notes updated for '{}'", service);
+    record_audit(vault, "data_modification", &format!("Edited notes on '{}'", service));
+    Ok(())
+}
+
+fn handle_dedupe(vault: &mut Vault, args: DedupeArgs) -> Result<()> {
+    // Decide on every group first; the groups borrow the vault
+    let mut plans: Vec<(Vec<uuid::Uuid>, String, DedupeChoice)> = Vec::new();
+    {
+        let groups = merge::duplicate_groups(vault.get_entries().map(|e| e.as_slice()).unwrap_or_default());
+        if groups.is_empty() {
+            println!("✅ No duplicate entries found");
+            return Ok(());
+        }
+        println!("🔁 Found {} group(s) of duplicate entries", groups.len());
+
+        for (i, group) in groups.iter().enumerate() {
+            println!("\n{}. 🌐 {} | 👤 {}", i + 1, group[0].service, group[0].username);
+            for (n, entry) in group.iter().enumerate() {
+                println!("   [{}] modified {}{}{}",
+                    n + 1,
+                    entry.modified_at.format("%Y-%m-%d %H:%M"),
+                    entry.url.as_deref().map(|url| format!(" | {}", url)).unwrap_or_default(),
+                    if n == 0 { " (newest)" } else { "" });
+            }
+
+            let choice = if args.auto {
+                DedupeChoice::Keep(0)
+            } else {
+                print!("   Keep which? [1-{}, m = merge into newest, s = skip] (default 1): ", group.len());
+                io::stdout().flush()?;
+                let mut input = String::new();
+                io::stdin().read_line(&mut input)?;
+                match input.trim().to_lowercase().as_str() {
+                    "" => DedupeChoice::Keep(0),
+                    "m" => DedupeChoice::Merge,
+                    "s" => DedupeChoice::Skip,
+                    other => match other.parse::<usize>() {
+                        Ok(n) if (1..=group.len()).contains(&n) => DedupeChoice::Keep(n - 1),
+                        _ => {
+                            println!("   ⚠️  Invalid choice, skipping this group");
+                            DedupeChoice::Skip
+                        }
+                    },
+                }
+            };
+            plans.push((group.iter().map(|e| e.id).collect(), group[0].service.clone(), choice));
+        }
+    }
+
+    let mut removed = 0;
+    for (ids, service, choice) in plans {
+        let (keep, merge_fields) = match choice {
+            DedupeChoice::Skip => continue,
+            DedupeChoice::Keep(index) => (ids[index], false),
+            DedupeChoice::Merge => (ids[0], true),
+        };
+        let Some(entries) = vault.get_entries_mut() else { break };
+        let (dropped, kept): (Vec<Entry>, Vec<Entry>) = std::mem::take(entries)
+            .into_iter()
+            .partition(|e| ids.contains(&e.id) && e.id != keep);
+        *entries = kept;
+        if merge_fields && let Some(target) = entries.iter_mut().find(|e| e.id == keep) {
+            for other in &dropped {
+                merge::merge_entry_fields(target, other);
+            }
+        }
+
+        merge::record_deletions(dropped.iter().map(|e| e.id))?;
+        removed += dropped.len();
+        let action = if merge_fields { "Merged" } else { "Removed" };
+        record_audit(vault, "data_modification", &format!("{} {} duplicate(s) of '{}'", action, dropped.len(), service));
+    }
+
+    println!("\n🧹 Removed {} duplicate entry(ies)", removed);
+    Ok(())
+}
+
+fn handle_protect(vault: &mut Vault, service: &str, protect: bool, master_password: &str) -> Result<()> {
+    let entry = select_entry(vault, service)?;
+    let (id, service) = (entry.id, entry.service.clone());
+    if entry.settings.require_master_password == protect {
+        println!("ℹ️  '{}' is already {}", service, if protect { "protected" } else { "unprotected" });
+        return Ok(());
+    }
+    // Otherwise anyone at an unlocked terminal could simply lift the protection
+    if !protect && !confirm_master_password(vault, master_password, &format!("'{}' is protected", service))? {
+        return Err("Incorrect master password".into());
+    }
+
+    if let Some(entry) = vault.get_entries_mut().and_then(|entries| entries.iter_mut().find(|e| e.id == id)) {
+        entry.settings.require_master_password = protect;
+        entry.modified_at = chrono::Utc::now();
+    }
+    if protect {
+        println!("🔒 '{}' now requires the master password to reveal", service);
+    } else {
+        println!("🔓 '{}' no longer requires the master password to reveal", service);
+    }
+    record_audit(vault, "data_modification", &format!("{} '{}'", if protect { "Protected" } else { "Unprotected" }, service));
+    Ok(())
+}
+
+fn handle_field(vault: &mut Vault, args: FieldArgs) -> Result<()> {
+    let (FieldCommands::Set { service, .. } | FieldCommands::Get { service, .. } | FieldCommands::Remove { service, .. }) = &args.command;
+    let id = select_entry(vault, service)?.id;
+    let entry = vault.get_entries_mut()
+        .and_then(|entries| entries.iter_mut().find(|e| e.id == id))
+        .ok_or("Entry disappeared from the vault")?;
+    let service = entry.service.clone();
+
+    match args.command {
+        FieldCommands::Set { key, value, secret, .. } => {
+            // Replace the field even if it changes between secret and plain
+            if let Some(existing) = fields::find_key(&entry.custom_fields, &key).cloned() {
+                entry.custom_fields.remove(&existing);
+            }
+            entry.add_custom_field(fields::storage_key(&key, secret), value);
+            entry.modified_at = chrono::Utc::now();
+            println!("✅ Set field '{}' on '{}'{}", key, service, if secret { " (secret)" } else { "" });
+            record_audit(vault, "data_modification", &format!("Set custom field '{}' on '{}'", key, service));
+        }
+        FieldCommands::Get { key, .. } => {
+            let stored = fields::find_key(&entry.custom_fields, &key)
+                .ok_or_else(|| format!("'{}' has no field '{}'", service, key))?;
+            println!("{}", entry.custom_fields[stored]);
+            record_audit(vault, "data_access", &format!("Read custom field '{}' on '{}'", key, service));
+        }
+        FieldCommands::Remove { key, .. } => {
+            let stored = fields::find_key(&entry.custom_fields, &key)
+                .cloned()
+                .ok_or_else(|| format!("'{}' has no field '{}'", service, key))?;
+            entry.custom_fields.remove(&stored);
+            entry.modified_at = chrono::Utc::now();
+            println!("🗑️  Removed field '{}' from '{}'", key, service);
+            record_audit(vault, "data_modification", &format!("Removed custom field '{}' from '{}'", key, service));
+        }
+    }
+    Ok(())
+}
+
+fn handle_delete(vault: &mut Vault, args: DeleteArgs) -> Result<()> {
+    if let Some(tag) = &args.tag {
+        return handle_delete_by_tag(vault, tag, args.force, args.dry_run);
+    }
+    let pattern = args.pattern.unwrap_or_default();
+    
+    // The preview and the delete pick entries with the same predicate
+    let doomed: Vec<&Entry> = vault.get_entries()
+        .map(|entries| entries.iter().filter(|e| matches_delete_pattern(e, &pattern)).collect())
+        .unwrap_or_default();
+    if args.dry_run {
+        print_delete_preview(&doomed, &format!("matching '{}'", pattern));
+        return Ok(());
+    }
+    
+    let ids: std::collections::HashSet<uuid::Uuid> = doomed.iter().map(|e| e.id).collect();
+    if ids.is_empty() {
+        println!("❌ No entries found matching '{}'", pattern);
+        return Ok(());
+    }
+    
+    if !args.force && !confirm_action(&format!("Are you sure you want to delete entries matching '{}'?", pattern))? {
+        println!("❌ Delete operation cancelled.");
+        return Ok(());
+    }
+
+    if let Some(entries) = vault.get_entries_mut() {
+        entries.retain(|e| !ids.contains(&e.id));
+    }
+    merge::record_deletions(ids.iter().copied())?;
+    println!("🗑️  Deleted {} entry(ies) matching '{}'", ids.len(), pattern);
+    
+    Ok(())
+}
+
+/// Entries `delete <pattern>` removes: those whose service contains the
+/// pattern, ignoring case. An empty pattern matches nothing.
+fn matches_delete_pattern(entry: &Entry, pattern: &str) -> bool {
+    !pattern.is_empty() && entry.service.to_lowercase().contains(&pattern.to_lowercase())
+}
+
+fn handle_delete_by_tag(vault: &mut Vault, tag: &str, force: bool, dry_run: bool) -> Result<()> {
+    if dry_run {
+        let doomed: Vec<&Entry> = vault.get_entries()
+            .map(|entries| entries.iter().filter(|e| has_tag(e, tag)).collect())
+            .unwrap_or_default();
+        print_delete_preview(&doomed, &format!("tagged '{}'", tag));
+        return Ok(());
+    }
+    
+    let ids: std::collections::HashSet<uuid::Uuid> = vault.get_entries()
+        .map(|entries| entries.iter().filter(|e| has_tag(e, tag)).map(|e| e.id).collect())
+        .unwrap_or_default();
+    if ids.is_empty() {
+        println!("❌ No entries tagged '{}'", tag);
+        return Ok(());
+    }
+    
+    if !force && !confirm_action(&format!("Delete {} entry(ies) tagged '{}'?", ids.len(), tag))? {
+        println!("❌ Delete operation cancelled.");
+        return Ok(());
+    }
+
+    if let Some(entries) = vault.get_entries_mut() {
+        entries.retain(|e| !ids.contains(&e.id));
+    }
+    merge::record_deletions(ids.iter().copied())?;
+    println!("🗑️  Deleted {} entry(ies) tagged '{}'", ids.len(), tag);
+    
+    Ok(())
+}
+
+fn print_delete_preview(entries: &[&Entry], description: &str) {
+    if entries.is_empty() {
+        println!("🔍 Dry run: no entries {}", description);
+        return;
+    }
+    println!("🔍 Dry run: {} entry(ies) {} would be deleted:", entries.len(), description);
+    for entry in entries {
+        println!("   • 🌐 {} | 👤 {}", entry.service, entry.username);
+    }
+    println!("💡 Run again without --dry-run to delete them");
+}
+
+/// Tags match case-insensitively, the same way for `list --tag` and `delete --tag`
+fn has_tag(entry: &Entry, tag: &str) -> bool {
+    entry.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+}
+
+/// Ask a yes/no question on stdout; anything but "y" means no
+fn confirm_action(prompt: &str) -> Result<bool> {
+    print!("⚠️  {} (y/N): ", prompt);
+    io::stdout().flush()?;
+    
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().to_lowercase().starts_with('y'))
+}
+
+fn handle_status(vault: &Vault, json: bool) -> Result<()> {
+    if json {
+        let stats = vault.get_vault_stats();
+        let expiring = expiring_entries(vault);
+        let expired = expiring.iter().filter(|(_, days)| *days < 0).count();
+        return output::print_json(&output::StatusOutput {
+            auto_lock_seconds: vault.get_lock_status().map(|left| left.as_secs()),
+            total_entries: stats.total_entries,
+            unique_services: stats.unique_services,
+            has_duplicates: stats.has_duplicates,
+            expired_passwords: expired,
+            expiring_passwords: expiring.len() - expired,
+            key_derivation_ms: benchmark_key_derivation().as_millis() as u64,
+        });
+    }
+    
+    if let Some(time_left) = vault.get_lock_status() {
+        let minutes = time_left.as_secs() / 60;
+        let seconds = time_left.as_secs() % 60;
+        println!("🔓 Vault Status: UNLOCKED");
+        println!("⏰ Auto-lock in: {}m {}s", minutes, seconds);
+    } else {
+        println!("🔒 Vault Status: No auto-lock configured");
+    }
+
+    let stats = vault.get_vault_stats();
+    println!("📊 Total entries: {}", stats.total_entries);
+    println!("🏢 Unique services: {}", stats.unique_services);
+    
+    if stats.has_duplicates {
+        println!("⚠️  Duplicate services detected");
+    }
+    
+    let expiring = expiring_entries(vault);
+    let expired = expiring.iter().filter(|(_, days)| *days < 0).count();
+    if expired > 0 {
+        println!("❌ Expired passwords: {}", expired);
+    }
+    if expiring.len() > expired {
+        println!("⏳ Passwords expiring soon: {}", expiring.len() - expired);
+    }
+    if !expiring.is_empty() {
+        println!("💡 Run 'passmann expiring' for details");
+    }
+
+    // Show crypto benchmark
+    let benchmark_time = benchmark_key_derivation();
+    println!("⚡ Key derivation time: {}ms", benchmark_time.as_millis());
+    
+    if benchmark_time.as_millis() < 100 {
         println!("⚠️  Consider increasing Argon2id parameters for better security");
     } else if benchmark_time.as_millis() > 1000 {
         println!("💡 Consider decreasing Argon2id parameters for better performance");
@@ -485,6 +1762,46 @@ fn handle_status(vault: &Vault) -> Result<()> {
     Ok(())
 }
 
+fn handle_expiring(vault: &Vault) -> Result<()> {
+    let expiring = expiring_entries(vault);
+    if expiring.is_empty() {
+        println!("✅ No expired or soon-to-expire passwords");
+        return Ok(());
+    }
+    
+    println!("⏳ Expiring passwords ({}):", expiring.len());
+    println!("{:-<60}", "");
+    for (entry, days) in &expiring {
+        let when = match *days {
+            d if d < 0 => format!("expired {} day(s) ago", -d),
+            0 => "expires today".to_string(),
+            d => format!("expires in {} day(s)", d),
+        };
+        println!("{} {} ({}) - {}", if *days < 0 { "❌" } else { "⚠️ " }, entry.service, entry.username, when);
+    }
+    
+    Ok(())
+}
+
+/// Entries whose password has expired or is in its warning window, soonest first,
+/// with the days remaining (negative once expired)
+fn expiring_entries(vault: &Vault) -> Vec<(&Entry, i64)> {
+    use passmann_shared::ExpirationStatus;
+    
+    let now = chrono::Utc::now();
+    let mut expiring: Vec<(&Entry, i64)> = vault.get_entries()
+        .map(|entries| entries.iter()
+            .filter(|entry| matches!(
+                entry.check_expiration_status(),
+                ExpirationStatus::Warning { .. } | ExpirationStatus::Expired { .. }
+            ))
+            .filter_map(|entry| entry.expires_at.map(|at| (entry, (at - now).num_days())))
+            .collect())
+        .unwrap_or_default();
+    expiring.sort_by_key(|(_, days)| *days);
+    expiring
+}
+
 fn handle_logs(vault: &Vault, args: LogsArgs) -> Result<()> {
     if let Some(ref audit) = vault.audit {
         let logs = audit.get_recent_logs(args.count);
@@ -505,39 +1822,295 @@ fn handle_logs(vault: &Vault, args: LogsArgs) -> Result<()> {
     Ok(())
 }
 
-fn handle_generate(args: GenerateArgs) -> Result<()> {
-    println!("🎲 Generating {} password(s):", args.count);
-    println!("{:-<60}", "");
+fn audit_log_path() -> std::path::PathBuf {
+    Vault::vault_path().with_file_name("audit.log")
+}
+
+fn pending_queue_path() -> std::path::PathBuf {
+    Vault::vault_path().with_file_name("sync_queue.enc")
+}
+
+/// Explain that nothing is lost when the cloud can't be reached
+fn offline_error(err: Box<dyn std::error::Error>) -> Box<dyn std::error::Error> {
+    format!("{} (local changes stay queued and upload on the next successful sync)", err).into()
+}
+
+/// Checksums of the vault files written recently, for `verify`
+fn vault_checksums_path() -> std::path::PathBuf {
+    Vault::vault_path().with_file_name("vault_checksums.json")
+}
+
+fn stats_history_path() -> std::path::PathBuf {
+    Vault::vault_path().with_file_name("stats_history.enc")
+}
+
+fn generation_policies_path() -> std::path::PathBuf {
+    Vault::vault_path().with_file_name("generation_policies.enc")
+}
+
+fn cloud_vault_id_path() -> std::path::PathBuf {
+    Vault::vault_path().with_file_name("cloud_vault_id")
+}
+
+fn handle_audit(vault: &Vault, args: AuditArgs, master_password: &str) -> Result<()> {
+    match args.command {
+        AuditCommands::Export { format, since, min_severity, event_type, output } => {
+            let min_severity = match min_severity {
+                Some(level) => Some(audit::severity_rank(&level)
+                    .ok_or_else(|| format!("Unknown severity '{}' (use info, low, medium, high or critical)", level))?),
+                None => None,
+            };
+            let filter = audit::AuditFilter {
+                since: since.as_deref().map(audit::parse_duration).transpose()?.map(|age| chrono::Utc::now() - age),
+                min_severity,
+                event_type,
+            };
+
+            let events = audit::filter_events(audit::read_events(&audit_log_path(), master_password)?, &filter);
+            let data = match format.as_str() {
+                "json" => serde_json::to_string_pretty(&events)?,
+                "csv" => audit::to_csv(&events),
+                other => return Err(format!("Unsupported audit export format '{}' (use json or csv)", other).into()),
+            };
+
+            match output {
+                Some(output) => {
+                    std::fs::write(&output, data)?;
+                    eprintln!("📤 Exported {} audit event(s) to '{}'", events.len(), output);
+                }
+                None => println!("{}", data),
+            }
+        }
+        AuditCommands::Verify => {
+            let path = audit_log_path();
+            if !path.exists() {
+                println!("📭 No audit log found at {}", path.display());
+                return Ok(());
+            }
+            let Some(report) = audit::verify_log(&path, master_password)? else {
+                println!("⚠️  Audit log has not been sealed yet; it will be after this command");
+                return Ok(());
+            };
+
+            match report.divergence {
+                Some(divergence) => {
+                    println!("🚨 Audit log has been tampered with!");
+                    println!("   Lines {}-{} no longer match their checkpoint", divergence.first_line, divergence.checkpoint_line);
+                    println!("   They will be moved to a quarantine file after this command");
+                    return Err("Audit log integrity check failed".into());
+                }
+                None => {
+                    println!("✅ Audit log integrity verified ({} sealed event(s))", report.sealed);
+                    if report.unsealed > 0 {
+                        println!("⚠️  {} event(s) after the last checkpoint were never sealed", report.unsealed);
+                        println!("   They will be moved to a quarantine file after this command");
+                    }
+                }
+            }
+        }
+        AuditCommands::Report { hours } => match vault.audit {
+            Some(ref audit) => println!("{}", audit.generate_security_report(hours)),
+            None => println!("❌ No audit log available."),
+        },
+    }
+    Ok(())
+}
+
+fn handle_generate(args: GenerateArgs, json: bool) -> Result<()> {
+    if args.clipboard && !(1..=args.count).contains(&args.index) {
+        return Err(format!("--index must be between 1 and {}", args.count).into());
+    }
+    if json {
+        return generate_json(args);
+    }
     
-    for i in 0..args.count {
-        let password = generate_password(args.length, args.symbols);
-        let strength = estimate_password_strength(&password);
-        
-        println!("{:2}. 🔑 {} (Strength: {} - {})", 
-            i + 1, password, strength.score, strength.level);
+    let options = generator_options(&args);
+    let generated = (0..args.count)
+        .map(|_| if args.pin { generator::generate_pin(args.pin_length) } else { generate_password_opts(args.length, &options) })
+        .collect::<Result<Vec<String>>>()?;
+    
+    if !args.quiet {
+        if args.pin {
+            println!("🔢 Generating {} PIN(s):", args.count);
+        } else {
+            println!("🎲 Generating {} password(s):", args.count);
+        }
+        println!("{:-<60}", "");
         
-        if args.clipboard && i == 0 {
-            copy_to_clipboard(&password)?;
-            println!("     📋 Copied to clipboard");
+        for (i, password) in generated.iter().enumerate() {
+            if args.pin {
+                println!("{:2}. 🔑 {}", i + 1, password);
+            } else {
+                let strength = estimate_password_strength(password);
+                println!("{:2}. 🔑 {} (Strength: {} - {})", 
+                    i + 1, password, strength.score, strength.level);
+            }
         }
     }
     
+    if args.clipboard {
+        copy_generated(&generated, &args)?;
+    }
     Ok(())
 }
 
+/// Copy the `--index` password, or all of them with `--copy-all`, and have
+/// the clipboard cleared once the timeout passes. Messages go to stderr so
+/// `--quiet` leaves stdout empty.
+fn copy_generated(passwords: &[String], args: &GenerateArgs) -> Result<()> {
+    if args.copy_all {
+        copy_to_clipboard(&passwords.join("\n"))?;
+        eprintln!("📋 Copied {} password(s) to clipboard", passwords.len());
+    } else {
+        copy_to_clipboard(&passwords[args.index - 1])?;
+        eprintln!("📋 Copied #{} to clipboard", args.index);
+    }
+    clear_clipboard_after(args.clear_after.unwrap_or_else(clipboard_clear_seconds))
+}
+
+fn clipboard_clear_seconds() -> u64 {
+    std::env::var("PASSMANN_CLIPBOARD_CLEAR_SECONDS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_CLIPBOARD_CLEAR_SECONDS)
+}
+
+/// Start a detached `clear-clipboard` process so the command returns at once
+fn clear_clipboard_after(seconds: u64) -> Result<()> {
+    if seconds == 0 {
+        return Ok(());
+    }
+    std::process::Command::new(std::env::current_exe()?)
+        .args(["clear-clipboard", &seconds.to_string()])
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()?;
+    eprintln!("⏳ The clipboard will be cleared in {}s", seconds);
+    Ok(())
+}
+
+fn handle_clear_clipboard(seconds: u64) -> Result<()> {
+    std::thread::sleep(std::time::Duration::from_secs(seconds));
+    copy_to_clipboard("")
+}
+
+fn generate_json(args: GenerateArgs) -> Result<()> {
+    let options = generator_options(&args);
+    let mut passwords = Vec::with_capacity(args.count);
+    for _ in 0..args.count {
+        passwords.push(if args.pin {
+            output::GeneratedPassword { password: generator::generate_pin(args.pin_length)?, score: None, level: None }
+        } else {
+            let password = generate_password_opts(args.length, &options)?;
+            let strength = estimate_password_strength(&password);
+            output::GeneratedPassword {
+                score: Some(strength.score as u64),
+                level: Some(strength.level.to_string()),
+                password,
+            }
+        });
+    }
+    
+    let copied: Vec<String> = passwords.iter().map(|p| p.password.clone()).collect();
+    if !args.quiet {
+        output::print_json(&output::GenerateOutput { passwords })?;
+    }
+    if args.clipboard {
+        copy_generated(&copied, &args)?;
+    }
+    Ok(())
+}
+
+fn generator_options(args: &GenerateArgs) -> GeneratorOptions {
+    GeneratorOptions {
+        uppercase: !args.no_uppercase,
+        digits: !args.no_digits,
+        symbols: args.symbols,
+        exclude_ambiguous: args.no_ambiguous,
+        exclude: args.exclude.clone(),
+        policy: PasswordPolicy {
+            min_lower: args.min_lower,
+            min_upper: args.min_upper,
+            min_digits: args.min_digits,
+            min_symbols: args.min_symbols,
+        },
+    }
+}
+
 fn handle_export(vault: &Vault, args: ExportArgs) -> Result<()> {
     let export_data = vault.export_entries(&args.format)?;
     std::fs::write(&args.output, export_data)?;
     
     println!("📤 Exported vault to '{}' in {} format", args.output, args.format);
-    println!("⚠️  Keep exported file secure - it contains sensitive data!");
     
+    match args.shred_after {
+        Some(seconds) => {
+            println!("⏳ Shredding '{}' in {}s (Ctrl-C keeps it; `passmann shred` removes it later)", args.output, seconds);
+            std::thread::sleep(std::time::Duration::from_secs(seconds));
+            shred::shred_file(std::path::Path::new(&args.output), shred::DEFAULT_PASSES)?;
+            println!("🧹 Shredded '{}'", args.output);
+        }
+        None => {
+            println!("⚠️  Keep exported file secure - it contains sensitive data!");
+            println!("💡 Remove it with `passmann shred {}` when you're done", args.output);
+        }
+    }
+    
+    Ok(())
+}
+
+fn handle_shred(path: &std::path::Path, passes: usize, force: bool) -> Result<()> {
+    if !force && !confirm_action(&format!("Overwrite and delete '{}'? This cannot be undone.", path.display()))? {
+        println!("❌ Shred cancelled");
+        return Ok(());
+    }
+    let size = shred::shred_file(path, passes)?;
+    println!("🧹 Shredded '{}' ({} byte(s), {} pass(es))", path.display(), size, passes.max(1));
+    println!("💡 On SSDs and copy-on-write filesystems old copies may survive; full-disk encryption is the real safeguard");
     Ok(())
 }
 
-fn handle_import(_vault: &mut Vault, _args: ImportArgs) -> Result<()> {
-    // TODO: Implement import functionality
-    println!("📥 Import functionality coming soon!");
+fn handle_import(vault: &mut Vault, args: ImportArgs) -> Result<()> {
+    let batch = match args.format.to_lowercase().as_str() {
+        // Edge exports the same format
+        "chrome" | "edge" => import::parse_chrome_csv(std::fs::File::open(&args.input)?)?,
+        "1password" if args.input.to_lowercase().ends_with(".1pux") => import::parse_1pux(std::fs::File::open(&args.input)?)?,
+        "1password" => import::parse_1password_csv(std::fs::File::open(&args.input)?)?,
+        _ => {
+            // TODO: Implement json and csv import
+            println!("📥 Import from {} coming soon!", args.format);
+            return Ok(());
+        }
+    };
+
+    println!("📥 Found {} entries in '{}'", batch.rows.len(), args.input);
+    if !batch.rows.is_empty() && !args.force && !confirm_action(&format!("Import {} entries?", batch.rows.len()))? {
+        println!("❌ Import cancelled");
+        return Ok(());
+    }
+
+    let imported = batch.rows.len();
+    for row in batch.rows {
+        vault.add_entry(row.service, row.username, row.password);
+        if let Some(entry) = vault.get_entries_mut().and_then(|entries| entries.last_mut()) {
+            entry.url = row.url;
+            entry.notes = row.notes;
+            entry.tags = row.tags;
+            // Kept as a masked custom field until entries have a TOTP field
+            if let Some(totp) = row.totp {
+                entry.add_custom_field(fields::storage_key("totp", true), totp);
+            }
+        }
+    }
+
+    println!("✅ Imported:      {}", imported);
+    println!("⏭️  Skipped empty: {}", batch.skipped_empty);
+    println!("🔁 Deduplicated:  {}", batch.deduplicated);
+    if batch.skipped_non_login > 0 {
+        println!("📄 Not logins:    {} (secure notes, documents, ...)", batch.skipped_non_login);
+    }
+    record_audit(vault, "data_modification", &format!("Imported {} entries from {} export", imported, args.format));
     Ok(())
 }
 
@@ -577,8 +2150,26 @@ fn handle_benchmark() -> Result<()> {
     Ok(())
 }
 
-fn handle_stats(vault: &Vault) -> Result<()> {
+fn handle_stats(vault: &Vault, args: StatsArgs, json: bool) -> Result<()> {
     let stats = vault.get_vault_stats();
+    let all_entries: &[Entry] = vault.get_entries().map(|entries| entries.as_slice()).unwrap_or_default();
+    let ages = stats::password_age_report(all_entries, args.age_threshold, args.oldest);
+    
+    let scores = stats::strength_scores(all_entries);
+    let strong_passwords = scores.iter().filter(|score| **score >= 80).count();
+    let weak_passwords = scores.iter().filter(|score| **score < 60).count();
+    
+    if json {
+        return output::print_json(&output::StatsOutput {
+            total_entries: stats.total_entries,
+            unique_services: stats.unique_services,
+            has_duplicates: stats.has_duplicates,
+            strong_passwords,
+            weak_passwords,
+            old_passwords: ages.old_count,
+            age_threshold_days: ages.threshold_days,
+        });
+    }
     
     println!("📊 Vault Statistics");
     println!("{:=<50}", "");
@@ -587,29 +2178,157 @@ fn handle_stats(vault: &Vault) -> Result<()> {
     println!("Duplicate check:   {}", if stats.has_duplicates { "❌ Found" } else { "✅ None" });
     
     if let Some(entries) = vault.get_entries() {
-        // Password strength analysis
-        let mut weak_passwords = 0;
-        let mut strong_passwords = 0;
+        println!("Strong passwords:  {} ({:.1}%)", 
+            strong_passwords, 
+            (strong_passwords as f32 / entries.len() as f32) * 100.0
+        );
+        println!("Weak passwords:    {} ({:.1}%)", 
+            weak_passwords,
+            (weak_passwords as f32 / entries.len() as f32) * 100.0
+        );
+        println!("Old passwords:     {} (unchanged > {} days)", ages.old_count, ages.threshold_days);
         
-        for entry in entries {
-            let strength = estimate_password_strength(&entry.password);
-            if strength.score < 60 {
-                weak_passwords += 1;
-            } else if strength.score >= 80 {
-                strong_passwords += 1;
+        if !ages.oldest.is_empty() {
+            println!("\n⏳ Oldest passwords:");
+            for (service, username, days) in &ages.oldest {
+                println!("  • {} ({}) — {} day(s)", service, username, days);
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+fn handle_derive(args: DeriveArgs, master_password: &str) -> Result<()> {
+    let password = derive::derive_password(master_password, &args.site, args.counter, args.length, !args.no_symbols)?;
+    if args.clipboard {
+        copy_to_clipboard(&password)?;
+        println!("📋 Derived password for '{}' (counter {}) copied to clipboard", args.site, args.counter);
+    } else {
+        println!("🔑 {}", password);
+    }
+    println!("💡 Nothing was stored: the same master password, site, counter and options always give this password");
+    println!("⚠️  Changing the master password changes every derived password");
+    if args.clipboard {
+        clear_clipboard_after(clipboard_clear_seconds())?;
+    }
+    Ok(())
+}
+
+fn handle_refresh_strength(vault: &mut Vault) -> Result<()> {
+    let Some(entries) = vault.get_entries_mut() else {
+        println!("📭 No entries found in vault.");
+        return Ok(());
+    };
+    let mut changed = 0;
+    for entry in entries.iter_mut() {
+        let before = entry.password_strength.score;
+        stats::refresh_strength(entry);
+        if entry.password_strength.score != before {
+            changed += 1;
+        }
+    }
+    println!("✅ Recomputed password strength for {} entries ({} scores changed)", entries.len(), changed);
+    Ok(())
+}
+
+fn handle_trend(args: TrendArgs, master_password: &str) -> Result<()> {
+    let snapshots = history::load(&stats_history_path(), master_password)?;
+    if snapshots.len() < 2 {
+        println!("📈 Not enough history yet: a snapshot is taken each time the vault changes");
+        return Ok(());
+    }
+    let recent = &snapshots[snapshots.len().saturating_sub(args.last.max(2))..];
+    let (first, last) = (&recent[0], &recent[recent.len() - 1]);
+
+    println!("📈 Vault trend over {} snapshot(s) since {}", recent.len(), first.timestamp.format("%Y-%m-%d"));
+    println!("{:=<60}", "");
+    // (label, value, whether a rise is an improvement; None when neither)
+    let metrics: [(&str, fn(&history::Snapshot) -> f32, Option<bool>); 5] = [
+        ("Entries", |s| s.total_entries as f32, None),
+        ("Weak", |s| s.weak as f32, Some(false)),
+        ("Reused", |s| s.reused as f32, Some(false)),
+        ("Old", |s| s.old as f32, Some(false)),
+        ("Avg strength", |s| s.avg_strength, Some(true)),
+    ];
+    for (name, value, higher_is_better) in metrics {
+        let values: Vec<f32> = recent.iter().map(value).collect();
+        let change = value(last) - value(first);
+        let marker = match higher_is_better {
+            Some(rise_is_better) if change.abs() >= 0.05 => if (change > 0.0) == rise_is_better { "✅" } else { "⚠️ " },
+            _ => "  ",
+        };
+        println!("{:<13} {} {:>6.1} → {:<6.1} {} {:+.1}", name, history::sparkline(&values), value(first), value(last), marker, change);
+    }
+
+    println!("\n{:<17} {:>7} {:>5} {:>7} {:>4} {:>8}", "Saved", "Entries", "Weak", "Reused", "Old", "Strength");
+    println!("{:-<60}", "");
+    for snapshot in recent {
+        println!("{:<17} {:>7} {:>5} {:>7} {:>4} {:>8.1}",
+            snapshot.timestamp.format("%Y-%m-%d %H:%M"), snapshot.total_entries, snapshot.weak,
+            snapshot.reused, snapshot.old, snapshot.avg_strength);
+    }
+    Ok(())
+}
+
+fn handle_fix_weak(vault: &mut Vault, args: FixWeakArgs, master_password: &str) -> Result<()> {
+    let options = GeneratorOptions { symbols: args.symbols, ..GeneratorOptions::default() };
+    // Fail on impossible options before asking about any entry
+    generate_password_opts(args.min_length, &options)?;
+    let mut policies = policies::load(&generation_policies_path(), master_password)?;
+
+    let weak: Vec<(uuid::Uuid, String, String, u32, Option<GenerationPolicy>)> = vault.get_entries()
+        .map(|entries| entries.iter()
+            .zip(stats::strength_scores(entries))
+            .map(|(e, score)| (e, score as u32))
+            .filter(|(_, score)| *score < args.threshold)
+            .map(|(e, score)| (e.id, e.service.clone(), e.username.clone(), score, policies.remove(&e.id)))
+            .collect())
+        .unwrap_or_default();
+    if weak.is_empty() {
+        println!("✅ No passwords score below {}", args.threshold);
+        return Ok(());
+    }
+
+    println!("⚠️  {} weak password(s) found", weak.len());
+    let mut strengthened = Vec::new();
+    for (id, service, username, score, policy) in weak {
+        if !args.yes {
+            print!("🔧 {} ({}) scores {}/100. Replace it? (y/N/q): ", service, username, score);
+            io::stdout().flush()?;
+            let mut input = String::new();
+            io::stdin().read_line(&mut input)?;
+            match input.trim().to_lowercase().as_str() {
+                "y" | "yes" => {}
+                "q" | "quit" => break,
+                _ => continue,
             }
         }
-        
-        println!("Strong passwords:  {} ({:.1}%)", 
-            strong_passwords, 
-            (strong_passwords as f32 / entries.len() as f32) * 100.0
-        );
-        println!("Weak passwords:    {} ({:.1}%)", 
-            weak_passwords,
-            (weak_passwords as f32 / entries.len() as f32) * 100.0
-        );
+
+        let generate = || match &policy {
+            Some(policy) => generate_password_opts(policy.length, &generator::options_for_policy(policy)),
+            None => generate_password_opts(args.min_length, &options),
+        };
+        let password = if args.allow_reuse {
+            generate()?
+        } else {
+            let entries = vault.get_entries().map(|entries| entries.as_slice()).unwrap_or_default();
+            reuse::generate_unused(entries, Some(id), generate)?
+        };
+        if let Some(entry) = vault.get_entries_mut().and_then(|entries| entries.iter_mut().find(|e| e.id == id)) {
+            // Keeps the old password in the entry's history
+            entry.update_password(password);
+            println!("   ✅ Replaced password for '{}'", service);
+            strengthened.push(service);
+        }
+    }
+
+    println!("{:-<60}", "");
+    println!("🛡️  Strengthened {} password(s)", strengthened.len());
+    if !strengthened.is_empty() {
+        println!("💡 Update them on each site too: `passmann reveal <service>` shows the new password");
+        record_audit(vault, "data_modification", &format!("Replaced {} weak password(s): {}", strengthened.len(), strengthened.join(", ")));
     }
-    
     Ok(())
 }
 
@@ -645,24 +2364,16 @@ fn handle_check_strength(password: &str) -> Result<()> {
 // LOCAL VAULT HANDLERS (Ultra-Secure Military-Grade)
 // ============================================================================
 
-async fn handle_create_local(args: CreateLocalArgs) -> Result<()> {
+async fn handle_create_local(args: CreateLocalArgs, weak_master_ok: bool) -> Result<()> {
     use passmann_shared::{LocalSecureVault, SecurityLevel};
-    use std::path::PathBuf;
     
     println!("🛡️ Creating Ultra-Secure Local Vault");
     println!("=====================================");
     
-    // Determine vault path
-    let vault_path = match args.path {
-        Some(path) => PathBuf::from(path),
-        None => {
-            let default_dir = dirs::data_dir()
-                .unwrap_or_else(|| PathBuf::from("."))
-                .join("passmann");
-            std::fs::create_dir_all(&default_dir)?;
-            default_dir.join("vault_local.pmv")
-        }
-    };
+    let vault_path = local_vault_path(args.path);
+    if let Some(dir) = vault_path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
     
     // Parse security level
     let security_level = match args.security.to_lowercase().as_str() {
@@ -698,6 +2409,7 @@ async fn handle_create_local(args: CreateLocalArgs) -> Result<()> {
         println!("❌ Master password must be at least 12 characters for military-grade security");
         return Ok(());
     }
+    check_master_password_strength(&master_password, weak_master_ok)?;
     
     // Confirm master password
     print!("🔐 Confirm master password: ");
@@ -736,20 +2448,27 @@ async fn handle_create_local(args: CreateLocalArgs) -> Result<()> {
     Ok(())
 }
 
-async fn handle_local_commands(args: LocalArgs) -> Result<()> {
+/// The local vault file: `--path` when given, otherwise the default location
+fn local_vault_path(path: Option<String>) -> std::path::PathBuf {
+    match path {
+        Some(path) => std::path::PathBuf::from(path),
+        None => dirs::data_dir()
+            .unwrap_or_else(|| std::path::PathBuf::from("."))
+            .join("passmann")
+            .join("vault_local.pmv"),
+    }
+}
+
+/// The entry database that belongs to the local vault at `local_vault`
+fn local_db_path(local_vault: &std::path::Path) -> std::path::PathBuf {
+    local_vault.with_extension("db")
+}
+
+async fn handle_local_commands(args: LocalArgs, weak_master_ok: bool) -> Result<()> {
     use passmann_shared::LocalSecureVault;
     use std::path::PathBuf;
     
-    // Determine vault path
-    let vault_path = match args.path {
-        Some(path) => PathBuf::from(path),
-        None => {
-            let default_dir = dirs::data_dir()
-                .unwrap_or_else(|| PathBuf::from("."))
-                .join("passmann");
-            default_dir.join("vault_local.pmv")
-        }
-    };
+    let vault_path = local_vault_path(args.path);
     
     if !vault_path.exists() {
         println!("❌ Local vault not found: {}", vault_path.display());
@@ -779,7 +2498,7 @@ async fn handle_local_commands(args: LocalArgs) -> Result<()> {
                 Some(p) => p,
                 None => {
                     println!("🎲 Generating secure password...");
-                    crate::generate_password(16, true)
+                    crate::generate_password(16, true)?
                 }
             };
             
@@ -851,6 +2570,7 @@ async fn handle_local_commands(args: LocalArgs) -> Result<()> {
                 println!("❌ New password must be at least 12 characters");
                 return Ok(());
             }
+            check_master_password_strength(&new_password, weak_master_ok)?;
             
             print!("🔐 Confirm new password: ");
             io::stdout().flush()?;
@@ -880,8 +2600,141 @@ async fn handle_local_commands(args: LocalArgs) -> Result<()> {
             vault.save_to_disk(&master_password)?;
             println!("💾 Backup created successfully");
         }
+        
+        LocalCommands::Verify { deep, db } => {
+            let stats = vault.get_stats()?;
+            println!("🔍 Local Vault Verification");
+            println!("{:-<50}", "");
+            println!("📄 File checksum: {}", if stats.checksum_verified { "✅ Verified" } else { "❌ Failed" });
+            
+            if deep {
+                let db_path = db.map(PathBuf::from)
+                    .unwrap_or_else(|| local_db_path(&vault_path));
+                if !db_path.exists() {
+                    println!("❌ Vault database not found: {}", db_path.display());
+                    return Ok(());
+                }
+                
+                // Read-only pass: every row is decrypted and parsed, nothing is written back
+                let secure_db = db::SecureDb::open(&db_path, &master_password)?;
+                let report = secure_db.verify_entries(&master_password)?;
+                
+                println!("🔐 Entries verified: {}", report.verified);
+                if report.failed.is_empty() {
+                    println!("✅ All entries decrypted successfully");
+                } else {
+                    println!("❌ {} entry(ies) failed to decrypt:", report.failed.len());
+                    for id in &report.failed {
+                        println!("   • {}", id);
+                    }
+                }
+            }
+        }
+        
+        LocalCommands::Migrate { db } => {
+            let db_path = db.map(PathBuf::from).unwrap_or_else(|| local_db_path(&vault_path));
+            if !db_path.exists() {
+                println!("❌ Vault database not found: {}", db_path.display());
+                return Ok(());
+            }
+            
+            match db::SecureDb::migrate_plaintext_file(&db_path, &master_password)? {
+                0 => println!("✅ Nothing to migrate: {} is already encrypted or SQLCipher is unavailable", db_path.display()),
+                moved => println!("✅ Encrypted {} with SQLCipher ({} entries moved)", db_path.display(), moved),
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+fn handle_verify(master_password: &str) -> Result<()> {
+    let path = Vault::vault_path();
+    // Vault::load only opens the vault at its default path, so backups can't be checked yet
+    let report = verify::verify_file(&path, &vault_checksums_path(), |_| {
+        Vault::load(master_password)
+            .inspect_err(|err| {
+                if let Some(guidance) = verify::load_failure_guidance(err.as_ref()) {
+                    eprintln!("💡 {}", guidance);
+                }
+            })?
+            .export_to_json()
+    });
+    
+    println!("🔍 Vault Integrity Check: {}", path.display());
+    println!("{:-<60}", "");
+    for check in &report.checks {
+        println!("{} {}: {}", if check.passed { "✅" } else { "❌" }, check.name, check.detail);
+    }
+    println!("{:-<60}", "");
+    
+    if !report.passed() {
+        return Err("Vault failed integrity verification".into());
+    }
+    println!("✅ All checks passed");
+    
+    Ok(())
+}
+
+fn handle_restore(vault: &mut Vault, store: &mut vault_store::VaultStore, args: RestoreArgs, master_password: &str) -> Result<()> {
+    let vault_path = Vault::vault_path();
+    let source = backup::backup_path(&vault_path, args.from);
+    if !source.exists() {
+        println!("❌ Backup {} not found: {}", args.from, source.display());
+        return Ok(());
+    }
+    
+    if !args.force {
+        print!("⚠️  Replace the current vault with backup {}? (y/N): ", args.from);
+        io::stdout().flush()?;
+        
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        
+        if !input.trim().to_lowercase().starts_with('y') {
+            println!("❌ Restore cancelled.");
+            return Ok(());
+        }
+    }
+    
+    let keep = backup::backup_count().max(args.from);
+    backup::promote(&vault_path, args.from, keep)?;
+    
+    match Vault::load(master_password) {
+        Ok(restored) => {
+            *vault = restored;
+            store.replaced_on_disk(vault)?;
+            record_audit(vault, "backup_restored", &source.display().to_string());
+            println!("✅ Restored backup {} ({} entries)", args.from, vault.get_entries().map_or(0, |e| e.len()));
+            println!("💡 The previous vault was kept as backup 1");
+        }
+        Err(err) => {
+            backup::undo_promote(&vault_path, keep)?;
+            return Err(format!("Backup {} could not be decrypted with this password: {}", args.from, err).into());
+        }
+    }
+    
+    Ok(())
+}
+
+fn handle_compact(args: CompactArgs, master_password: &str) -> Result<()> {
+    use std::path::PathBuf;
+    
+    let db_path = args.db.map(PathBuf::from).unwrap_or_else(|| local_db_path(&local_vault_path(args.path)));
+    if !db_path.exists() {
+        println!("❌ Vault database not found: {}", db_path.display());
+        return Ok(());
     }
     
+    let before = std::fs::metadata(&db_path)?.len();
+    println!("🧹 Compacting {}...", db_path.display());
+    
+    let secure_db = db::SecureDb::open(&db_path, master_password)?;
+    secure_db.compact()?;
+    
+    let after = std::fs::metadata(&db_path)?.len();
+    println!("✅ Compacted: {} bytes -> {} bytes ({} bytes reclaimed)", before, after, before.saturating_sub(after));
+    
     Ok(())
 }
 
@@ -889,6 +2742,57 @@ async fn handle_local_commands(args: LocalArgs) -> Result<()> {
 // CLOUD SYNCHRONIZATION HANDLERS
 // ============================================================================
 
+async fn handle_audit_breaches(vault: &mut Vault, args: AuditBreachesArgs) -> Result<()> {
+    let now = chrono::Utc::now();
+    let client = reqwest::Client::new();
+    let mut skipped: Vec<(String, breach::Skip)> = Vec::new();
+    let mut breached: Vec<(String, String, u64)> = Vec::new();
+    let mut checked = 0;
+
+    let entries = vault.get_entries_mut().ok_or("Vault is empty")?;
+    println!("🔍 Checking passwords against known breaches...");
+    for entry in entries.iter_mut() {
+        if !entry.settings.enable_breach_monitoring {
+            skipped.push((entry.service.clone(), breach::Skip::MonitoringDisabled));
+            continue;
+        }
+        if !breach::needs_rescan(entry.breach_status.last_checked, entry.modified_at, args.stale, now) {
+            skipped.push((entry.service.clone(), breach::Skip::RecentlyChecked));
+            continue;
+        }
+
+        let count = breach::pwned_count(&client, &entry.password).await?;
+        entry.breach_status.last_checked = Some(now);
+        entry.breach_status.is_breached = count > 0;
+        checked += 1;
+        if count > 0 {
+            breached.push((entry.service.clone(), entry.username.clone(), count));
+        }
+    }
+
+    for (service, reason) in &skipped {
+        match reason {
+            breach::Skip::RecentlyChecked => println!("   ⏭️  {} (recently checked)", service),
+            breach::Skip::MonitoringDisabled => println!("   ⏭️  {} (breach monitoring disabled)", service),
+        }
+    }
+    println!("{:-<60}", "");
+    if breached.is_empty() {
+        println!("✅ None of the {} checked passwords appear in known breaches", checked);
+    } else {
+        println!("🚨 {} of {} checked passwords appear in known breaches:", breached.len(), checked);
+        for (service, username, count) in &breached {
+            println!("   • {} ({}) seen {} times", service, username, count);
+        }
+        println!("💡 Change these passwords as soon as possible");
+    }
+    println!("⏭️  Skipped: {}", skipped.len());
+
+    record_audit(vault, "security_scan",
+        &format!("Breach check: {} checked, {} breached, {} skipped", checked, breached.len(), skipped.len()));
+    Ok(())
+}
+
 async fn handle_sync(
     vault: &mut Vault,
     args: SyncArgs,
@@ -899,12 +2803,24 @@ async fn handle_sync(
     
     println!("🌐 Initializing cloud sync...");
     
-    let mut client = SupabaseClient::new()?;
+    let mut client = SupabaseClient::new().map_err(offline_error)?;
     let user_id = get_or_create_user_id()?;
     let device_id = get_or_create_device_id()?;
     
     client.authenticate(user_id.clone()).await?;
     
+    let strategy = match args.strategy {
+        Some(strategy) => strategy,
+        None => client.get_sync_metadata(&device_id).await?
+            .filter(|meta| meta.sync_version >= SYNC_STRATEGY_VERSION)
+            .map(|meta| merge::ConflictStrategy::from_stored(&meta.conflict_resolution))
+            .unwrap_or_default(),
+    };
+    
+    if args.delta {
+        return handle_delta_sync(vault, &args, master_password, &client, user_id, device_id, strategy).await;
+    }
+    
     if args.dry_run {
         println!("🔍 Dry run mode - showing what would be synced:");
         let cloud_vault = client.download_vault(&device_id).await?;
@@ -925,16 +2841,69 @@ async fn handle_sync(
     
     match (sync_metadata, cloud_vault) {
         (Some(meta), Some(cloud)) => {
-            if meta.pending_changes && !args.force {
-                return Err("Sync conflict detected. Use --force to override.".into());
+            if meta.pending_changes {
+                println!("⚠️  Pending changes on both sides - merging by last modification");
             }
             
             // Download and merge cloud vault
             println!("📥 Downloading cloud vault...");
-            let decrypted_data = decrypt_cloud_vault(&cloud, master_password)?;
-            vault.merge_from_json(&decrypted_data)?;
-            
-            println!("✅ Sync completed successfully");
+            let decrypted_data = open_cloud_vault(&client, &cloud, master_password, !args.no_verify).await?;
+            if args.replace_local {
+                if !confirm_action("Replace every local entry with the cloud vault?")? {
+                    println!("❌ Sync cancelled, local vault unchanged");
+                    return Ok(());
+                }
+                let local_entries = vault.get_entries().cloned().unwrap_or_default();
+                vault.import_from_json(&decrypted_data)?;
+                println!("✅ Local vault replaced with cloud vault");
+                replay_pending(vault, &local_entries, master_password).await?;
+            } else {
+                let remote_entries = merge::entries_from_json(&decrypted_data)?;
+                let local_entries = vault.get_entries().cloned().unwrap_or_default();
+                let local_tombstones = merge::load_tombstones();
+                // The blob only holds live entries; deletions travel as entry rows
+                let deleted_rows: Vec<_> = client.fetch_entries_since(None).await?
+                    .into_iter()
+                    .filter(|row| row.deleted)
+                    .collect();
+                let (_, remote_tombstones) = decrypt_cloud_entries(&deleted_rows, master_password)?;
+                
+                // Forcing settles conflicts by last modification instead of stopping to ask
+                let strategy = match strategy {
+                    merge::ConflictStrategy::Manual if args.force => merge::ConflictStrategy::NewestWins,
+                    strategy => strategy,
+                };
+                let result = merge::merge_with(
+                    merge::MergeSide { entries: &local_entries, tombstones: &local_tombstones },
+                    merge::MergeSide { entries: &remote_entries, tombstones: &remote_tombstones },
+                    strategy,
+                    prompt_conflict,
+                );
+                
+                // Let other devices drop entries deleted here
+                let deleted: merge::Tombstones = result.tombstones.iter()
+                    .filter(|(id, _)| !remote_tombstones.contains_key(id))
+                    .map(|(id, deleted_at)| (*id, *deleted_at))
+                    .collect();
+                if !deleted.is_empty() {
+                    client.upsert_entries(&encrypt_cloud_entries(&[], &deleted, &user_id, &device_id, master_password)?).await?;
+                }
+                
+                let cloud_outdated = merge::diff(&result.entries, &remote_entries).has_changes();
+                if let Some(entries) = vault.get_entries_mut() {
+                    *entries = result.entries;
+                }
+                merge::save_tombstones(&result.tombstones)?;
+                
+                print_merge_report(&result.report);
+                
+                // Merging only brought the cloud's changes down; send ours up
+                if cloud_outdated {
+                    println!("📤 Uploading merged vault...");
+                    handle_upload(vault, master_password).await?;
+                }
+                println!("✅ Sync completed successfully ({})", strategy.as_str());
+            }
         }
         (None, None) => {
             // First sync - upload local vault
@@ -944,7 +2913,9 @@ async fn handle_sync(
         (None, Some(_)) => {
             // Download existing cloud vault
             println!("📥 Downloading existing cloud vault...");
-            handle_download(vault, master_password).await?;
+            let local_entries = vault.get_entries().cloned().unwrap_or_default();
+            handle_download(vault, master_password, DownloadArgs { no_verify: args.no_verify, preview: false, yes: false }).await?;
+            replay_pending(vault, &local_entries, master_password).await?;
         }
         (Some(_), None) => {
             // Upload local vault (cloud vault was deleted)
@@ -958,13 +2929,143 @@ async fn handle_sync(
         user_id,
         device_id,
         last_sync: Utc::now(),
-        sync_version: 1,
+        sync_version: SYNC_STRATEGY_VERSION,
         pending_changes: false,
-        conflict_resolution: "local_wins".to_string(),
+        conflict_resolution: strategy.as_str().to_string(),
     };
     
     client.update_sync_metadata(&metadata).await?;
     println!("🔄 Sync metadata updated");
+    pending::clear(&pending_queue_path())?;
+    
+    Ok(())
+}
+
+/// Put changes queued while offline back on top of a vault that was just
+/// replaced by the cloud copy, and upload them before the queue is cleared
+async fn replay_pending(vault: &mut Vault, local_entries: &[Entry], master_password: &str) -> Result<()> {
+    let queued = pending::load(&pending_queue_path(), master_password)?;
+    if queued.is_empty() {
+        return Ok(());
+    }
+    if let Some(entries) = vault.get_entries_mut() {
+        pending::replay(&queued, local_entries, entries);
+    }
+    println!("🔁 Re-applied {} change(s) made while offline", queued.len());
+    handle_upload(vault, master_password).await
+}
+
+/// Sync metadata from this version on records the conflict strategy in use.
+/// Older metadata always said "local_wins" while syncs merged newest-wins, so
+/// its strategy is ignored.
+const SYNC_STRATEGY_VERSION: i32 = 2;
+
+/// Ask whether to take the cloud copy of an entry edited on both sides
+fn prompt_conflict(local: &Entry, remote: &Entry) -> bool {
+    println!("⚔️  '{}' ({}) was changed on both sides", local.service, local.username);
+    for (side, entry) in [("Local", local), ("Cloud", remote)] {
+        println!("   {}: modified {}{}", side, entry.modified_at.format("%Y-%m-%d %H:%M UTC"),
+            if entry.username != local.username { format!(", username {}", entry.username) } else { String::new() });
+    }
+    if local.password != remote.password {
+        println!("   Passwords differ");
+    }
+    print!("   Keep (l)ocal or take (c)loud copy? [L/c]: ");
+    let mut input = String::new();
+    if io::stdout().flush().is_err() || io::stdin().read_line(&mut input).is_err() {
+        return false;
+    }
+    matches!(input.trim().to_lowercase().as_str(), "c" | "cloud")
+}
+
+/// Incremental sync: exchange only entries changed since the last sync.
+///
+/// The first delta sync migrates off the single-blob vault by merging it in and
+/// uploading every entry as its own row.
+async fn handle_delta_sync(
+    vault: &mut Vault,
+    args: &SyncArgs,
+    master_password: &str,
+    client: &cloud::SupabaseClient,
+    user_id: String,
+    device_id: String,
+    strategy: merge::ConflictStrategy
+) -> Result<()> {
+    use cloud::SyncMetadata;
+    use chrono::Utc;
+    use std::collections::HashSet;
+    
+    // Anything uploaded by other devices after this point is picked up next time
+    let sync_started = Utc::now();
+    
+    let migrating = !client.has_entry_rows().await?;
+    let since = if migrating {
+        println!("📦 No per-entry rows yet - migrating from the single-blob cloud vault");
+        None
+    } else {
+        client.get_sync_metadata(&device_id).await?.map(|meta| meta.last_sync)
+    };
+    
+    println!("📥 Fetching changed entries...");
+    let (mut remote_entries, remote_tombstones) =
+        decrypt_cloud_entries(&client.fetch_entries_since(since).await?, master_password)?;
+    
+    if migrating {
+        if let Some(cloud) = client.download_vault(&device_id).await? {
+            let decrypted_data = open_cloud_vault(client, &cloud, master_password, !args.no_verify).await?;
+            remote_entries.extend(merge::entries_from_json(&decrypted_data)?);
+        }
+    }
+    
+    let local_entries = vault.get_entries().cloned().unwrap_or_default();
+    let local_tombstones = merge::load_tombstones();
+    let result = merge::merge_with(
+        merge::MergeSide { entries: &local_entries, tombstones: &local_tombstones },
+        merge::MergeSide { entries: &remote_entries, tombstones: &remote_tombstones },
+        strategy,
+        prompt_conflict,
+    );
+    
+    // Don't echo back what was just downloaded
+    let downloaded: HashSet<_> = remote_entries.iter().map(|e| (e.id, e.modified_at)).collect();
+    let (changed, deleted) = merge::changed_since(&result.entries, &result.tombstones, since);
+    let to_upload: Vec<&Entry> = if migrating {
+        changed
+    } else {
+        changed.into_iter().filter(|e| !downloaded.contains(&(e.id, e.modified_at))).collect()
+    };
+    let deleted: merge::Tombstones = deleted.into_iter()
+        .filter(|(id, _)| !remote_tombstones.contains_key(id))
+        .collect();
+    
+    if args.dry_run {
+        println!("🔍 Dry run mode - showing what would be synced:");
+        print_merge_report(&result.report);
+        println!("  Would upload {} changed entry(ies) and {} deletion(s)", to_upload.len(), deleted.len());
+        return Ok(());
+    }
+    
+    let rows = encrypt_cloud_entries(&to_upload, &deleted, &user_id, &device_id, master_password)?;
+    println!("📤 Uploading {} changed entry(ies) and {} deletion(s)...", to_upload.len(), deleted.len());
+    client.upsert_entries(&rows).await?;
+    
+    if let Some(entries) = vault.get_entries_mut() {
+        *entries = result.entries;
+    }
+    merge::save_tombstones(&result.tombstones)?;
+    print_merge_report(&result.report);
+    
+    let metadata = SyncMetadata {
+        user_id,
+        device_id,
+        last_sync: sync_started,
+        sync_version: SYNC_STRATEGY_VERSION,
+        pending_changes: false,
+        conflict_resolution: strategy.as_str().to_string(),
+    };
+    client.update_sync_metadata(&metadata).await?;
+    println!("✅ Delta sync completed successfully");
+    pending::clear(&pending_queue_path())?;
     
     Ok(())
 }
@@ -978,17 +3079,18 @@ async fn handle_upload(
     
     println!("📤 Uploading vault to cloud storage...");
     
-    let mut client = SupabaseClient::new()?;
+    let mut client = SupabaseClient::new().map_err(offline_error)?;
     let user_id = get_or_create_user_id()?;
     let device_id = get_or_create_device_id()?;
     let device_name = std::env::var("PASSMANN_DEVICE_NAME")
         .unwrap_or_else(|_| "Unknown Device".to_string());
     
-    client.authenticate(user_id.clone()).await?;
+    client.authenticate(user_id.clone()).await.map_err(offline_error)?;
     
-    // Encrypt vault data
+    // Compress, then encrypt vault data (ciphertext doesn't compress)
     let vault_json = vault.export_to_json()?;
-    let (encrypted_data, salt) = encrypt_vault_data(&vault_json, master_password)?;
+    let payload = cloud::compress_payload(vault_json.as_bytes())?;
+    let (encrypted_data, salt) = encrypt_vault_data(&payload, master_password)?;
     
     let cloud_vault = CloudVault {
         id: None,
@@ -1000,20 +3102,26 @@ async fn handle_upload(
         version: 1,
         created_at: Some(Utc::now()),
         updated_at: Some(Utc::now()),
-        checksum: calculate_checksum(&vault_json)?,
+        checksum: calculate_checksum(&encrypted_data)?,
+        // Uploads were flagged like this before they were gzipped, so the flag
+        // can't tell the two apart; downloads sniff the gzip magic instead
+        // (`cloud::is_compressed`)
         compression_enabled: true,
-        size_bytes: vault_json.len() as i64,
+        size_bytes: encrypted_data.len() as i64,
+        vault_id: Some(get_or_create_vault_id()?),
     };
     
-    let vault_id = client.upload_vault(&cloud_vault).await?;
+    let vault_id = client.upload_vault(&cloud_vault).await.map_err(offline_error)?;
     println!("✅ Vault uploaded successfully (ID: {})", vault_id);
+    pending::clear(&pending_queue_path())?;
     
     Ok(())
 }
 
 async fn handle_download(
     vault: &mut Vault,
-    master_password: &str
+    master_password: &str,
+    args: DownloadArgs
 ) -> Result<()> {
     use cloud::SupabaseClient;
     
@@ -1032,7 +3140,19 @@ async fn handle_download(
             println!("📦 Found cloud vault: {} bytes", cloud.size_bytes);
             
             // Decrypt and load vault data
-            let decrypted_data = decrypt_cloud_vault(&cloud, master_password)?;
+            let decrypted_data = open_cloud_vault(&client, &cloud, master_password, !args.no_verify).await?;
+            if args.preview {
+                let local = vault.get_entries().map(|entries| entries.as_slice()).unwrap_or_default();
+                let changes = merge::diff(local, &merge::entries_from_json(&decrypted_data)?);
+                print_vault_diff(&changes);
+                if !changes.has_changes() {
+                    return Ok(());
+                }
+                if !args.yes && !confirm_action("Replace the local vault with the cloud copy?")? {
+                    println!("❌ Download cancelled, local vault unchanged");
+                    return Ok(());
+                }
+            }
             vault.import_from_json(&decrypted_data)?;
             
             println!("✅ Vault downloaded and decrypted successfully");
@@ -1046,9 +3166,18 @@ async fn handle_download(
     Ok(())
 }
 
-async fn handle_cloud_status() -> Result<()> {
+async fn handle_cloud_status(master_password: &str) -> Result<()> {
     use cloud::SupabaseClient;
     
+    // Works offline, so report it before trying to connect
+    let queued = pending::load(&pending_queue_path(), master_password)?;
+    if queued.is_empty() {
+        println!("📤 Pending upload: none");
+    } else {
+        println!("📤 Pending upload: {} change(s), oldest from {}", queued.len(),
+            queued.iter().map(|change| change.at).min().unwrap_or_default().format("%Y-%m-%d %H:%M UTC"));
+    }
+    
     println!("🌐 Checking cloud storage status...");
     
     let mut client = SupabaseClient::new()?;
@@ -1099,6 +3228,67 @@ async fn handle_cloud_status() -> Result<()> {
     Ok(())
 }
 
+async fn handle_devices(args: DevicesArgs) -> Result<()> {
+    use cloud::SupabaseClient;
+    
+    let mut client = SupabaseClient::new()?;
+    let user_id = get_or_create_user_id()?;
+    
+    if let Err(e) = client.authenticate(user_id).await {
+        return Err(format!("Not signed in to cloud storage ({}). Check SUPABASE_URL, SUPABASE_ANON_KEY, SUPABASE_ACCESS_TOKEN and PASSMANN_USER_ID", e).into());
+    }
+    
+    let devices = client.list_devices().await?;
+    let current_device = std::env::var("PASSMANN_DEVICE_ID").ok();
+    
+    match args.command {
+        None => {
+            if devices.is_empty() {
+                println!("📭 No devices have synced yet");
+                return Ok(());
+            }
+            
+            println!("📱 Synced devices ({}):", devices.len());
+            println!("{:-<60}", "");
+            for device in &devices {
+                let marker = if current_device.as_deref() == Some(device.device_id.as_str()) { " (this device)" } else { "" };
+                println!("📱 {}{}", device.device_name, marker);
+                println!("   ID:      {}", device.device_id);
+                println!("   Updated: {}", device.updated_at
+                    .map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+                    .unwrap_or_else(|| "unknown".to_string()));
+                println!("   Size:    {} bytes", device.size_bytes);
+                println!("   Version: {}", device.version);
+            }
+        }
+        Some(DeviceCommands::Revoke { device_id, force }) => {
+            let device = devices.iter()
+                .find(|d| d.device_id == device_id)
+                .ok_or_else(|| format!("No synced device with ID '{}'", device_id))?;
+            
+            if !force {
+                print!("⚠️  Delete the cloud vault for '{}' ({})? (y/N): ", device.device_name, device.device_id);
+                io::stdout().flush()?;
+                
+                let mut input = String::new();
+                io::stdin().read_line(&mut input)?;
+                
+                if !input.trim().to_lowercase().starts_with('y') {
+                    println!("❌ Revoke cancelled.");
+                    return Ok(());
+                }
+            }
+            
+            for vault_id in &device.vault_ids {
+                client.delete_vault(*vault_id).await?;
+            }
+            println!("🗑️  Revoked device '{}' ({} vault(s) deleted)", device.device_name, device.vault_ids.len());
+        }
+    }
+    
+    Ok(())
+}
+
 // ============================================================================
 // CLOUD UTILITY FUNCTIONS
 // ============================================================================
@@ -1135,13 +3325,25 @@ fn get_or_create_device_id() -> Result<String> {
     }
 }
 
-fn encrypt_vault_data(data: &str, master_password: &str) -> Result<(String, String)> {
+/// The id this vault's uploads are stored under, created on first upload
+fn get_or_create_vault_id() -> Result<uuid::Uuid> {
+    let path = cloud_vault_id_path();
+    if let Ok(stored) = std::fs::read_to_string(&path) {
+        return Ok(stored.trim().parse()
+            .map_err(|e| format!("Invalid cloud vault id in '{}': {}", path.display(), e))?);
+    }
+    let vault_id = uuid::Uuid::new_v4();
+    std::fs::write(&path, vault_id.to_string())?;
+    Ok(vault_id)
+}
+
+fn encrypt_vault_data(data: &[u8], master_password: &str) -> Result<(String, String)> {
     use base64::{Engine as _, engine::general_purpose};
     use {derive_key, encrypt};
     
     let salt = generate_salt(32);
     let key = derive_key(master_password, &salt);
-    let encrypted = encrypt(&key, data.as_bytes());
+    let encrypted = encrypt(&key, data);
     
     Ok((
         general_purpose::STANDARD.encode(encrypted),
@@ -1149,19 +3351,160 @@ fn encrypt_vault_data(data: &str, master_password: &str) -> Result<(String, Stri
     ))
 }
 
-fn decrypt_cloud_vault(cloud_vault: &cloud::CloudVault, master_password: &str) -> Result<String> {
+/// Decrypt a downloaded vault, recording a security audit event if it fails verification
+async fn open_cloud_vault(
+    client: &cloud::SupabaseClient,
+    cloud_vault: &cloud::CloudVault,
+    master_password: &str,
+    verify: bool
+) -> Result<String> {
+    if !verify {
+        println!("⚠️  Skipping cloud vault integrity check (--no-verify)");
+    }
+    
+    match decrypt_cloud_vault(cloud_vault, master_password, verify) {
+        Err(e) if e.is::<cloud::IntegrityError>() => {
+            client.log_audit_action("security_violation", false, Some(e.to_string()), Some(serde_json::json!({
+                "vault_id": cloud_vault.id,
+                "device_id": cloud_vault.device_id
+            }))).await?;
+            Err(e)
+        }
+        result => result,
+    }
+}
+
+fn decrypt_cloud_vault(cloud_vault: &cloud::CloudVault, master_password: &str, verify: bool) -> Result<String> {
     use base64::{Engine as _, engine::general_purpose};
     use passmann_shared::{derive_key, decrypt};
     
+    let payload_verified = cloud::verify_checksum(&cloud_vault.checksum, cloud_vault.encrypted_data.as_bytes());
     let encrypted_data = general_purpose::STANDARD.decode(&cloud_vault.encrypted_data)?;
     let salt = general_purpose::STANDARD.decode(&cloud_vault.salt)?;
     
     let key = derive_key(master_password, &salt);
     let decrypted = decrypt(&key, &encrypted_data)?;
     
+    // Vaults uploaded before compression existed carry the flag but plain JSON
+    let decrypted = if cloud_vault.compression_enabled && cloud::is_compressed(&decrypted) {
+        cloud::decompress_payload(&decrypted)?
+    } else {
+        decrypted
+    };
+    
+    // Vaults uploaded before compression existed hashed the plaintext JSON instead
+    if verify && !payload_verified && !cloud::verify_checksum(&cloud_vault.checksum, &decrypted) {
+        return Err(cloud::IntegrityError { vault_id: cloud_vault.id }.into());
+    }
+    
     Ok(String::from_utf8(decrypted)?)
 }
 
+/// Encrypt entries for per-row upload. One salt and key is shared by the batch
+/// so Argon2 runs once per sync rather than once per entry.
+fn encrypt_cloud_entries(
+    entries: &[&Entry],
+    tombstones: &merge::Tombstones,
+    user_id: &str,
+    device_id: &str,
+    master_password: &str
+) -> Result<Vec<cloud::CloudEntry>> {
+    use base64::{Engine as _, engine::general_purpose};
+    use chrono::Utc;
+    
+    let salt = generate_salt(32);
+    let key = derive_key(master_password, &salt);
+    let encoded_salt = general_purpose::STANDARD.encode(&salt);
+    let now = Utc::now();
+    
+    let mut rows = Vec::with_capacity(entries.len() + tombstones.len());
+    for entry in entries {
+        let entry_json = serde_json::to_vec(entry)?;
+        rows.push(cloud::CloudEntry {
+            id: entry.id,
+            user_id: user_id.to_string(),
+            device_id: device_id.to_string(),
+            encrypted_data: general_purpose::STANDARD.encode(encrypt(&key, &entry_json)),
+            salt: encoded_salt.clone(),
+            updated_at: now,
+            deleted: false,
+            deleted_at: None,
+        });
+    }
+    for (id, deleted_at) in tombstones {
+        rows.push(cloud::CloudEntry::tombstone(*id, user_id, device_id, *deleted_at, now));
+    }
+    
+    Ok(rows)
+}
+
+/// Decrypt downloaded entry rows into live entries and deletion markers
+fn decrypt_cloud_entries(
+    rows: &[cloud::CloudEntry],
+    master_password: &str
+) -> Result<(Vec<Entry>, merge::Tombstones)> {
+    use base64::{Engine as _, engine::general_purpose};
+    use passmann_shared::decrypt;
+    use std::collections::HashMap;
+    
+    let mut keys = HashMap::new();
+    let mut entries = Vec::new();
+    let mut tombstones = merge::Tombstones::new();
+    
+    for row in rows {
+        if let Some(deleted_at) = row.deletion_time() {
+            tombstones.insert(row.id, deleted_at);
+            continue;
+        }
+        
+        if !keys.contains_key(&row.salt) {
+            let salt = general_purpose::STANDARD.decode(&row.salt)?;
+            keys.insert(row.salt.clone(), derive_key(master_password, &salt));
+        }
+        let encrypted_data = general_purpose::STANDARD.decode(&row.encrypted_data)?;
+        let decrypted = decrypt(&keys[&row.salt], &encrypted_data)?;
+        entries.push(serde_json::from_slice(&decrypted)?);
+    }
+    
+    Ok((entries, tombstones))
+}
+
+/// Append an event to the vault's audit log, if it has one
+fn record_audit(vault: &mut Vault, action: &str, details: &str) {
+    if let Some(audit) = vault.audit.as_mut() {
+        audit.log_event(action, details);
+    }
+}
+
+fn print_merge_report(report: &merge::MergeReport) {
+    if !report.has_changes() {
+        println!("✅ Already up to date ({} entries unchanged)", report.unchanged);
+        return;
+    }
+    println!("🔀 Merge summary:");
+    println!("   ➕ Added:     {}", report.added);
+    println!("   ✏️  Updated:   {}", report.updated);
+    println!("   🗑️  Deleted:   {}", report.deleted);
+    println!("   ➖ Unchanged: {}", report.unchanged);
+}
+
+fn print_vault_diff(diff: &merge::VaultDiff) {
+    if !diff.has_changes() {
+        println!("✅ Local vault already matches the cloud copy");
+        return;
+    }
+    println!("🔍 Applying the cloud copy would change:");
+    for (symbol, label, rows) in [("➕", "Added", &diff.added), ("✏️ ", "Updated", &diff.updated), ("🗑️ ", "Removed", &diff.removed)] {
+        if rows.is_empty() {
+            continue;
+        }
+        println!("   {} {} ({}):", symbol, label, rows.len());
+        for (service, username) in rows {
+            println!("      • {} ({})", service, username);
+        }
+    }
+}
+
 fn calculate_checksum(data: &str) -> Result<String> {
     use blake3::Hasher;
     
@@ -1186,9 +3529,8 @@ fn copy_to_clipboard(text: &str) -> Result<()> {
     Ok(())
 }
 
-fn get_password_strength_indicator(password: &str) -> String {
-    let strength = estimate_password_strength(password);
-    match strength.score {
+fn get_password_strength_indicator(score: u8) -> String {
+    match score {
         0..=30 => "🔴 Weak".to_string(),
         31..=60 => "🟡 Fair".to_string(),
         61..=80 => "🟢 Good".to_string(),
@@ -0,0 +1,503 @@
+use chrono::{DateTime, Utc};
+use passmann_shared::{Entry, Result};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// Deletion markers keyed by entry id, recording when each entry was removed
+pub type Tombstones = HashMap<Uuid, DateTime<Utc>>;
+
+/// One side of a merge: its live entries plus the deletions it knows about
+pub struct MergeSide<'a> {
+    pub entries: &'a [Entry],
+    pub tombstones: &'a Tombstones,
+}
+
+/// What a merge changed, from the point of view of the local vault
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MergeReport {
+    /// Entries that only existed remotely and were brought in
+    pub added: usize,
+    /// Local entries replaced by a newer remote version
+    pub updated: usize,
+    /// Local entries removed because of a newer remote deletion
+    pub deleted: usize,
+    /// Local entries kept as they were
+    pub unchanged: usize,
+}
+
+impl MergeReport {
+    pub fn has_changes(&self) -> bool {
+        self.added + self.updated + self.deleted > 0
+    }
+}
+
+/// How to settle an entry edited differently on both sides. Deletions always
+/// follow their timestamps, whatever the strategy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+#[value(rename_all = "snake_case")]
+pub enum ConflictStrategy {
+    LocalWins,
+    RemoteWins,
+    /// Keep whichever copy was modified last (ties keep the local copy)
+    #[default]
+    NewestWins,
+    /// Ask for every conflicting entry
+    Manual,
+}
+
+impl ConflictStrategy {
+    /// Name stored in the sync metadata
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ConflictStrategy::LocalWins => "local_wins",
+            ConflictStrategy::RemoteWins => "remote_wins",
+            ConflictStrategy::NewestWins => "newest_wins",
+            ConflictStrategy::Manual => "manual",
+        }
+    }
+
+    /// Read a stored strategy; anything unknown falls back to newest-wins
+    pub fn from_stored(value: &str) -> Self {
+        match value {
+            "local_wins" => ConflictStrategy::LocalWins,
+            "remote_wins" => ConflictStrategy::RemoteWins,
+            "manual" => ConflictStrategy::Manual,
+            _ => ConflictStrategy::NewestWins,
+        }
+    }
+}
+
+pub struct MergeResult {
+    pub entries: Vec<Entry>,
+    pub tombstones: Tombstones,
+    pub report: MergeReport,
+}
+
+/// Merge two versions of a vault.
+///
+/// Entries are matched by `id`. When both sides have an entry the one with the
+/// newer `modified_at` wins (ties keep the local copy), entries present on only
+/// one side are unioned, and a deletion wins over an edit only if it is newer.
+pub fn merge(local: MergeSide, remote: MergeSide) -> MergeResult {
+    merge_with(local, remote, ConflictStrategy::NewestWins, |_, _| false)
+}
+
+/// Like [`merge`], but entries that differ on both sides are settled by
+/// `strategy`. With `Manual`, `take_remote(local, remote)` decides each one.
+pub fn merge_with(
+    local: MergeSide,
+    remote: MergeSide,
+    strategy: ConflictStrategy,
+    mut take_remote: impl FnMut(&Entry, &Entry) -> bool,
+) -> MergeResult {
+    let mut tombstones = local.tombstones.clone();
+    for (id, deleted_at) in remote.tombstones {
+        let latest = tombstones.entry(*id).or_insert(*deleted_at);
+        if *deleted_at > *latest {
+            *latest = *deleted_at;
+        }
+    }
+
+    let remote_by_id: HashMap<Uuid, &Entry> = remote.entries.iter().map(|e| (e.id, e)).collect();
+    let deleted_after = |entry: &Entry| {
+        tombstones.get(&entry.id).is_some_and(|deleted_at| *deleted_at > entry.modified_at)
+    };
+
+    let mut report = MergeReport::default();
+    let mut merged = Vec::with_capacity(local.entries.len().max(remote.entries.len()));
+
+    let mut remote_wins = |local_entry: &Entry, remote_entry: &Entry| match strategy {
+        ConflictStrategy::LocalWins => false,
+        ConflictStrategy::RemoteWins => true,
+        ConflictStrategy::NewestWins => remote_entry.modified_at > local_entry.modified_at,
+        ConflictStrategy::Manual => take_remote(local_entry, remote_entry),
+    };
+
+    for local_entry in local.entries {
+        let (winner, from_remote) = match remote_by_id.get(&local_entry.id) {
+            Some(remote_entry) if !same_content(local_entry, remote_entry) && remote_wins(local_entry, remote_entry) => {
+                (*remote_entry, true)
+            }
+            _ => (local_entry, false),
+        };
+
+        if deleted_after(winner) {
+            report.deleted += 1;
+        } else {
+            if from_remote {
+                report.updated += 1;
+            } else {
+                report.unchanged += 1;
+            }
+            merged.push(winner.clone());
+        }
+    }
+
+    let local_ids: HashSet<Uuid> = local.entries.iter().map(|e| e.id).collect();
+    for remote_entry in remote.entries {
+        if !local_ids.contains(&remote_entry.id) && !deleted_after(remote_entry) {
+            report.added += 1;
+            merged.push(remote_entry.clone());
+        }
+    }
+
+    // An edit newer than the deletion resurrected these entries
+    for entry in &merged {
+        tombstones.remove(&entry.id);
+    }
+
+    MergeResult { entries: merged, tombstones, report }
+}
+
+/// What replacing the local entries with `remote` would change, as
+/// `(service, username)` pairs. Nothing is applied.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct VaultDiff {
+    /// Only in the remote copy
+    pub added: Vec<(String, String)>,
+    /// On both sides with different content
+    pub updated: Vec<(String, String)>,
+    /// Only in the local vault
+    pub removed: Vec<(String, String)>,
+}
+
+impl VaultDiff {
+    pub fn has_changes(&self) -> bool {
+        !(self.added.is_empty() && self.updated.is_empty() && self.removed.is_empty())
+    }
+}
+
+/// Compare two versions of a vault entry by entry, matching on `id`
+pub fn diff(local: &[Entry], remote: &[Entry]) -> VaultDiff {
+    let label = |entry: &Entry| (entry.service.clone(), entry.username.clone());
+    let local_by_id: HashMap<Uuid, &Entry> = local.iter().map(|e| (e.id, e)).collect();
+    let remote_ids: HashSet<Uuid> = remote.iter().map(|e| e.id).collect();
+
+    let mut diff = VaultDiff::default();
+    for remote_entry in remote {
+        match local_by_id.get(&remote_entry.id) {
+            None => diff.added.push(label(remote_entry)),
+            Some(local_entry) if !same_content(local_entry, remote_entry) => diff.updated.push(label(remote_entry)),
+            Some(_) => {}
+        }
+    }
+    diff.removed = local.iter().filter(|e| !remote_ids.contains(&e.id)).map(label).collect();
+    diff
+}
+
+fn same_content(a: &Entry, b: &Entry) -> bool {
+    a.modified_at == b.modified_at
+        && (&a.service, &a.username, &a.password, &a.url, &a.notes) == (&b.service, &b.username, &b.password, &b.url, &b.notes)
+}
+
+/// Entries and deletions newer than `since`, or everything when there was no previous sync
+pub fn changed_since<'a>(
+    entries: &'a [Entry],
+    tombstones: &Tombstones,
+    since: Option<DateTime<Utc>>,
+) -> (Vec<&'a Entry>, Tombstones) {
+    let is_new = |at: &DateTime<Utc>| since.is_none_or(|since| *at > since);
+    let changed = entries.iter().filter(|e| is_new(&e.modified_at)).collect();
+    let deleted = tombstones.iter()
+        .filter(|(_, deleted_at)| is_new(deleted_at))
+        .map(|(id, deleted_at)| (*id, *deleted_at))
+        .collect();
+    (changed, deleted)
+}
+
+/// Fold `other` into `keep` when both describe the same account: the newer
+/// password and URL win, tags and custom fields are unioned (the newer entry
+/// winning conflicts) and the longer notes are kept. `keep` keeps its id.
+pub fn merge_entry_fields(keep: &mut Entry, other: &Entry) {
+    let other_newer = other.modified_at > keep.modified_at;
+    if other_newer {
+        keep.password = other.password.clone();
+        keep.password_strength = other.password_strength.clone();
+    }
+    if other.url.is_some() && (other_newer || keep.url.is_none()) {
+        keep.url = other.url.clone();
+    }
+    for tag in &other.tags {
+        if !keep.tags.contains(tag) {
+            keep.tags.push(tag.clone());
+        }
+    }
+    for (key, value) in &other.custom_fields {
+        if other_newer || !keep.custom_fields.contains_key(key) {
+            keep.custom_fields.insert(key.clone(), value.clone());
+        }
+    }
+    let note_len = |notes: &Option<String>| notes.as_ref().map_or(0, |n| n.chars().count());
+    if note_len(&other.notes) > note_len(&keep.notes) {
+        keep.notes = other.notes.clone();
+    }
+    keep.created_at = keep.created_at.min(other.created_at);
+    keep.modified_at = Utc::now();
+}
+
+/// Entries sharing a service (ignoring case) and username, newest first within
+/// each group. Groups are ordered by service.
+pub fn duplicate_groups(entries: &[Entry]) -> Vec<Vec<&Entry>> {
+    let mut groups: HashMap<(String, &str), Vec<&Entry>> = HashMap::new();
+    for entry in entries {
+        groups.entry((entry.service.to_lowercase(), entry.username.as_str())).or_default().push(entry);
+    }
+
+    let mut duplicates: Vec<Vec<&Entry>> = groups.into_values().filter(|group| group.len() > 1).collect();
+    for group in &mut duplicates {
+        group.sort_by_key(|entry| std::cmp::Reverse(entry.modified_at));
+    }
+    duplicates.sort_by_key(|group| (group[0].service.to_lowercase(), group[0].username.clone()));
+    duplicates
+}
+
+/// Parse entries out of a vault JSON export, accepting either a bare array or
+/// an object with an `entries` field.
+pub fn entries_from_json(json: &str) -> Result<Vec<Entry>> {
+    let value: serde_json::Value = serde_json::from_str(json)?;
+    let entries = match value {
+        serde_json::Value::Array(_) => value,
+        serde_json::Value::Object(mut map) => map.remove("entries")
+            .ok_or("Vault JSON has no entries field")?,
+        _ => return Err("Unexpected vault JSON format".into()),
+    };
+    Ok(serde_json::from_value(entries)?)
+}
+
+fn tombstones_path() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("passmann")
+        .join("sync_tombstones.json")
+}
+
+/// Load locally recorded deletions. Only ids and timestamps are stored.
+pub fn load_tombstones() -> Tombstones {
+    std::fs::read_to_string(tombstones_path())
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_tombstones(tombstones: &Tombstones) -> Result<()> {
+    let path = tombstones_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string(tombstones)?)?;
+    Ok(())
+}
+
+/// Remember that these entries were deleted locally so a sync doesn't resurrect them
+pub fn record_deletions(ids: impl IntoIterator<Item = Uuid>) -> Result<()> {
+    let mut tombstones = load_tombstones();
+    let now = Utc::now();
+    for id in ids {
+        tombstones.insert(id, now);
+    }
+    save_tombstones(&tombstones)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn entry(service: &str, modified_minutes_ago: i64) -> Entry {
+        let mut entry = Entry::new(service.to_string(), "user".to_string(), "password".to_string());
+        entry.modified_at = Utc::now() - Duration::minutes(modified_minutes_ago);
+        entry
+    }
+
+    #[test]
+    fn test_merge_entry_fields_unions_and_prefers_newer() {
+        let mut keep = entry("github", 60);
+        keep.tags = vec!["work".to_string()];
+        keep.custom_fields.insert("pin".to_string(), "old".to_string());
+        keep.notes = Some("short".to_string());
+
+        let mut other = entry("GitHub", 5);
+        other.password = "newer".to_string();
+        other.tags = vec!["work".to_string(), "dev".to_string()];
+        other.custom_fields.insert("pin".to_string(), "new".to_string());
+        other.notes = Some("a longer note".to_string());
+
+        let id = keep.id;
+        merge_entry_fields(&mut keep, &other);
+        assert_eq!(keep.id, id);
+        assert_eq!(keep.service, "github");
+        assert_eq!(keep.password, "newer");
+        assert_eq!(keep.tags, vec!["work".to_string(), "dev".to_string()]);
+        assert_eq!(keep.custom_fields["pin"], "new");
+        assert_eq!(keep.notes.as_deref(), Some("a longer note"));
+    }
+
+    #[test]
+    fn test_duplicate_groups_newest_first() {
+        let old = entry("GitHub", 60);
+        let new = entry("github", 5);
+        let mut other_user = entry("github", 1);
+        other_user.username = "someone-else".to_string();
+        let entries = vec![old.clone(), entry("gitlab", 0), new.clone(), other_user];
+
+        let groups = duplicate_groups(&entries);
+        assert_eq!(groups.len(), 1);
+        let ids: Vec<Uuid> = groups[0].iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![new.id, old.id]);
+    }
+
+    #[test]
+    fn test_merge_edit_on_both_sides_keeps_newest() {
+        let local_entry = entry("github", 30);
+        let mut remote_entry = local_entry.clone();
+        remote_entry.password = "remote_password".to_string();
+        remote_entry.modified_at = Utc::now() - Duration::minutes(5);
+
+        let none = Tombstones::new();
+        let result = merge(
+            MergeSide { entries: std::slice::from_ref(&local_entry), tombstones: &none },
+            MergeSide { entries: &[remote_entry], tombstones: &none },
+        );
+        assert_eq!(result.entries.len(), 1);
+        assert_eq!(result.entries[0].password, "remote_password");
+        assert_eq!(result.report, MergeReport { added: 0, updated: 1, deleted: 0, unchanged: 0 });
+
+        // Newer local edit wins the other way round
+        let mut newer_local = local_entry.clone();
+        newer_local.modified_at = Utc::now();
+        let mut older_remote = local_entry;
+        older_remote.password = "stale".to_string();
+        let result = merge(
+            MergeSide { entries: &[newer_local], tombstones: &none },
+            MergeSide { entries: &[older_remote], tombstones: &none },
+        );
+        assert_eq!(result.entries[0].password, "password");
+        assert_eq!(result.report.unchanged, 1);
+    }
+
+    #[test]
+    fn test_merge_with_each_strategy() {
+        let none = Tombstones::new();
+        let mut local_entry = entry("github", 10);
+        local_entry.password = "local-edit".to_string();
+        let mut remote_entry = local_entry.clone();
+        remote_entry.password = "remote-edit".to_string();
+        remote_entry.modified_at = Utc::now() - Duration::minutes(20);
+        let (local_entries, remote_entries) = (vec![local_entry], vec![remote_entry]);
+
+        let password_with = |strategy, take_remote: bool| {
+            let result = merge_with(
+                MergeSide { entries: &local_entries, tombstones: &none },
+                MergeSide { entries: &remote_entries, tombstones: &none },
+                strategy,
+                |local, remote| {
+                    assert_eq!((local.password.as_str(), remote.password.as_str()), ("local-edit", "remote-edit"));
+                    take_remote
+                },
+            );
+            (result.entries[0].password.clone(), result.report.updated)
+        };
+
+        assert_eq!(password_with(ConflictStrategy::LocalWins, true), ("local-edit".to_string(), 0));
+        assert_eq!(password_with(ConflictStrategy::RemoteWins, false), ("remote-edit".to_string(), 1));
+        // The local edit is newer
+        assert_eq!(password_with(ConflictStrategy::NewestWins, true), ("local-edit".to_string(), 0));
+        assert_eq!(password_with(ConflictStrategy::Manual, true), ("remote-edit".to_string(), 1));
+        assert_eq!(password_with(ConflictStrategy::Manual, false), ("local-edit".to_string(), 0));
+
+        assert_eq!(ConflictStrategy::from_stored(ConflictStrategy::RemoteWins.as_str()), ConflictStrategy::RemoteWins);
+        assert_eq!(ConflictStrategy::from_stored("merge_by_entry"), ConflictStrategy::NewestWins);
+    }
+
+    #[test]
+    fn test_merge_delete_vs_edit() {
+        let none = Tombstones::new();
+
+        // Local delete newer than the remote edit: the delete wins
+        let remote_edit = entry("gitlab", 30);
+        let mut local_deleted = Tombstones::new();
+        local_deleted.insert(remote_edit.id, Utc::now() - Duration::minutes(10));
+        let result = merge(
+            MergeSide { entries: &[], tombstones: &local_deleted },
+            MergeSide { entries: std::slice::from_ref(&remote_edit), tombstones: &none },
+        );
+        assert!(result.entries.is_empty());
+        assert!(result.tombstones.contains_key(&remote_edit.id));
+        assert_eq!(result.report, MergeReport::default());
+
+        // Remote edit newer than the local delete: the edit wins
+        let mut late_edit = remote_edit.clone();
+        late_edit.modified_at = Utc::now();
+        let result = merge(
+            MergeSide { entries: &[], tombstones: &local_deleted },
+            MergeSide { entries: &[late_edit], tombstones: &none },
+        );
+        assert_eq!(result.entries.len(), 1);
+        assert!(result.tombstones.is_empty());
+        assert_eq!(result.report.added, 1);
+
+        // Remote delete newer than the local copy removes it locally
+        let local_entry = entry("bitbucket", 30);
+        let mut remote_deleted = Tombstones::new();
+        remote_deleted.insert(local_entry.id, Utc::now());
+        let result = merge(
+            MergeSide { entries: &[local_entry], tombstones: &none },
+            MergeSide { entries: &[], tombstones: &remote_deleted },
+        );
+        assert!(result.entries.is_empty());
+        assert_eq!(result.report.deleted, 1);
+    }
+
+    #[test]
+    fn test_merge_disjoint_additions_are_unioned() {
+        let none = Tombstones::new();
+        let local_entries = vec![entry("email", 10), entry("bank", 20)];
+        let remote_entries = vec![entry("forum", 5)];
+
+        let result = merge(
+            MergeSide { entries: &local_entries, tombstones: &none },
+            MergeSide { entries: &remote_entries, tombstones: &none },
+        );
+        assert_eq!(result.entries.len(), 3);
+        assert_eq!(result.report, MergeReport { added: 1, updated: 0, deleted: 0, unchanged: 2 });
+    }
+
+    #[test]
+    fn test_diff_reports_without_applying() {
+        let kept = entry("email", 10);
+        let local_edit = entry("github", 30);
+        let mut remote_edit = local_edit.clone();
+        remote_edit.password = "rotated".to_string();
+        remote_edit.modified_at = Utc::now();
+        let local = vec![kept.clone(), local_edit, entry("stale", 90)];
+        let remote = vec![kept, remote_edit, entry("bank", 5)];
+
+        let changes = diff(&local, &remote);
+        let user = |service: &str| (service.to_string(), "user".to_string());
+        assert_eq!(changes.added, vec![user("bank")]);
+        assert_eq!(changes.updated, vec![user("github")]);
+        assert_eq!(changes.removed, vec![user("stale")]);
+        assert!(!diff(&local[..1], &local[..1]).has_changes());
+    }
+
+    #[test]
+    fn test_changed_since_filters_by_last_sync() {
+        let entries = vec![entry("old", 60), entry("new", 1)];
+        let mut tombstones = Tombstones::new();
+        tombstones.insert(Uuid::new_v4(), Utc::now() - Duration::minutes(90));
+        let recent_delete = Uuid::new_v4();
+        tombstones.insert(recent_delete, Utc::now() - Duration::minutes(2));
+
+        let last_sync = Some(Utc::now() - Duration::minutes(30));
+        let (changed, deleted) = changed_since(&entries, &tombstones, last_sync);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].service, "new");
+        assert_eq!(deleted.keys().collect::<Vec<_>>(), vec![&recent_delete]);
+
+        // Without a previous sync everything counts as changed
+        let (changed, deleted) = changed_since(&entries, &tombstones, None);
+        assert_eq!(changed.len(), 2);
+        assert_eq!(deleted.len(), 2);
+    }
+}
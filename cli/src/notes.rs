@@ -0,0 +1,85 @@
+use passmann_shared::Result;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::process::Command;
+use uuid::Uuid;
+
+/// The user's editor command from $VISUAL or $EDITOR, if either is set
+pub fn configured_editor() -> Option<String> {
+    ["VISUAL", "EDITOR"].iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|editor| !editor.trim().is_empty())
+}
+
+/// Edit `initial` in `editor` through a private temp file, which is
+/// overwritten and removed afterwards whether or not the editor succeeds
+pub fn edit_in_editor(editor: &str, initial: &str) -> Result<String> {
+    let path = std::env::temp_dir().join(format!("passmann_note_{}.txt", Uuid::new_v4()));
+    create_private(&path)?.write_all(initial.as_bytes())?;
+
+    let result = run_editor(editor, &path).and_then(|()| Ok(fs::read_to_string(&path)?));
+    secure_remove(&path)?;
+    result
+}
+
+/// Read notes from stdin until EOF
+pub fn read_from_stdin() -> Result<String> {
+    let mut notes = String::new();
+    io::stdin().read_to_string(&mut notes)?;
+    Ok(notes)
+}
+
+fn run_editor(editor: &str, path: &Path) -> Result<()> {
+    // Allow editors with arguments, e.g. `code --wait`
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().ok_or("Editor command is empty")?;
+    let status = Command::new(program).args(parts).arg(path).status()
+        .map_err(|e| format!("Could not start editor '{}': {}", program, e))?;
+    if !status.success() {
+        return Err(format!("Editor exited with {}", status).into());
+    }
+    Ok(())
+}
+
+fn create_private(path: &Path) -> Result<File> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    Ok(options.open(path)?)
+}
+
+/// Overwrite a file with zeros before removing it
+fn secure_remove(path: &Path) -> Result<()> {
+    if let Ok(metadata) = fs::metadata(path) {
+        let mut file = OpenOptions::new().write(true).open(path)?;
+        file.write_all(&vec![0u8; metadata.len() as usize])?;
+        file.sync_all()?;
+    }
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secure_remove_deletes_private_file() {
+        let path = std::env::temp_dir().join(format!("passmann_note_test_{}.txt", Uuid::new_v4()));
+        create_private(&path).unwrap().write_all(b"recovery codes").unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+
+        secure_remove(&path).unwrap();
+        assert!(!path.exists());
+    }
+}
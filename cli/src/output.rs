@@ -0,0 +1,96 @@
+//! Stable `--json` output shapes. Field names here are part of the CLI's
+//! scripting interface, so rename with care.
+
+use chrono::{DateTime, Utc};
+use passmann_shared::{Entry, Result};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// An entry as printed by `--json`. The password is only included on request.
+#[derive(Debug, Serialize)]
+pub struct EntryOutput {
+    pub id: Uuid,
+    pub service: String,
+    pub username: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    pub url: Option<String>,
+    pub tags: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub modified_at: DateTime<Utc>,
+}
+
+impl EntryOutput {
+    pub fn new(entry: &Entry, show_password: bool) -> Self {
+        Self {
+            id: entry.id,
+            service: entry.service.clone(),
+            username: entry.username.clone(),
+            password: show_password.then(|| entry.password.clone()),
+            url: entry.url.clone(),
+            tags: entry.tags.clone(),
+            created_at: entry.created_at,
+            modified_at: entry.modified_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListOutput {
+    /// Entries in this page
+    pub count: usize,
+    /// Entries matching the filters, before pagination
+    pub total: usize,
+    pub entries: Vec<EntryOutput>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FindOutput {
+    pub query: String,
+    pub count: usize,
+    pub entries: Vec<EntryOutput>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatsOutput {
+    pub total_entries: usize,
+    pub unique_services: usize,
+    pub has_duplicates: bool,
+    pub strong_passwords: usize,
+    pub weak_passwords: usize,
+    pub old_passwords: usize,
+    pub age_threshold_days: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatusOutput {
+    /// Seconds until auto-lock, or null when no auto-lock is configured
+    pub auto_lock_seconds: Option<u64>,
+    pub total_entries: usize,
+    pub unique_services: usize,
+    pub has_duplicates: bool,
+    pub expired_passwords: usize,
+    pub expiring_passwords: usize,
+    pub key_derivation_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GeneratedPassword {
+    pub password: String,
+    /// Strength score and level, omitted for PINs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GenerateOutput {
+    pub passwords: Vec<GeneratedPassword>,
+}
+
+/// Print a value as pretty JSON on stdout
+pub fn print_json<T: Serialize>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
@@ -0,0 +1,169 @@
+//! Queue of local changes the cloud hasn't seen yet, kept encrypted in
+//! `sync_queue.enc` next to the vault. Every command that changes entries adds
+//! to it; a successful sync or upload carries the changes up and empties it.
+
+use crate::sealed_file;
+use chrono::{DateTime, Utc};
+use passmann_shared::{Entry, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Updated,
+    Deleted,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingChange {
+    pub entry_id: Uuid,
+    pub service: String,
+    pub kind: ChangeKind,
+    pub at: DateTime<Utc>,
+}
+
+/// `id -> (modified_at, service)` for every entry, taken before a command runs
+pub type EntryVersions = HashMap<Uuid, (DateTime<Utc>, String)>;
+
+pub fn versions(entries: &[Entry]) -> EntryVersions {
+    entries.iter().map(|e| (e.id, (e.modified_at, e.service.clone()))).collect()
+}
+
+/// What changed between `before` and `after`
+pub fn changes(before: &EntryVersions, after: &[Entry], at: DateTime<Utc>) -> Vec<PendingChange> {
+    let mut changes: Vec<PendingChange> = after.iter()
+        .filter_map(|entry| {
+            let kind = match before.get(&entry.id) {
+                None => ChangeKind::Added,
+                Some((modified_at, _)) if *modified_at != entry.modified_at => ChangeKind::Updated,
+                Some(_) => return None,
+            };
+            Some(PendingChange { entry_id: entry.id, service: entry.service.clone(), kind, at })
+        })
+        .collect();
+    let remaining: HashSet<Uuid> = after.iter().map(|e| e.id).collect();
+    changes.extend(before.iter()
+        .filter(|(id, _)| !remaining.contains(id))
+        .map(|(id, (_, service))| PendingChange { entry_id: *id, service: service.clone(), kind: ChangeKind::Deleted, at }));
+    changes
+}
+
+/// Fold `change` into the queue, keeping one change per entry: an entry added
+/// and then deleted while offline never needs uploading, and later edits to a
+/// new entry keep it `Added`.
+fn enqueue(queue: &mut Vec<PendingChange>, change: PendingChange) {
+    let Some(index) = queue.iter().position(|queued| queued.entry_id == change.entry_id) else {
+        queue.push(change);
+        return;
+    };
+    match (queue[index].kind, change.kind) {
+        (ChangeKind::Added, ChangeKind::Deleted) => {
+            queue.remove(index);
+        }
+        (ChangeKind::Added, _) => queue[index] = PendingChange { kind: ChangeKind::Added, ..change },
+        _ => queue[index] = change,
+    }
+}
+
+pub fn load(path: &Path, master_password: &str) -> Result<Vec<PendingChange>> {
+    Ok(sealed_file::load(path, master_password)?.unwrap_or_default())
+}
+
+/// Add `changes` to the queue at `path`
+pub fn record(path: &Path, master_password: &str, changes: Vec<PendingChange>) -> Result<()> {
+    if changes.is_empty() {
+        return Ok(());
+    }
+    let mut queue = load(path, master_password)?;
+    for change in changes {
+        enqueue(&mut queue, change);
+    }
+    sealed_file::store(path, master_password, &queue)
+}
+
+/// Re-apply queued changes on top of `base` (typically a vault just replaced
+/// by the cloud copy), taking each changed entry from `local`. Entries the
+/// queue mentions but `local` no longer has were deleted offline.
+pub fn replay(queue: &[PendingChange], local: &[Entry], base: &mut Vec<Entry>) {
+    for change in queue {
+        let position = base.iter().position(|e| e.id == change.entry_id);
+        match (position, local.iter().find(|e| e.id == change.entry_id)) {
+            (Some(index), Some(entry)) => base[index] = entry.clone(),
+            (None, Some(entry)) => base.push(entry.clone()),
+            (Some(index), None) => {
+                base.remove(index);
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+/// Empty the queue once the cloud has everything
+pub fn clear(path: &Path) -> Result<()> {
+    match std::fs::remove_file(path) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(entry_id: Uuid, kind: ChangeKind) -> PendingChange {
+        PendingChange { entry_id, service: "github".to_string(), kind, at: Utc::now() }
+    }
+
+    #[test]
+    fn test_changes_between_versions() {
+        let kept = Entry::new("email".to_string(), "me".to_string(), "pw".to_string());
+        let mut edited = Entry::new("github".to_string(), "me".to_string(), "pw".to_string());
+        let removed = Entry::new("bank".to_string(), "me".to_string(), "pw".to_string());
+        let before = versions(&[kept.clone(), edited.clone(), removed.clone()]);
+
+        edited.modified_at += chrono::Duration::seconds(5);
+        let added = Entry::new("forum".to_string(), "me".to_string(), "pw".to_string());
+        let found = changes(&before, &[kept, edited.clone(), added.clone()], Utc::now());
+
+        let kinds: Vec<(Uuid, ChangeKind)> = found.iter().map(|c| (c.entry_id, c.kind)).collect();
+        assert_eq!(kinds, vec![(edited.id, ChangeKind::Updated), (added.id, ChangeKind::Added), (removed.id, ChangeKind::Deleted)]);
+        assert_eq!(found[2].service, "bank");
+    }
+
+    #[test]
+    fn test_enqueue_collapses_changes_per_entry() {
+        let (new_id, old_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut queue = Vec::new();
+        enqueue(&mut queue, change(new_id, ChangeKind::Added));
+        enqueue(&mut queue, change(new_id, ChangeKind::Updated));
+        enqueue(&mut queue, change(old_id, ChangeKind::Updated));
+        enqueue(&mut queue, change(old_id, ChangeKind::Deleted));
+        assert_eq!(queue.iter().map(|c| c.kind).collect::<Vec<_>>(), vec![ChangeKind::Added, ChangeKind::Deleted]);
+
+        enqueue(&mut queue, change(new_id, ChangeKind::Deleted));
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue[0].entry_id, old_id);
+    }
+
+    #[test]
+    fn test_replay_restores_offline_changes_over_downloaded_vault() {
+        let mut edited = Entry::new("github".to_string(), "me".to_string(), "pw".to_string());
+        let removed = Entry::new("bank".to_string(), "me".to_string(), "pw".to_string());
+        let untouched = Entry::new("email".to_string(), "me".to_string(), "pw".to_string());
+        let downloaded = vec![edited.clone(), removed.clone(), untouched.clone()];
+
+        edited.password = "new-pw".to_string();
+        let added = Entry::new("forum".to_string(), "me".to_string(), "pw".to_string());
+        let local = vec![edited.clone(), added.clone(), untouched.clone()];
+        let queue = vec![change(edited.id, ChangeKind::Updated), change(removed.id, ChangeKind::Deleted), change(added.id, ChangeKind::Added)];
+
+        let mut base = downloaded;
+        replay(&queue, &local, &mut base);
+        assert_eq!(base.iter().map(|e| e.id).collect::<Vec<_>>(), vec![edited.id, untouched.id, added.id]);
+        assert_eq!(base[0].password, "new-pw");
+    }
+}
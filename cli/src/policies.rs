@@ -0,0 +1,43 @@
+//! Per-entry generator policies, kept encrypted in `generation_policies.enc`
+//! next to the vault. Entries have no field for them, so the file maps entry
+//! ids to policies; `fix-weak` looks an entry up there before generating.
+
+use crate::generator::GenerationPolicy;
+use crate::sealed_file;
+use passmann_shared::{Entry, Result};
+use std::collections::HashMap;
+use std::path::Path;
+use uuid::Uuid;
+
+pub type Policies = HashMap<Uuid, GenerationPolicy>;
+
+/// Stored policies, empty when none were ever set
+pub fn load(path: &Path, master_password: &str) -> Result<Policies> {
+    Ok(sealed_file::load(path, master_password)?.unwrap_or_default())
+}
+
+/// Write `policies`, leaving out entries that are no longer in the vault
+pub fn store(path: &Path, master_password: &str, mut policies: Policies, entries: &[Entry]) -> Result<()> {
+    policies.retain(|id, _| entries.iter().any(|entry| entry.id == *id));
+    sealed_file::store(path, master_password, &policies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_keeps_policies_of_current_entries() {
+        let path = std::env::temp_dir().join(format!("passmann-policies-{}.enc", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        assert!(load(&path, "master").unwrap().is_empty());
+
+        let entries = vec![Entry::new("bank".to_string(), "me".to_string(), "pw".to_string())];
+        let policy = GenerationPolicy { length: 12, uppercase: true, digits: true, symbols: false, exclude: String::new() };
+        let policies = Policies::from([(entries[0].id, policy.clone()), (Uuid::new_v4(), policy.clone())]);
+        store(&path, "master", policies, &entries).unwrap();
+
+        assert_eq!(load(&path, "master").unwrap(), Policies::from([(entries[0].id, policy)]));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,66 @@
+//! Keeps generated passwords from repeating one already in the vault.
+//! Passwords are compared by BLAKE3 hash, whose equality check is constant-time.
+
+use passmann_shared::{Entry, Result};
+use uuid::Uuid;
+
+/// Draws before giving up on finding an unused password. A collision is
+/// practically impossible for generated passwords, so hitting this means the
+/// generator options leave almost no choice.
+const MAX_ATTEMPTS: usize = 16;
+
+/// Services of the entries, other than `except`, whose current or previous
+/// passwords include `password`
+pub fn used_by<'a>(entries: &'a [Entry], password: &str, except: Option<Uuid>) -> Vec<&'a str> {
+    let hash = blake3::hash(password.as_bytes());
+    entries.iter()
+        .filter(|entry| Some(entry.id) != except)
+        .filter(|entry| blake3::hash(entry.password.as_bytes()) == hash || entry.is_password_reused(password))
+        .map(|entry| entry.service.as_str())
+        .collect()
+}
+
+/// Call `generate` until it returns a password no other entry uses
+pub fn generate_unused(
+    entries: &[Entry],
+    except: Option<Uuid>,
+    mut generate: impl FnMut() -> Result<String>,
+) -> Result<String> {
+    for _ in 0..MAX_ATTEMPTS {
+        let password = generate()?;
+        if used_by(entries, &password, except).is_empty() {
+            return Ok(password);
+        }
+    }
+    Err("Could not generate a password that isn't already in use; widen the generator options or pass --allow-reuse".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries() -> Vec<Entry> {
+        vec![
+            Entry::new("github".to_string(), "me".to_string(), "taken-Password-1".to_string()),
+            Entry::new("gitlab".to_string(), "me".to_string(), "other-Password-2".to_string()),
+        ]
+    }
+
+    #[test]
+    fn test_used_by_skips_the_entry_itself() {
+        let entries = entries();
+        assert_eq!(used_by(&entries, "taken-Password-1", None), vec!["github"]);
+        assert!(used_by(&entries, "taken-Password-1", Some(entries[0].id)).is_empty());
+        assert!(used_by(&entries, "fresh-Password-3", None).is_empty());
+    }
+
+    #[test]
+    fn test_generate_unused_retries_collisions() {
+        let entries = entries();
+        let mut candidates = vec!["fresh-Password-3", "other-Password-2", "taken-Password-1"];
+        let password = generate_unused(&entries, None, || Ok(candidates.pop().unwrap().to_string())).unwrap();
+        assert_eq!(password, "fresh-Password-3");
+
+        assert!(generate_unused(&entries, None, || Ok("taken-Password-1".to_string())).is_err());
+    }
+}
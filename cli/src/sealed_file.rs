@@ -0,0 +1,95 @@
+//! Small JSON side files kept next to the vault and encrypted with the master
+//! password: a random salt followed by the encrypted JSON.
+//!
+//! A file keeps its salt when it is rewritten and derived keys are cached per
+//! salt for the rest of the process, so a command pays for one Argon2 run per
+//! side file however many times it loads and stores it.
+
+use passmann_shared::{decrypt, derive_key, encrypt, generate_salt, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{LazyLock, Mutex};
+
+const SALT_LEN: usize = 32;
+
+/// Salt and a hash of the password a key was derived from
+type KeyId = (Vec<u8>, blake3::Hash);
+
+static KEYS: LazyLock<Mutex<HashMap<KeyId, [u8; 32]>>> = LazyLock::new(Default::default);
+
+fn key_for(master_password: &str, salt: &[u8]) -> [u8; 32] {
+    let mut keys = KEYS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    *keys.entry((salt.to_vec(), blake3::hash(master_password.as_bytes())))
+        .or_insert_with(|| derive_key(master_password, salt))
+}
+
+/// The salt of an existing file, so rewriting it reuses the cached key
+fn existing_salt(path: &Path) -> Option<Vec<u8>> {
+    use std::io::Read;
+    let mut salt = vec![0u8; SALT_LEN];
+    std::fs::File::open(path).ok()?.read_exact(&mut salt).ok()?;
+    Some(salt)
+}
+
+/// The stored value, or `None` when the file doesn't exist
+pub fn load<T: DeserializeOwned>(path: &Path, master_password: &str) -> Result<Option<T>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let data = std::fs::read(path)?;
+    if data.len() <= SALT_LEN {
+        return Err(format!("'{}' is truncated", path.display()).into());
+    }
+    let (salt, ciphertext) = data.split_at(SALT_LEN);
+    let key = key_for(master_password, salt);
+    let plaintext = decrypt(&key, ciphertext).map_err(|_| format!("Could not decrypt '{}'", path.display()))?;
+    Ok(Some(serde_json::from_slice(&plaintext)?))
+}
+
+/// Encrypt `value` and replace the file atomically. A new file gets a fresh
+/// salt; an existing one keeps its own.
+pub fn store<T: Serialize>(path: &Path, master_password: &str, value: &T) -> Result<()> {
+    let salt = existing_salt(path).unwrap_or_else(|| generate_salt(SALT_LEN));
+    let key = key_for(master_password, &salt);
+    let mut data = salt;
+    data.extend(encrypt(&key, &serde_json::to_vec(value)?));
+
+    // Write beside the file and rename so a crash can't leave it half-written
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    std::fs::write(&temp, data)?;
+    std::fs::rename(&temp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_and_wrong_password() {
+        let path = std::env::temp_dir().join(format!("passmann-sealed-{}.enc", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(load::<Vec<u32>>(&path, "master").unwrap(), None);
+        store(&path, "master", &vec![1u32, 2, 3]).unwrap();
+        assert_eq!(load::<Vec<u32>>(&path, "master").unwrap(), Some(vec![1, 2, 3]));
+        assert!(load::<Vec<u32>>(&path, "wrong").is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_rewrite_keeps_salt_so_the_key_is_reused() {
+        let path = std::env::temp_dir().join(format!("passmann-sealed-salt-{}.enc", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        store(&path, "master", &1u32).unwrap();
+        let salt = existing_salt(&path).unwrap();
+        store(&path, "master", &2u32).unwrap();
+        assert_eq!(existing_salt(&path), Some(salt.clone()));
+        assert!(KEYS.lock().unwrap().contains_key(&(salt, blake3::hash(b"master"))));
+        assert_eq!(load::<u32>(&path, "master").unwrap(), Some(2));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
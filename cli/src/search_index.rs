@@ -0,0 +1,218 @@
+//! Inverted index for case-insensitive substring search (`find` and the TUI).
+//! Every searchable field is lowercased once and split into alphanumeric
+//! tokens; a query made only of token characters can only match inside a
+//! single token, so it is answered from the distinct tokens instead of
+//! lowercasing every field of every entry. Tokens are further indexed by
+//! their trigrams so queries of three or more characters only check the
+//! tokens that could contain them. Queries containing separators
+//! (`github.com`, `first last`) return `None` and the caller scans.
+//!
+//! Memory is a copy of each distinct token per trigram it contains plus the
+//! ids that use it, so it grows linearly with the searchable text.
+
+use passmann_shared::Entry;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+    /// Lowercase token -> entries with a field containing it
+    tokens: HashMap<String, HashSet<Uuid>>,
+    /// Tokens each entry added, so it can be removed without a full rebuild
+    entry_tokens: HashMap<Uuid, Vec<String>>,
+    /// Three-character substring -> tokens containing it
+    trigrams: HashMap<String, HashSet<String>>,
+}
+
+/// Every field `find` searches: those of the WASM `search_entries`, plus tags
+pub fn searchable_fields(entry: &Entry) -> impl Iterator<Item = &str> {
+    [entry.service.as_str(), entry.username.as_str()].into_iter()
+        .chain(entry.url.as_deref())
+        .chain(entry.notes.as_deref())
+        .chain(entry.tags.iter().map(String::as_str))
+}
+
+fn is_token_char(c: char) -> bool {
+    c.is_alphanumeric()
+}
+
+fn trigrams(text: &str) -> impl Iterator<Item = String> + '_ {
+    let chars: Vec<char> = text.chars().collect();
+    (0..chars.len().saturating_sub(2)).map(move |i| chars[i..i + 3].iter().collect())
+}
+
+fn entry_tokens(entry: &Entry) -> Vec<String> {
+    let tokens: HashSet<String> = searchable_fields(entry)
+        .flat_map(|field| field.to_lowercase()
+            .split(|c: char| !is_token_char(c))
+            .filter(|token| !token.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>())
+        .collect();
+    tokens.into_iter().collect()
+}
+
+impl SearchIndex {
+    pub fn build(entries: &[Entry]) -> Self {
+        let mut index = Self::default();
+        for entry in entries {
+            index.insert(entry);
+        }
+        index
+    }
+
+    pub fn insert(&mut self, entry: &Entry) {
+        let tokens = entry_tokens(entry);
+        for token in &tokens {
+            let ids = self.tokens.entry(token.clone()).or_default();
+            if ids.is_empty() {
+                for gram in trigrams(token) {
+                    self.trigrams.entry(gram).or_default().insert(token.clone());
+                }
+            }
+            ids.insert(entry.id);
+        }
+        self.entry_tokens.insert(entry.id, tokens);
+    }
+
+    pub fn remove(&mut self, id: Uuid) {
+        for token in self.entry_tokens.remove(&id).unwrap_or_default() {
+            if let Some(ids) = self.tokens.get_mut(&token) {
+                ids.remove(&id);
+                if ids.is_empty() {
+                    self.tokens.remove(&token);
+                    self.forget_trigrams(&token);
+                }
+            }
+        }
+    }
+
+    fn forget_trigrams(&mut self, token: &str) {
+        for gram in trigrams(token) {
+            if let Some(tokens) = self.trigrams.get_mut(&gram) {
+                tokens.remove(token);
+                if tokens.is_empty() {
+                    self.trigrams.remove(&gram);
+                }
+            }
+        }
+    }
+
+    /// Re-index an entry after its fields changed
+    pub fn update(&mut self, entry: &Entry) {
+        self.remove(entry.id);
+        self.insert(entry);
+    }
+
+    /// Entries with a field containing `query`, ignoring case. `None` when
+    /// the query is empty or spans token boundaries and needs a scan.
+    pub fn search(&self, query: &str) -> Option<HashSet<Uuid>> {
+        let query = query.to_lowercase();
+        if query.is_empty() || !query.chars().all(is_token_char) {
+            return None;
+        }
+        let ids_of = |token: &String| self.tokens.get(token).into_iter().flatten().copied();
+        if query.chars().count() < 3 {
+            return Some(self.tokens.keys()
+                .filter(|token| token.contains(&query))
+                .flat_map(ids_of)
+                .collect());
+        }
+
+        // Every token containing the query contains each of its trigrams,
+        // so the rarest one bounds the candidates
+        let mut candidates: Option<&HashSet<String>> = None;
+        for gram in trigrams(&query) {
+            let Some(tokens) = self.trigrams.get(&gram) else {
+                return Some(HashSet::new());
+            };
+            if candidates.is_none_or(|current| tokens.len() < current.len()) {
+                candidates = Some(tokens);
+            }
+        }
+        Some(candidates.into_iter().flatten()
+            .filter(|token| token.contains(&query))
+            .flat_map(ids_of)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(service: &str, username: &str) -> Entry {
+        Entry::new(service.to_string(), username.to_string(), "pw".to_string())
+    }
+
+    /// What `find` returned before the index: a lowercase scan of every field
+    fn scan(entries: &[Entry], query: &str) -> HashSet<Uuid> {
+        let query = query.to_lowercase();
+        entries.iter()
+            .filter(|e| searchable_fields(e).any(|field| field.to_lowercase().contains(&query)))
+            .map(|e| e.id)
+            .collect()
+    }
+
+    #[test]
+    fn test_search_matches_linear_scan() {
+        let mut entries = vec![entry("GitHub", "me@example.com"), entry("gitlab.com", "work"), entry("Bank", "Me")];
+        entries[2].tags.push("finance".to_string());
+        entries[1].notes = Some("shared with the team".to_string());
+        let index = SearchIndex::build(&entries);
+
+        for query in ["git", "GITHUB", "hub", "me", "e", "example", "fin", "team", "nothing", "xyz"] {
+            assert_eq!(index.search(query), Some(scan(&entries, query)), "query {:?}", query);
+        }
+        assert_eq!(index.search("gitlab.com"), None);
+        assert_eq!(index.search(""), None);
+    }
+
+    #[test]
+    fn test_index_follows_updates_and_removals() {
+        let mut entries = vec![entry("github", "me"), entry("gitlab", "me")];
+        let mut index = SearchIndex::build(&entries);
+
+        entries[0].service = "codeberg".to_string();
+        index.update(&entries[0]);
+        assert_eq!(index.search("github"), Some(HashSet::new()));
+        assert_eq!(index.search("codeberg"), Some(HashSet::from([entries[0].id])));
+
+        index.remove(entries[1].id);
+        assert_eq!(index.search("me"), Some(HashSet::from([entries[0].id])));
+        assert!(!index.tokens.contains_key("gitlab"));
+        assert!(!index.trigrams.contains_key("tla"));
+    }
+
+    /// `cargo test --release -- --ignored --nocapture bench_` for timings at 10k entries
+    #[test]
+    #[ignore]
+    fn bench_query_latency_10k_entries() {
+        let entries: Vec<Entry> = (0..10_000)
+            .map(|i| {
+                let mut e = entry(&format!("Service-{} example{}.com", i, i % 97), &format!("user{}@mail.test", i));
+                e.notes = Some(format!("Account number {} for the billing team", i * 7));
+                e
+            })
+            .collect();
+        let queries = ["service", "example42", "user9999", "billing", "zzz"];
+
+        let started = std::time::Instant::now();
+        let index = SearchIndex::build(&entries);
+        let build = started.elapsed();
+
+        let started = std::time::Instant::now();
+        for query in queries {
+            std::hint::black_box(scan(&entries, query));
+        }
+        let scanned = started.elapsed() / queries.len() as u32;
+
+        let started = std::time::Instant::now();
+        for query in queries {
+            std::hint::black_box(index.search(query));
+        }
+        let indexed = started.elapsed() / queries.len() as u32;
+
+        println!("build {:?}, per query: scan {:?}, index {:?} ({} tokens)", build, scanned, indexed, index.tokens.len());
+    }
+}
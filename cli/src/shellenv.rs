@@ -0,0 +1,71 @@
+//! Shell syntax for `passmann env`, which exports a password as an
+//! environment variable for scripts to source.
+
+use clap::ValueEnum;
+use passmann_shared::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ShellFormat {
+    /// POSIX shells: bash, zsh, sh
+    Sh,
+    Fish,
+    Powershell,
+}
+
+/// Variable name derived from a service, e.g. `work/github.com` becomes
+/// `WORK_GITHUB_COM_PASSWORD`
+pub fn default_var_name(service: &str) -> String {
+    let mut name: String = service.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect::<String>()
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_");
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    name.push_str("_PASSWORD");
+    name
+}
+
+/// Reject names a shell wouldn't accept as a variable
+pub fn validate_var_name(name: &str) -> Result<()> {
+    let valid = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(format!("'{}' is not a valid environment variable name", name).into());
+    }
+    Ok(())
+}
+
+/// The statement that sets `name` to `value` in the given shell, quoted so
+/// the value is taken literally
+pub fn export_line(format: ShellFormat, name: &str, value: &str) -> String {
+    match format {
+        ShellFormat::Sh => format!("export {}='{}'", name, value.replace('\'', r"'\''")),
+        ShellFormat::Fish => format!("set -gx {} '{}'", name, value.replace('\\', r"\\").replace('\'', r"\'")),
+        ShellFormat::Powershell => format!("$env:{} = '{}'", name, value.replace('\'', "''")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_var_name() {
+        assert_eq!(default_var_name("work/github.com"), "WORK_GITHUB_COM_PASSWORD");
+        assert_eq!(default_var_name("1password"), "_1PASSWORD_PASSWORD");
+        assert!(validate_var_name(&default_var_name("--")).is_ok());
+        assert!(validate_var_name("MY-VAR").is_err());
+    }
+
+    #[test]
+    fn test_export_line_quotes_each_shell() {
+        let value = r"it's $HOME\n";
+        assert_eq!(export_line(ShellFormat::Sh, "PW", value), r"export PW='it'\''s $HOME\n'");
+        assert_eq!(export_line(ShellFormat::Fish, "PW", value), r"set -gx PW 'it\'s $HOME\\n'");
+        assert_eq!(export_line(ShellFormat::Powershell, "PW", value), r"$env:PW = 'it''s $HOME\n'");
+    }
+}
@@ -0,0 +1,55 @@
+//! Overwrite-then-delete for plaintext exports. This is best-effort: SSDs
+//! (wear levelling), copy-on-write filesystems (btrfs, APFS, ZFS), snapshots
+//! and backups can all keep the old blocks, so full-disk encryption is the
+//! only real protection there.
+
+use passmann_shared::Result;
+use std::io::{Seek, SeekFrom, Write};
+use std::path::Path;
+
+pub const DEFAULT_PASSES: usize = 1;
+const CHUNK: usize = 64 * 1024;
+
+/// Overwrite `path` with random data `passes` times, syncing after each pass,
+/// then delete it. Returns the file's size.
+pub fn shred_file(path: &Path, passes: usize) -> Result<u64> {
+    let metadata = std::fs::symlink_metadata(path)
+        .map_err(|e| format!("Could not read '{}': {}", path.display(), e))?;
+    if !metadata.is_file() {
+        return Err(format!("'{}' is not a regular file", path.display()).into());
+    }
+    let size = metadata.len();
+
+    let mut file = std::fs::OpenOptions::new().write(true).open(path)?;
+    let mut buffer = vec![0u8; CHUNK];
+    for _ in 0..passes.max(1) {
+        file.seek(SeekFrom::Start(0))?;
+        let mut remaining = size;
+        while remaining > 0 {
+            let len = remaining.min(CHUNK as u64) as usize;
+            getrandom::getrandom(&mut buffer[..len]).map_err(|e| format!("OS RNG failed: {}", e))?;
+            file.write_all(&buffer[..len])?;
+            remaining -= len as u64;
+        }
+        file.sync_all()?;
+    }
+    drop(file);
+    std::fs::remove_file(path)?;
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shred_file_removes_the_file() {
+        let path = std::env::temp_dir().join(format!("passmann-shred-{}.json", std::process::id()));
+        std::fs::write(&path, vec![b'x'; CHUNK + 100]).unwrap();
+
+        assert_eq!(shred_file(&path, 2).unwrap(), (CHUNK + 100) as u64);
+        assert!(!path.exists());
+        assert!(shred_file(&path, 1).is_err());
+        assert!(shred_file(&std::env::temp_dir(), 1).is_err());
+    }
+}
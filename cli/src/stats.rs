@@ -0,0 +1,170 @@
+use crate::blocklist::capped_score;
+use chrono::{DateTime, Duration, Utc};
+use passmann_shared::{assess_password_strength, Entry};
+use rayon::prelude::*;
+
+/// Passwords older than this many days count as old unless `--age-threshold` says otherwise
+pub const DEFAULT_AGE_THRESHOLD_DAYS: i64 = 365;
+
+/// How long passwords have gone unchanged across the vault
+#[derive(Debug, Clone)]
+pub struct PasswordAgeReport {
+    pub threshold_days: i64,
+    /// Entries whose password is older than the threshold
+    pub old_count: usize,
+    /// `(service, username, age in days)`, oldest first
+    pub oldest: Vec<(String, String, i64)>,
+}
+
+pub fn password_age_report(entries: &[Entry], threshold_days: i64, top: usize) -> PasswordAgeReport {
+    let mut ages: Vec<(&Entry, i64)> = entries.iter()
+        .map(|entry| (entry, entry.get_password_age_days() as i64))
+        .collect();
+    ages.sort_by_key(|(_, days)| std::cmp::Reverse(*days));
+
+    PasswordAgeReport {
+        threshold_days,
+        old_count: ages.iter().filter(|(_, days)| *days > threshold_days).count(),
+        oldest: ages.into_iter()
+            .take(top)
+            .map(|(entry, days)| (entry.service.clone(), entry.username.clone(), days))
+            .collect(),
+    }
+}
+
+/// Cached strength scores older than this are recomputed when read
+pub const STRENGTH_RECHECK_DAYS: i64 = 30;
+
+/// The strength score stored on the entry, if it was assessed recently.
+/// `Entry::update_password` re-assesses, so a changed password is never stale.
+pub fn cached_score(entry: &Entry, now: DateTime<Utc>) -> Option<u8> {
+    let checked = entry.password_strength.last_checked;
+    (now - checked <= Duration::days(STRENGTH_RECHECK_DAYS)).then_some(entry.password_strength.score)
+}
+
+/// The entry's strength score: the cached one when current, otherwise a
+/// fresh assessment by the same scorer. Either way blocklisted passwords are
+/// capped.
+pub fn strength_score(entry: &Entry, now: DateTime<Utc>) -> u8 {
+    let score = cached_score(entry, now).unwrap_or_else(|| assess_password_strength(&entry.password).score);
+    capped_score(&entry.password, score)
+}
+
+/// Strength score of every entry's password, in entry order. Scores that
+/// need recomputing are independent per entry, so they are spread across
+/// threads; `collect` keeps the order, so totals never depend on scheduling.
+pub fn strength_scores(entries: &[Entry]) -> Vec<u8> {
+    let now = Utc::now();
+    entries.par_iter().map(|entry| strength_score(entry, now)).collect()
+}
+
+/// Re-assess the stored strength, e.g. after the scoring algorithm changed
+pub fn refresh_strength(entry: &mut Entry) {
+    entry.password_strength = assess_password_strength(&entry.password);
+}
+
+/// Re-assess every stored strength too old for `cached_score`, so the next
+/// read finds it cached. Returns how many were refreshed.
+pub fn refresh_stale_strength(entries: &mut [Entry], now: DateTime<Utc>) -> usize {
+    entries.par_iter_mut()
+        .filter(|entry| cached_score(entry, now).is_none())
+        .map(refresh_strength)
+        .count()
+}
+
+/// Note that an entry's password was revealed or copied. Only the time is kept.
+pub fn record_access(entry: &mut Entry, at: DateTime<Utc>) {
+    entry.last_accessed = Some(at);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn entry_aged(service: &str, days: i64) -> Entry {
+        let mut entry = Entry::new(service.to_string(), "user".to_string(), "pw".to_string());
+        entry.modified_at = Utc::now() - Duration::days(days);
+        entry
+    }
+
+    #[test]
+    fn test_password_age_report_counts_backdated_entries() {
+        let entries = vec![entry_aged("new", 3), entry_aged("ancient", 900), entry_aged("old", 400)];
+        let report = password_age_report(&entries, DEFAULT_AGE_THRESHOLD_DAYS, 2);
+
+        assert_eq!(report.old_count, 2);
+        let services: Vec<&str> = report.oldest.iter().map(|(service, _, _)| service.as_str()).collect();
+        assert_eq!(services, vec!["ancient", "old"]);
+        assert!(report.oldest[0].2 >= 900);
+
+        assert_eq!(password_age_report(&entries, 1000, 5).old_count, 0);
+    }
+
+    #[test]
+    fn test_strength_scores_keep_entry_order() {
+        let entries: Vec<Entry> = (0..200)
+            .map(|i| Entry::new(format!("site{}", i), "user".to_string(), "x".repeat(1 + i % 30)))
+            .collect();
+        let serial: Vec<u8> = entries.iter().map(|e| capped_score(&e.password, e.password_strength.score)).collect();
+        assert_eq!(strength_scores(&entries), serial);
+    }
+
+    #[test]
+    fn test_cached_score_used_until_stale() {
+        let now = Utc::now();
+        let mut entry = Entry::new("site".to_string(), "user".to_string(), "Tr0ub4dor&3-horse".to_string());
+        entry.password_strength.score = 42;
+        entry.password_strength.last_checked = now - Duration::days(1);
+        assert_eq!(cached_score(&entry, now), Some(42));
+        assert_eq!(strength_score(&entry, now), 42);
+
+        entry.password_strength.last_checked = now - Duration::days(STRENGTH_RECHECK_DAYS + 1);
+        assert_eq!(cached_score(&entry, now), None);
+        assert_eq!(strength_score(&entry, now), assess_password_strength(&entry.password).score);
+
+        // The blocklist applies to cached scores too
+        let mut common = Entry::new("site".to_string(), "user".to_string(), "password".to_string());
+        common.password_strength.score = 90;
+        assert!(strength_score(&common, now) <= 10);
+        common.password_strength.last_checked = now - Duration::days(STRENGTH_RECHECK_DAYS + 1);
+        assert!(strength_score(&common, now) <= 10);
+    }
+
+    #[test]
+    fn test_refresh_stale_strength_writes_back_only_stale_scores() {
+        let now = Utc::now();
+        let mut entries: Vec<Entry> = ["fresh", "stale"].iter()
+            .map(|service| Entry::new(service.to_string(), "user".to_string(), "Tr0ub4dor&3-horse".to_string()))
+            .collect();
+        for entry in &mut entries {
+            entry.password_strength.score = 1;
+        }
+        entries[1].password_strength.last_checked = now - Duration::days(STRENGTH_RECHECK_DAYS + 1);
+
+        assert_eq!(refresh_stale_strength(&mut entries, now), 1);
+        assert_eq!(entries[0].password_strength.score, 1);
+        assert_eq!(entries[1].password_strength.score, assess_password_strength("Tr0ub4dor&3-horse").score);
+        assert_eq!(cached_score(&entries[1], now), Some(entries[1].password_strength.score));
+    }
+
+    /// `cargo test --release -- --ignored --nocapture bench_` for timings at 5k entries
+    #[test]
+    #[ignore]
+    fn bench_strength_scores_5k_entries() {
+        let entries: Vec<Entry> = (0..5_000)
+            .map(|i| Entry::new(format!("site{}", i), "user".to_string(), format!("Pa55-word-{}-{}", i, "x".repeat(i % 40))))
+            .collect();
+
+        let started = std::time::Instant::now();
+        let serial: Vec<u8> = entries.iter().map(|e| capped_score(&e.password, assess_password_strength(&e.password).score)).collect();
+        let serial_time = started.elapsed();
+
+        let started = std::time::Instant::now();
+        let parallel = strength_scores(&entries);
+        let parallel_time = started.elapsed();
+
+        assert_eq!(serial, parallel);
+        println!("serial {:?}, parallel {:?} on {} threads", serial_time, parallel_time, rayon::current_num_threads());
+    }
+}
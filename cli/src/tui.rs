@@ -0,0 +1,386 @@
+//! Interactive terminal session (`passmann tui`), built with the `tui` feature.
+//! The vault is unlocked once and every change is saved as it is made.
+
+use crate::{copy_to_clipboard, merge, stats};
+use crate::vault_store::VaultStore;
+use crate::search_index::{searchable_fields, SearchIndex};
+use chrono::Utc;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use passmann_shared::{Entry, Result, Vault};
+use ratatui::{
+    DefaultTerminal, Frame,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// How often the auto-lock is checked while waiting for input
+const TICK: Duration = Duration::from_millis(250);
+
+const FORM_LABELS: [&str; 3] = ["Service", "Username", "Password"];
+
+enum Mode {
+    Browse,
+    Search,
+    View,
+    Form(Form),
+    ConfirmDelete,
+}
+
+/// Add/edit form. `editing` is `None` when adding a new entry.
+struct Form {
+    editing: Option<Uuid>,
+    fields: [String; 3],
+    focus: usize,
+}
+
+struct App<'a> {
+    vault: &'a mut Vault,
+    store: &'a mut VaultStore,
+    master_password: &'a str,
+    mode: Mode,
+    query: String,
+    /// Kept in step with every add, edit and delete so typing a query stays fast
+    index: SearchIndex,
+    visible: Vec<Uuid>,
+    list: ListState,
+    status: String,
+    quit: bool,
+}
+
+/// Run the interactive session until the user quits or the vault auto-locks
+pub fn run(vault: &mut Vault, store: &mut VaultStore, master_password: &str) -> Result<()> {
+    let index = SearchIndex::build(vault.get_entries().map(|entries| entries.as_slice()).unwrap_or_default());
+    let mut terminal = ratatui::init();
+    let mut app = App {
+        vault,
+        store,
+        master_password,
+        mode: Mode::Browse,
+        query: String::new(),
+        index,
+        visible: Vec::new(),
+        list: ListState::default(),
+        status: "↑/↓ move  / search  enter view  c copy  a add  e edit  d delete  q quit".to_string(),
+        quit: false,
+    };
+    app.refresh();
+
+    let result = app.event_loop(&mut terminal);
+    if matches!(result, Ok(true)) {
+        // Don't leave entries on screen once locked
+        terminal.clear()?;
+    }
+    ratatui::restore();
+
+    match result? {
+        true => Err("Vault is locked due to inactivity. Please restart the application.".into()),
+        false => Ok(()),
+    }
+}
+
+impl App<'_> {
+    /// Returns true when the session ended because the vault locked
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> Result<bool> {
+        while !self.quit {
+            if self.vault.check_and_handle_lock() {
+                return Ok(true);
+            }
+
+            terminal.draw(|frame| self.draw(frame))?;
+
+            if event::poll(TICK)?
+                && let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+            {
+                self.handle_key(key);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Rebuild the visible list from the vault and the search query, which
+    /// matches the same fields as `passmann find`
+    fn refresh(&mut self) {
+        let query = self.query.to_lowercase();
+        let hits = self.index.search(&query);
+        let mut entries: Vec<&Entry> = self.vault.get_entries()
+            .map(|entries| entries.iter()
+                .filter(|e| match &hits {
+                    Some(hits) => hits.contains(&e.id),
+                    None => query.is_empty() || searchable_fields(e).any(|field| field.to_lowercase().contains(&query)),
+                })
+                .collect())
+            .unwrap_or_default();
+        entries.sort_by_key(|e| e.service.to_lowercase());
+        self.visible = entries.iter().map(|e| e.id).collect();
+
+        let selected = match self.list.selected() {
+            _ if self.visible.is_empty() => None,
+            Some(i) => Some(i.min(self.visible.len() - 1)),
+            None => Some(0),
+        };
+        self.list.select(selected);
+    }
+
+    fn selected(&self) -> Option<&Entry> {
+        let id = self.visible.get(self.list.selected()?)?;
+        self.vault.get_entries()?.iter().find(|e| e.id == *id)
+    }
+
+    fn save(&mut self, message: String) {
+        self.status = match self.store.save(self.vault, self.master_password) {
+            Ok(_) => message,
+            Err(e) => format!("❌ Could not save vault: {}", e),
+        };
+        self.refresh();
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        match std::mem::replace(&mut self.mode, Mode::Browse) {
+            Mode::Browse => self.browse_key(key),
+            Mode::Search => self.search_key(key),
+            Mode::View => {
+                if key.code == KeyCode::Char('c') {
+                    self.copy_selected();
+                }
+            }
+            Mode::Form(form) => self.form_key(form, key),
+            Mode::ConfirmDelete => {
+                if key.code == KeyCode::Char('y') {
+                    self.delete_selected();
+                }
+            }
+        }
+    }
+
+    fn browse_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Down | KeyCode::Char('j') => self.list.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => self.list.select_previous(),
+            KeyCode::Char('/') => self.mode = Mode::Search,
+            KeyCode::Enter if self.selected().is_some() => self.mode = Mode::View,
+            KeyCode::Char('c') => self.copy_selected(),
+            KeyCode::Char('a') => self.mode = Mode::Form(Form {
+                editing: None,
+                fields: Default::default(),
+                focus: 0,
+            }),
+            KeyCode::Char('e') => {
+                if let Some(entry) = self.selected() {
+                    self.mode = Mode::Form(Form {
+                        editing: Some(entry.id),
+                        fields: [entry.service.clone(), entry.username.clone(), entry.password.clone()],
+                        focus: 0,
+                    });
+                }
+            }
+            KeyCode::Char('d') if self.selected().is_some() => self.mode = Mode::ConfirmDelete,
+            _ => {}
+        }
+    }
+
+    fn search_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => {}
+            KeyCode::Esc => {
+                self.query.clear();
+                self.refresh();
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.refresh();
+                self.mode = Mode::Search;
+            }
+            KeyCode::Char(c) => {
+                self.query.push(c);
+                self.refresh();
+                self.mode = Mode::Search;
+            }
+            _ => self.mode = Mode::Search,
+        }
+    }
+
+    fn form_key(&mut self, mut form: Form, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => return,
+            KeyCode::Tab | KeyCode::Down => form.focus = (form.focus + 1) % FORM_LABELS.len(),
+            KeyCode::BackTab | KeyCode::Up => form.focus = (form.focus + FORM_LABELS.len() - 1) % FORM_LABELS.len(),
+            KeyCode::Backspace => {
+                form.fields[form.focus].pop();
+            }
+            KeyCode::Char(c) => form.fields[form.focus].push(c),
+            KeyCode::Enter => {
+                if form.fields.iter().any(|field| field.is_empty()) {
+                    self.status = "⚠️  All fields are required".to_string();
+                } else {
+                    self.submit(form);
+                    return;
+                }
+            }
+            _ => {}
+        }
+        self.mode = Mode::Form(form);
+    }
+
+    fn submit(&mut self, form: Form) {
+        let [service, username, password] = form.fields;
+        match form.editing {
+            None => {
+                self.vault.add_entry(service.clone(), username, password);
+                if let Some(entry) = self.vault.get_entries().and_then(|entries| entries.last()) {
+                    self.index.insert(entry);
+                }
+                self.save(format!("✅ Entry added for '{}'", service));
+            }
+            Some(id) => {
+                let updated = self.vault.get_entries_mut()
+                    .and_then(|entries| entries.iter_mut().find(|e| e.id == id))
+                    .map(|entry| {
+                        entry.service = service.clone();
+                        entry.username = username;
+                        if entry.password != password {
+                            entry.update_password(password);
+                        }
+                        entry.modified_at = Utc::now();
+                        self.index.update(entry);
+                    })
+                    .is_some();
+                if updated {
+                    self.save(format!("✅ Updated '{}'", service));
+                }
+            }
+        }
+    }
+
+    fn copy_selected(&mut self) {
+        let Some(entry) = self.selected() else { return };
+        let service = entry.service.clone();
+        if entry.settings.require_master_password {
+            self.status = format!("🔒 '{}' is protected: use `passmann reveal` to copy it", service);
+            return;
+        }
+        let id = entry.id;
+        self.status = match copy_to_clipboard(&entry.password) {
+            Ok(()) => format!("📋 Copied password for '{}'", service),
+            Err(e) => format!("❌ Could not copy: {}", e),
+        };
+        if let Some(entry) = self.vault.get_entries_mut().and_then(|entries| entries.iter_mut().find(|e| e.id == id)) {
+            stats::record_access(entry, Utc::now());
+        }
+    }
+
+    fn delete_selected(&mut self) {
+        let Some((id, service)) = self.selected().map(|e| (e.id, e.service.clone())) else { return };
+        if let Some(entries) = self.vault.get_entries_mut() {
+            entries.retain(|e| e.id != id);
+        }
+        self.index.remove(id);
+        if let Err(e) = merge::record_deletions([id]) {
+            self.status = format!("⚠️  Could not record deletion for sync: {}", e);
+        }
+        self.save(format!("🗑️  Deleted '{}'", service));
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [search_area, list_area, status_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ]).areas(frame.area());
+
+        let searching = matches!(self.mode, Mode::Search);
+        let search = Paragraph::new(self.query.as_str())
+            .block(Block::default().borders(Borders::ALL).title(if searching { "Search (enter to keep, esc to clear)" } else { "Search (/)" }));
+        frame.render_widget(search, search_area);
+
+        let items: Vec<ListItem> = self.visible.iter()
+            .filter_map(|id| self.vault.get_entries()?.iter().find(|e| e.id == *id))
+            .map(|e| ListItem::new(format!("{}  —  {}", e.service, e.username)))
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(format!("🛡️ PassMann ({} entries)", self.visible.len())))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, list_area, &mut self.list);
+
+        let lock = self.vault.get_lock_status()
+            .map(|left| format!("🔓 auto-lock {}m {:02}s  ", left.as_secs() / 60, left.as_secs() % 60))
+            .unwrap_or_default();
+        frame.render_widget(Paragraph::new(format!("{}{}", lock, self.status)), status_area);
+
+        match &self.mode {
+            Mode::View => {
+                if let Some(entry) = self.selected() {
+                    let mut lines = vec![
+                        Line::from(format!("Service:  {}", entry.service)),
+                        Line::from(format!("Username: {}", entry.username)),
+                        Line::from(if entry.settings.require_master_password {
+                            "Password: 🔒 protected".to_string()
+                        } else {
+                            format!("Password: {}", entry.password)
+                        }),
+                    ];
+                    if let Some(url) = &entry.url {
+                        lines.push(Line::from(format!("URL:      {}", url)));
+                    }
+                    if let Some(notes) = &entry.notes {
+                        lines.push(Line::from(format!("Notes:    {}", notes)));
+                    }
+                    lines.push(Line::from(""));
+                    lines.push(Line::from("c copy password   any other key closes"));
+                    popup(frame, "Entry", lines);
+                }
+            }
+            Mode::Form(form) => {
+                let mut lines: Vec<Line> = FORM_LABELS.iter().zip(&form.fields).enumerate()
+                    .map(|(i, (label, value))| {
+                        let shown = if i == 2 { "•".repeat(value.chars().count()) } else { value.clone() };
+                        let line = Line::from(format!("{:<9} {}", format!("{}:", label), shown));
+                        if i == form.focus {
+                            line.style(Style::default().fg(Color::Yellow))
+                        } else {
+                            line
+                        }
+                    })
+                    .collect();
+                lines.push(Line::from(""));
+                lines.push(Line::from("tab next field   enter save   esc cancel"));
+                popup(frame, if form.editing.is_some() { "Edit entry" } else { "Add entry" }, lines);
+            }
+            Mode::ConfirmDelete => {
+                if let Some(entry) = self.selected() {
+                    popup(frame, "Delete", vec![
+                        Line::from(format!("Delete '{}' ({})?", entry.service, entry.username)),
+                        Line::from(""),
+                        Line::from("y delete   any other key cancels"),
+                    ]);
+                }
+            }
+            Mode::Browse | Mode::Search => {}
+        }
+    }
+}
+
+fn popup(frame: &mut Frame, title: &str, lines: Vec<Line>) {
+    let area = centered(frame.area(), 60, lines.len() as u16 + 2);
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title.to_string())),
+        area,
+    );
+}
+
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
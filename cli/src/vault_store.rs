@@ -0,0 +1,107 @@
+//! Every save of the vault goes through `VaultStore`, so commands, restores
+//! and the TUI all rotate a backup before overwriting changed contents, add
+//! a stats history snapshot and queue their changes for the next upload. A
+//! save that writes back what is already on disk only records the new file's
+//! checksum for `verify`.
+
+use crate::{backup, history, pending, pending_queue_path, record_audit, stats, stats_history_path, vault_checksums_path, verify};
+use passmann_shared::{Entry, Result, Vault};
+
+/// Hash of everything a user can change: the entries
+type Fingerprint = blake3::Hash;
+
+fn fingerprint(entries: &[Entry]) -> Result<Fingerprint> {
+    Ok(blake3::hash(&serde_json::to_vec(entries)?))
+}
+
+fn fingerprint_of(vault: &Vault) -> Result<Fingerprint> {
+    fingerprint(vault.get_entries().map(|entries| entries.as_slice()).unwrap_or_default())
+}
+
+pub struct VaultStore {
+    /// What the vault file currently holds
+    on_disk: Fingerprint,
+    /// Contents the history and upload queue have already accounted for
+    recorded: Fingerprint,
+    /// Entry versions the upload queue has already accounted for
+    versions: pending::EntryVersions,
+    /// Sync and download bring the vault up to date with the cloud, so what
+    /// they change doesn't need uploading again
+    queue_changes: bool,
+}
+
+impl VaultStore {
+    pub fn new(vault: &Vault, queue_changes: bool) -> Result<Self> {
+        let loaded = fingerprint_of(vault)?;
+        Ok(VaultStore {
+            on_disk: loaded,
+            recorded: loaded,
+            versions: pending::versions(vault.get_entries().map(|entries| entries.as_slice()).unwrap_or_default()),
+            queue_changes,
+        })
+    }
+
+    /// Write the vault. Returns whether its contents changed.
+    pub fn save(&mut self, vault: &mut Vault, master_password: &str) -> Result<bool> {
+        // Scores recomputed because they went stale are kept for next time
+        if let Some(entries) = vault.get_entries_mut() {
+            stats::refresh_stale_strength(entries, chrono::Utc::now());
+        }
+        let current = fingerprint_of(vault)?;
+        if current != self.on_disk {
+            let vault_path = Vault::vault_path();
+            match backup::rotate(&vault_path, backup::backup_count()) {
+                Ok(true) => record_audit(vault, "backup_created", &backup::backup_path(&vault_path, 1).display().to_string()),
+                Ok(false) => {}
+                Err(err) => eprintln!("⚠️  Warning: Could not back up vault: {}", err),
+            }
+        }
+
+        vault.save(master_password)?;
+        self.on_disk = current;
+        // Every save re-encrypts, so the file's checksum changes even when its contents don't
+        if let Err(err) = verify::record_checksum(&vault_checksums_path(), &Vault::vault_path(), backup::backup_count() + 1) {
+            eprintln!("⚠️  Warning: Could not record vault checksum: {}", err);
+        }
+        if current == self.recorded {
+            return Ok(false);
+        }
+
+        let entries = vault.get_entries().map(|entries| entries.as_slice()).unwrap_or_default();
+        let snapshot = history::Snapshot::of(entries, chrono::Utc::now());
+        if let Err(err) = history::record(&stats_history_path(), master_password, snapshot) {
+            eprintln!("⚠️  Warning: Could not update stats history: {}", err);
+        }
+        if self.queue_changes {
+            let changes = pending::changes(&self.versions, entries, chrono::Utc::now());
+            if let Err(err) = pending::record(&pending_queue_path(), master_password, changes) {
+                eprintln!("⚠️  Warning: Could not queue changes for upload: {}", err);
+            }
+        }
+        self.versions = pending::versions(entries);
+        self.recorded = current;
+        Ok(true)
+    }
+
+    /// The vault file was replaced outside `save` (a restored backup), so the
+    /// next save needs no backup of it, though its changes are still queued
+    pub fn replaced_on_disk(&mut self, vault: &Vault) -> Result<()> {
+        self.on_disk = fingerprint_of(vault)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint_follows_entries() {
+        let mut entries = vec![Entry::new("github".to_string(), "me".to_string(), "pw".to_string())];
+        let saved = fingerprint(&entries).unwrap();
+        assert_eq!(fingerprint(&entries).unwrap(), saved);
+
+        entries[0].notes = Some("recovery codes in the safe".to_string());
+        assert_ne!(fingerprint(&entries).unwrap(), saved);
+    }
+}
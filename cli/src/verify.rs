@@ -0,0 +1,245 @@
+use passmann_shared::{Entry, PassMannError, Result};
+use std::collections::HashSet;
+use std::error::Error;
+use std::path::Path;
+
+/// Outcome of a single integrity check
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub passed: bool,
+    pub detail: String,
+}
+
+/// Result of verifying a vault without opening a session on it
+#[derive(Debug, Default)]
+pub struct VerifyReport {
+    pub checks: Vec<CheckResult>,
+}
+
+impl VerifyReport {
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|check| check.passed)
+    }
+
+    fn check(&mut self, name: &'static str, passed: bool, detail: String) {
+        self.checks.push(CheckResult { name, passed, detail });
+    }
+}
+
+fn file_checksum(path: &Path) -> Result<String> {
+    Ok(blake3::hash(&std::fs::read(path)?).to_hex().to_string())
+}
+
+fn load_checksums(list_path: &Path) -> Result<Vec<String>> {
+    match std::fs::read(list_path) {
+        Ok(data) => Ok(serde_json::from_slice(&data)?),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Remember the checksum of the vault file just written, keeping the latest
+/// `keep` so backups rotated out of the vault can still be checked. This
+/// catches corruption on disk; it is not a defence against tampering.
+pub fn record_checksum(list_path: &Path, file_path: &Path, keep: usize) -> Result<()> {
+    let mut checksums = load_checksums(list_path)?;
+    let checksum = file_checksum(file_path)?;
+    checksums.retain(|known| *known != checksum);
+    checksums.push(checksum);
+    let overflow = checksums.len().saturating_sub(keep);
+    checksums.drain(..overflow);
+    std::fs::write(list_path, serde_json::to_vec(&checksums)?)?;
+    Ok(())
+}
+
+/// Verify the vault file at `path` as stored: its bytes must
+/// match a checksum recorded when it was written, and `open` must decrypt it
+/// to vault JSON that passes `verify_vault_json`.
+pub fn verify_file(path: &Path, list_path: &Path, open: impl FnOnce(&Path) -> Result<String>) -> VerifyReport {
+    let mut report = VerifyReport::default();
+    match (file_checksum(path), load_checksums(list_path)) {
+        (Err(e), _) => {
+            report.check("File", false, format!("Could not read {}: {}", path.display(), e));
+            return report;
+        }
+        (_, Err(e)) => report.check("Checksum", false, format!("Could not read recorded checksums: {}", e)),
+        (Ok(_), Ok(known)) if known.is_empty() => {
+            report.check("Checksum", true, "No checksum recorded yet; one is kept from the next save".to_string());
+        }
+        (Ok(checksum), Ok(known)) => report.check(
+            "Checksum",
+            known.contains(&checksum),
+            if known.contains(&checksum) {
+                "File matches the checksum recorded when it was written".to_string()
+            } else {
+                "File does not match any recorded checksum; it changed after it was written".to_string()
+            },
+        ),
+    }
+
+    match open(path) {
+        Ok(json) => report.checks.extend(verify_vault_json(&json).checks),
+        Err(e) => report.check("Decryption", false, e.to_string()),
+    }
+    report
+}
+
+/// What the user can do about a vault that failed to load, when the error
+/// says why it failed
+pub fn load_failure_guidance(error: &(dyn Error + 'static)) -> Option<&'static str> {
+    match error.downcast_ref::<PassMannError>()? {
+        PassMannError::WrongPassword => Some("The master password is incorrect. Check it and try again."),
+        PassMannError::CorruptVault(_) => Some("The vault file is damaged. Restore a backup with `passmann restore --from 1`."),
+        PassMannError::UnsupportedVersion(_) => Some("The vault was written by a newer PassMann. Upgrade PassMann to open it."),
+        _ => None,
+    }
+}
+
+/// Check the decrypted vault JSON: every entry must deserialize, ids must be
+/// unique, and timestamps must be consistent.
+pub fn verify_vault_json(json: &str) -> VerifyReport {
+    let mut report = VerifyReport::default();
+    report.check("Decryption", true, "Vault decrypted with this password".to_string());
+
+    let raw_entries = match serde_json::from_str::<serde_json::Value>(json) {
+        Ok(serde_json::Value::Array(items)) => items,
+        Ok(serde_json::Value::Object(mut map)) => match map.remove("entries") {
+            Some(serde_json::Value::Array(items)) => items,
+            _ => Vec::new(),
+        },
+        Ok(_) => Vec::new(),
+        Err(e) => {
+            report.check("Vault format", false, format!("Invalid vault JSON: {}", e));
+            return report;
+        }
+    };
+
+    let mut entries = Vec::with_capacity(raw_entries.len());
+    let mut unreadable = Vec::new();
+    for (index, raw) in raw_entries.into_iter().enumerate() {
+        match serde_json::from_value::<Entry>(raw) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => unreadable.push(format!("#{}: {}", index + 1, e)),
+        }
+    }
+    report.check(
+        "Entries deserialize",
+        unreadable.is_empty(),
+        if unreadable.is_empty() {
+            format!("{} entries read", entries.len())
+        } else {
+            format!("{} unreadable: {}", unreadable.len(), unreadable.join("; "))
+        },
+    );
+
+    let mut seen = HashSet::new();
+    let duplicates: Vec<String> = entries.iter()
+        .filter(|entry| !seen.insert(entry.id))
+        .map(|entry| entry.id.to_string())
+        .collect();
+    report.check(
+        "Unique ids",
+        duplicates.is_empty(),
+        if duplicates.is_empty() {
+            "No duplicate ids".to_string()
+        } else {
+            format!("Duplicate ids: {}", duplicates.join(", "))
+        },
+    );
+
+    let bad_timestamps: Vec<&str> = entries.iter()
+        .filter(|entry| entry.modified_at < entry.created_at)
+        .map(|entry| entry.service.as_str())
+        .collect();
+    report.check(
+        "Timestamps",
+        bad_timestamps.is_empty(),
+        if bad_timestamps.is_empty() {
+            "Modification times follow creation times".to_string()
+        } else {
+            format!("Modified before created: {}", bad_timestamps.join(", "))
+        },
+    );
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry_json(service: &str) -> serde_json::Value {
+        serde_json::to_value(Entry::new(service.to_string(), "user".to_string(), "pw".to_string())).unwrap()
+    }
+
+    #[test]
+    fn test_load_failure_guidance_per_error_kind() {
+        let wrong: Box<dyn Error> = Box::new(PassMannError::WrongPassword);
+        assert!(load_failure_guidance(wrong.as_ref()).unwrap().contains("password"));
+
+        let corrupt: Box<dyn Error> = Box::new(PassMannError::CorruptVault("truncated".to_string()));
+        assert!(load_failure_guidance(corrupt.as_ref()).unwrap().contains("restore"));
+
+        let other: Box<dyn Error> = "disk full".into();
+        assert_eq!(load_failure_guidance(other.as_ref()), None);
+    }
+
+    #[test]
+    fn test_verify_clean_vault_passes() {
+        let json = serde_json::json!({ "entries": [entry_json("a"), entry_json("b")] }).to_string();
+        let report = verify_vault_json(&json);
+        assert!(report.passed(), "{:?}", report);
+    }
+
+    #[test]
+    fn test_verify_file_fails_after_corruption() {
+        let dir = std::env::temp_dir().join(format!("passmann_verify_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (vault_path, list_path) = (dir.join("vault.enc"), dir.join("vault_checksums.json"));
+        let json = serde_json::json!({ "entries": [entry_json("a")] }).to_string();
+        std::fs::write(&vault_path, &json).unwrap();
+        record_checksum(&list_path, &vault_path, 5).unwrap();
+
+        // Stands in for decryption: the file holds the vault JSON itself
+        let open = |path: &Path| -> Result<String> { Ok(String::from_utf8(std::fs::read(path)?)?) };
+        let report = verify_file(&vault_path, &list_path, open);
+        assert!(report.passed(), "{:?}", report);
+
+        let mut data = std::fs::read(&vault_path).unwrap();
+        data[json.len() / 2] ^= 0x20;
+        std::fs::write(&vault_path, &data).unwrap();
+        let report = verify_file(&vault_path, &list_path, open);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(!report.passed());
+        assert!(!report.checks[0].passed, "{:?}", report);
+    }
+
+    #[test]
+    fn test_record_checksum_keeps_latest() {
+        let dir = std::env::temp_dir().join(format!("passmann_checksums_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (file, list_path) = (dir.join("vault.enc"), dir.join("vault_checksums.json"));
+        for version in 0..4 {
+            std::fs::write(&file, format!("version {}", version)).unwrap();
+            record_checksum(&list_path, &file, 2).unwrap();
+        }
+        let known = load_checksums(&list_path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(known.len(), 2);
+        assert_eq!(known[1], blake3::hash(b"version 3").to_hex().to_string());
+    }
+
+    #[test]
+    fn test_verify_reports_duplicates_and_bad_entries() {
+        let duplicate = entry_json("dup");
+        let json = serde_json::json!([duplicate.clone(), duplicate, { "service": "broken" }]).to_string();
+        let report = verify_vault_json(&json);
+
+        assert!(!report.passed());
+        let failed: Vec<&str> = report.checks.iter().filter(|c| !c.passed).map(|c| c.name).collect();
+        assert_eq!(failed, vec!["Entries deserialize", "Unique ids"]);
+    }
+}
@@ -0,0 +1,128 @@
+//! Password strength scoring for the CLI and the web client. Both depend on
+//! this crate rather than on `passmann-shared`, so a password gets the same
+//! score, level and feedback everywhere and the WASM build stays free of the
+//! native crypto and storage dependencies.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PasswordStrength {
+    pub score: u8,
+    pub level: String,
+    pub feedback: Vec<String>,
+}
+
+/// Built-in blocklist, used when no other list is configured
+pub const COMMON_PASSWORDS: [&str; 13] = [
+    "password", "123456", "12345678", "qwerty", "abc123", "monkey", "letmein",
+    "dragon", "111111", "baseball", "iloveyou", "trustno1", "admin",
+];
+
+/// Highest score a blocklisted password can get, whatever its length or mix
+pub const BLOCKED_MAX_SCORE: u8 = 10;
+
+/// Score `password` from its length, character classes and obvious patterns
+pub fn estimate_password_strength(password: &str) -> PasswordStrength {
+    let length = password.chars().count();
+    let has_lower = password.chars().any(|c| c.is_ascii_lowercase());
+    let has_upper = password.chars().any(|c| c.is_ascii_uppercase());
+    let has_digit = password.chars().any(|c| c.is_ascii_digit());
+    let has_symbol = password.chars().any(|c| !c.is_alphanumeric());
+
+    let mut score: i32 = match length {
+        0 => 0,
+        1..=7 => 10,
+        8..=11 => 30,
+        12..=15 => 45,
+        _ => 60,
+    };
+    let classes = [has_lower, has_upper, has_digit, has_symbol].iter().filter(|&&c| c).count() as i32;
+    score += classes * 10;
+
+    let mut feedback = Vec::new();
+    if length < 12 {
+        feedback.push("Use at least 12 characters".to_string());
+    }
+    if !has_upper || !has_lower {
+        feedback.push("Mix uppercase and lowercase letters".to_string());
+    }
+    if !has_digit {
+        feedback.push("Add numbers".to_string());
+    }
+    if !has_symbol {
+        feedback.push("Add symbols".to_string());
+    }
+
+    let lower = password.to_lowercase();
+    if COMMON_PASSWORDS.iter().any(|common| lower.contains(common)) {
+        score -= 40;
+        feedback.push("Avoid common passwords and words".to_string());
+    }
+    if has_repeated_run(password) {
+        score -= 15;
+        feedback.push("Avoid repeated characters".to_string());
+    }
+
+    let score = score.clamp(0, 100) as u8;
+    PasswordStrength { score, level: level_for(score).to_string(), feedback }
+}
+
+fn level_for(score: u8) -> &'static str {
+    match score {
+        0..=30 => "Weak",
+        31..=60 => "Fair",
+        61..=80 => "Good",
+        _ => "Strong",
+    }
+}
+
+fn has_repeated_run(password: &str) -> bool {
+    let chars: Vec<char> = password.chars().collect();
+    chars.windows(3).any(|w| w[0] == w[1] && w[1] == w[2])
+}
+
+/// Whether `password` is on the built-in blocklist (case-insensitive)
+pub fn is_builtin_common(password: &str) -> bool {
+    let lower = password.to_lowercase();
+    COMMON_PASSWORDS.contains(&lower.as_str())
+}
+
+/// Cap `strength` for a password found on a blocklist
+pub fn cap_blocked(strength: &mut PasswordStrength) {
+    strength.score = strength.score.min(BLOCKED_MAX_SCORE);
+    strength.level = "Weak".to_string();
+    strength.feedback.push("This password is on a list of common passwords".to_string());
+}
+
+/// The estimate, capped when `password` is on the built-in blocklist
+pub fn estimate_with_builtin_blocklist(password: &str) -> PasswordStrength {
+    let mut strength = estimate_password_strength(password);
+    if is_builtin_common(password) {
+        cap_blocked(&mut strength);
+    }
+    strength
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scores_rise_with_length_and_variety() {
+        let short = estimate_password_strength("abc");
+        let long = estimate_password_strength("x9$Lq!vR2#mZ8@pT");
+        assert_eq!(short.level, "Weak");
+        assert_eq!(long.level, "Strong");
+        assert!(long.feedback.is_empty());
+        assert!(estimate_password_strength("aaaaaaaaaaaaaaaa").score < estimate_password_strength("abcdefghijklmnop").score);
+    }
+
+    #[test]
+    fn test_builtin_blocklist_caps_the_score() {
+        let strength = estimate_with_builtin_blocklist("LetMeIn");
+        assert!(strength.score <= BLOCKED_MAX_SCORE);
+        assert_eq!(strength.level, "Weak");
+        assert!(strength.feedback.iter().any(|f| f.contains("common passwords")));
+        assert_eq!(estimate_with_builtin_blocklist("Tr0ub4dor&3"), estimate_password_strength("Tr0ub4dor&3"));
+    }
+}
@@ -0,0 +1,10 @@
+-- Uploads upsert on (user_id, vault_id) so that a retried or repeated
+-- upload replaces the vault's row instead of adding another one. vault_id
+-- is generated by the client once per vault. Rows written before this
+-- migration keep a null vault_id, which the unique constraint allows any
+-- number of.
+alter table public.encrypted_vaults
+    add column if not exists vault_id uuid;
+
+alter table public.encrypted_vaults
+    add constraint encrypted_vaults_user_id_vault_id_key unique (user_id, vault_id);
@@ -1,7 +1,11 @@
 use wasm_bindgen::prelude::*;
 use serde::{Serialize, Deserialize};
 use getrandom::getrandom;
-use argon2::Argon2;
+use zeroize::{Zeroize, Zeroizing};
+use base64::{Engine as _, engine::general_purpose};
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
+use sha1::{Digest, Sha1};
+use argon2::{Algorithm, Argon2, Params, Version};
 use chacha20poly1305::{
     aead::{Aead, KeyInit, generic_array::GenericArray},
     ChaCha20Poly1305, Nonce
@@ -38,11 +42,171 @@ pub struct VaultData {
     storage_mode: String,
 }
 
+/// Serializes exactly like `VaultData` but borrows the entries, so sealing
+/// the vault doesn't copy every entry first
+#[derive(Serialize)]
+struct VaultDataRef<'a> {
+    entries: &'a [Entry],
+    created_at: i64,
+    modified_at: i64,
+    storage_mode: &'a str,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PasswordStrength {
+    pub score: u8,
+    pub level: String,
+    pub feedback: Vec<String>,
+    pub entropy_bits: f64,
+    pub crack_time: String,
+}
+
+/// Argon2id parameters used to derive the vault key.
+///
+/// Every sealed vault starts with the parameters it was sealed with (see
+/// `KDF_HEADER_MAGIC`), so unlocking never depends on the current defaults.
+/// Vaults sealed before the header existed used `Argon2::default()`, kept as
+/// `KdfParams::LEGACY`. The defaults for new vaults mirror the native
+/// `crypto::derive_key` (Argon2id, v0x13, 64 MiB, 3 passes, 32-byte output);
+/// `set_kdf_params` overrides them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KdfParams {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            memory_kib: 65536, // 64MB
+            iterations: 3,
+            parallelism: 4,
+        }
+    }
+}
+
+/// Starts a vault blob whose KDF parameters follow as three little-endian
+/// u32s, after a version byte
+const KDF_HEADER_MAGIC: &[u8; 3] = b"PMK";
+/// ASCII so the header of the first version still reads `PMK1`
+const KDF_HEADER_VERSION: u8 = b'1';
+const KDF_HEADER_LEN: usize = KDF_HEADER_MAGIC.len() + 1 + 12;
+
+/// Upper bounds for parameters read from a vault blob, well above anything a
+/// browser would seal with
+const MAX_KDF_MEMORY_KIB: u32 = 1024 * 1024; // 1 GiB
+const MAX_KDF_ITERATIONS: u32 = 10;
+const MAX_KDF_PARALLELISM: u32 = 16;
+
+impl KdfParams {
+    /// What `Argon2::default()` used, for vaults sealed without a header
+    pub const LEGACY: KdfParams = KdfParams {
+        memory_kib: Params::DEFAULT_M_COST,
+        iterations: Params::DEFAULT_T_COST,
+        parallelism: Params::DEFAULT_P_COST,
+    };
+
+    fn header(&self) -> [u8; KDF_HEADER_LEN] {
+        let mut header = [0u8; KDF_HEADER_LEN];
+        header[..3].copy_from_slice(KDF_HEADER_MAGIC);
+        header[3] = KDF_HEADER_VERSION;
+        for (i, value) in [self.memory_kib, self.iterations, self.parallelism].into_iter().enumerate() {
+            header[4 + i * 4..8 + i * 4].copy_from_slice(&value.to_le_bytes());
+        }
+        header
+    }
+
+    /// Split a vault blob into the parameters it was sealed with and the
+    /// ciphertext that follows them. Blobs without a complete header are
+    /// legacy vaults, whose random nonce can't be told from a header by the
+    /// magic alone. Parameters outside the bounds are refused before any key
+    /// is derived with them.
+    fn split_header(blob: &[u8]) -> Result<(KdfParams, &[u8]), UnlockError> {
+        if blob.len() < KDF_HEADER_LEN + MIN_ENCRYPTED_LEN
+            || !blob.starts_with(KDF_HEADER_MAGIC)
+            || blob[KDF_HEADER_MAGIC.len()] != KDF_HEADER_VERSION
+        {
+            return Ok((KdfParams::LEGACY, blob));
+        }
+        let value = |i: usize| u32::from_le_bytes(blob[4 + i * 4..8 + i * 4].try_into().unwrap());
+        let params = KdfParams { memory_kib: value(0), iterations: value(1), parallelism: value(2) };
+        params.check_bounds().map_err(|_| UnlockError::CorruptData)?;
+        Ok((params, &blob[KDF_HEADER_LEN..]))
+    }
+
+    fn check_bounds(&self) -> Result<(), String> {
+        if !(1..=MAX_KDF_PARALLELISM).contains(&self.parallelism) {
+            return Err(format!("parallelism must be 1-{}", MAX_KDF_PARALLELISM));
+        }
+        if !(1..=MAX_KDF_ITERATIONS).contains(&self.iterations) {
+            return Err(format!("iterations must be 1-{}", MAX_KDF_ITERATIONS));
+        }
+        // Argon2 needs 8 KiB per lane
+        if !(8 * self.parallelism..=MAX_KDF_MEMORY_KIB).contains(&self.memory_kib) {
+            return Err(format!("memory must be {}-{} KiB", 8 * self.parallelism, MAX_KDF_MEMORY_KIB));
+        }
+        Ok(())
+    }
+}
+
+/// Why an unlock attempt failed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnlockError {
+    SaltTooShort,
+    KeyDerivationFailed,
+    /// The vault didn't authenticate with the derived key
+    WrongPassword,
+    /// The vault decrypted but isn't a readable vault, or is too short to be one
+    CorruptData,
+}
+
+impl UnlockError {
+    /// Stable code reported to JS
+    fn code(self) -> &'static str {
+        match self {
+            UnlockError::SaltTooShort => "salt_too_short",
+            UnlockError::KeyDerivationFailed => "key_derivation_failed",
+            UnlockError::WrongPassword => "wrong_password",
+            UnlockError::CorruptData => "corrupt_data",
+        }
+    }
+
+    fn message(self) -> &'static str {
+        match self {
+            UnlockError::SaltTooShort => "Salt must be at least 16 bytes",
+            UnlockError::KeyDerivationFailed => "Key derivation failed",
+            UnlockError::WrongPassword => "Incorrect master password",
+            UnlockError::CorruptData => "Vault data is corrupted",
+        }
+    }
+}
+
+/// Result of `unlock_vault_checked` as seen from JS
+#[derive(Serialize)]
+struct UnlockReport {
+    /// `unlocked`, `new_vault`, or an `UnlockError` code
+    code: &'static str,
+    message: &'static str,
+    entries: usize,
+    /// Wrong passwords since the last successful unlock, for throttling in the UI
+    failed_attempts: u32,
+}
+
+/// Smallest ciphertext that can hold a nonce and a Poly1305 tag
+const MIN_ENCRYPTED_LEN: usize = 12 + 16;
+
 #[wasm_bindgen]
 pub struct PassMannWasm {
     master_key: Option<[u8; 32]>,
+    /// Salt the key was derived with, uploaded alongside the ciphertext
+    salt: Option<Vec<u8>>,
     vault_data: Option<Vec<Entry>>,
     storage_mode: String,
+    kdf_params: KdfParams,
+    failed_unlocks: u32,
+    /// Idle seconds before `check_auto_lock` locks the vault; 0 disables it
+    auto_lock_seconds: u32,
 }
 
 #[wasm_bindgen]
@@ -52,11 +216,31 @@ impl PassMannWasm {
         console_error_panic_hook::set_once();
         PassMannWasm {
             master_key: None,
+            salt: None,
             vault_data: None,
             storage_mode: "local".to_string(),
+            kdf_params: KdfParams::default(),
+            failed_unlocks: 0,
+            auto_lock_seconds: 0,
         }
     }
 
+    #[wasm_bindgen]
+    pub fn set_kdf_params(&mut self, memory: u32, time: u32, parallelism: u32) -> bool {
+        let params = KdfParams {
+            memory_kib: memory,
+            iterations: time,
+            parallelism,
+        };
+        if let Err(e) = argon2_for(&params) {
+            console_log!("Invalid KDF parameters: {}", e);
+            return false;
+        }
+        self.kdf_params = params;
+        console_log!("KDF parameters set: {} KiB, {} iterations, {} lanes", memory, time, parallelism);
+        true
+    }
+
     #[wasm_bindgen]
     pub fn set_storage_mode(&mut self, mode: &str) {
         self.storage_mode = mode.to_string();
@@ -65,55 +249,95 @@ impl PassMannWasm {
 
     #[wasm_bindgen]
     pub fn unlock_vault(&mut self, master_password: &str, salt: &[u8], encrypted_vault: Option<Vec<u8>>) -> bool {
+        self.try_unlock(master_password, salt, encrypted_vault).is_ok()
+    }
+
+    /// Like `unlock_vault`, but resolves to `{ code, message, entries, failed_attempts }`
+    /// with code `unlocked` or `new_vault`, and throws the same shape with an
+    /// error code (`wrong_password`, `corrupt_data`, `salt_too_short`,
+    /// `key_derivation_failed`) when the vault stays locked.
+    #[wasm_bindgen]
+    pub fn unlock_vault_checked(&mut self, master_password: &str, salt: &[u8], encrypted_vault: Option<Vec<u8>>) -> Result<JsValue, JsValue> {
+        let is_new = encrypted_vault.is_none();
+        let outcome = self.try_unlock(master_password, salt, encrypted_vault);
+        let (code, message) = match outcome {
+            Ok(_) if is_new => ("new_vault", "New vault created"),
+            Ok(_) => ("unlocked", "Vault unlocked"),
+            Err(error) => (error.code(), error.message()),
+        };
+        let report = UnlockReport {
+            code,
+            message,
+            entries: *outcome.as_ref().unwrap_or(&0),
+            failed_attempts: self.failed_unlocks,
+        };
+
+        let value = serde_wasm_bindgen::to_value(&report).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        if outcome.is_ok() { Ok(value) } else { Err(value) }
+    }
+
+    /// Derive the key and open the vault, leaving the instance locked on failure.
+    /// Returns the number of entries loaded.
+    fn try_unlock(&mut self, master_password: &str, salt: &[u8], encrypted_vault: Option<Vec<u8>>) -> Result<usize, UnlockError> {
         if salt.len() < 16 {
             console_log!("Salt too short");
-            return false;
+            return Err(UnlockError::SaltTooShort);
         }
         
-        let key = match self.derive_key(master_password, salt) {
-            Ok(k) => k,
-            Err(e) => {
-                console_log!("Key derivation failed: {}", e);
-                return false;
+        // An existing vault is opened with the parameters it was sealed with
+        let (params, ciphertext) = match &encrypted_vault {
+            Some(blob) => {
+                let (params, ciphertext) = KdfParams::split_header(blob)?;
+                (params, Some(ciphertext))
             }
+            None => (self.kdf_params, None),
         };
-        self.master_key = Some(key);
+        let key = derive_key_with_params(master_password, salt, &params).map_err(|e| {
+            console_log!("Key derivation failed: {}", e);
+            UnlockError::KeyDerivationFailed
+        })?;
         
         // If we have encrypted vault data, decrypt it
-        if let Some(encrypted_data) = encrypted_vault {
-            match self.decrypt_vault_data(&encrypted_data) {
-                Some(entries) => {
-                    self.vault_data = Some(entries);
-                    console_log!("Vault unlocked with {} entries", self.vault_data.as_ref().unwrap().len());
+        let entries = match ciphertext {
+            Some(encrypted_data) => match decrypt_vault_entries(&key, encrypted_data) {
+                Ok(entries) => {
+                    console_log!("Vault unlocked with {} entries", entries.len());
+                    entries
                 }
-                None => {
-                    console_log!("Failed to decrypt vault data");
-                    return false;
+                Err(error) => {
+                    if error == UnlockError::WrongPassword {
+                        self.failed_unlocks += 1;
+                    }
+                    console_log!("Failed to decrypt vault data: {}", error.message());
+                    return Err(error);
                 }
+            },
+            None => {
+                console_log!("New vault created");
+                Vec::new()
             }
-        } else {
-            // New vault
-            self.vault_data = Some(Vec::new());
-            console_log!("New vault created");
-        }
+        };
         
-        true
+        let count = entries.len();
+        // The session being replaced must not leave its key or passwords behind
+        self.wipe_session();
+        self.kdf_params = params;
+        self.master_key = Some(*key);
+        self.salt = Some(salt.to_vec());
+        self.vault_data = Some(entries);
+        self.failed_unlocks = 0;
+        Ok(count)
     }
 
     #[wasm_bindgen]
     pub fn add_entry(&mut self, service: &str, username: &str, password: &str, url: Option<String>, notes: Option<String>) -> bool {
         if let Some(entries) = &mut self.vault_data {
-            let now = chrono::Utc::now().timestamp_millis();
-            let entry = Entry {
-                id: format!("entry_{}", now),
-                service: service.to_string(),
-                username: username.to_string(),
-                password: password.to_string(),
-                url,
-                notes,
-                created_at: now,
-                modified_at: now,
-                is_favorite: false,
+            let entry = match new_entry(service, username, password, url, notes) {
+                Ok(entry) => entry,
+                Err(e) => {
+                    console_log!("Failed to create entry: {}", e);
+                    return false;
+                }
             };
             entries.push(entry);
             console_log!("Entry added for service: {}", service);
@@ -124,6 +348,22 @@ impl PassMannWasm {
         }
     }
 
+    /// Generate a password, save it in a new entry and return it so an
+    /// extension can fill the signup form. Returns `null` when the vault is
+    /// locked or `length` is too short for the selected character classes.
+    #[wasm_bindgen]
+    pub fn add_entry_with_generated(&mut self, service: &str, username: &str, length: usize, symbols: bool, url: Option<String>, notes: Option<String>) -> Option<String> {
+        if self.vault_data.is_none() {
+            console_log!("Vault not unlocked");
+            return None;
+        }
+        let password = Self::generate_password(length, symbols);
+        if password.is_empty() || !self.add_entry(service, username, &password, url, notes) {
+            return None;
+        }
+        Some(password)
+    }
+
     #[wasm_bindgen]
     pub fn update_entry(&mut self, index: usize, service: &str, username: &str, password: &str, url: Option<String>, notes: Option<String>) -> bool {
         if let Some(entries) = &mut self.vault_data {
@@ -164,6 +404,45 @@ impl PassMannWasm {
         }
     }
 
+    #[wasm_bindgen]
+    pub fn update_entry_by_id(&mut self, id: &str, service: &str, username: &str, password: &str, url: Option<String>, notes: Option<String>) -> bool {
+        if let Some(entries) = &mut self.vault_data {
+            if let Some(entry) = entries.iter_mut().find(|entry| entry.id == id) {
+                entry.service = service.to_string();
+                entry.username = username.to_string();
+                entry.password = password.to_string();
+                entry.url = url;
+                entry.notes = notes;
+                entry.modified_at = chrono::Utc::now().timestamp_millis();
+                console_log!("Entry updated for service: {}", service);
+                true
+            } else {
+                console_log!("Entry not found: {}", id);
+                false
+            }
+        } else {
+            console_log!("Vault not unlocked");
+            false
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn delete_entry_by_id(&mut self, id: &str) -> bool {
+        if let Some(entries) = &mut self.vault_data {
+            if let Some(index) = entries.iter().position(|entry| entry.id == id) {
+                let removed = entries.remove(index);
+                console_log!("Entry deleted for service: {}", removed.service);
+                true
+            } else {
+                console_log!("Entry not found: {}", id);
+                false
+            }
+        } else {
+            console_log!("Vault not unlocked");
+            false
+        }
+    }
+
     #[wasm_bindgen]
     pub fn get_entries_json(&self) -> Option<String> {
         if let Some(entries) = &self.vault_data {
@@ -180,6 +459,83 @@ impl PassMannWasm {
         }
     }
 
+    #[wasm_bindgen]
+    pub fn export_entries_json(&self) -> Option<String> {
+        self.get_entries_json()
+    }
+
+    #[wasm_bindgen]
+    pub fn import_entries_json(&mut self, json: &str) -> usize {
+        let imported: Vec<Entry> = match serde_json::from_str(json) {
+            Ok(entries) => entries,
+            Err(e) => {
+                console_log!("Failed to parse entries: {}", e);
+                return 0;
+            }
+        };
+
+        if let Some(entries) = &mut self.vault_data {
+            let mut added = 0;
+            for entry in imported {
+                if !entries.iter().any(|existing| existing.id == entry.id) {
+                    entries.push(entry);
+                    added += 1;
+                }
+            }
+            console_log!("Imported {} new entries", added);
+            added
+        } else {
+            console_log!("Vault not unlocked");
+            0
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn toggle_favorite(&mut self, id: &str) -> bool {
+        if let Some(entries) = &mut self.vault_data {
+            if let Some(entry) = entries.iter_mut().find(|entry| entry.id == id) {
+                entry.is_favorite = !entry.is_favorite;
+                entry.modified_at = chrono::Utc::now().timestamp_millis();
+                console_log!("Favorite {} for service: {}", if entry.is_favorite { "set" } else { "cleared" }, entry.service);
+                true
+            } else {
+                console_log!("Entry not found: {}", id);
+                false
+            }
+        } else {
+            console_log!("Vault not unlocked");
+            false
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn get_entries_sorted(&self, by: &str) -> Option<String> {
+        if let Some(entries) = &self.vault_data {
+            let mut sorted: Vec<&Entry> = entries.iter().collect();
+            // sort_by is stable, so equal keys keep their insertion order
+            match by {
+                "service" => sorted.sort_by_key(|entry| entry.service.to_lowercase()),
+                "modified" => sorted.sort_by_key(|entry| std::cmp::Reverse(entry.modified_at)),
+                "favorite" => sorted.sort_by_key(|entry| (!entry.is_favorite, entry.service.to_lowercase())),
+                _ => {
+                    console_log!("Unknown sort key: {}", by);
+                    return None;
+                }
+            }
+
+            match serde_json::to_string(&sorted) {
+                Ok(json) => Some(json),
+                Err(e) => {
+                    console_log!("Failed to serialize entries: {}", e);
+                    None
+                }
+            }
+        } else {
+            console_log!("Vault not unlocked");
+            None
+        }
+    }
+
     #[wasm_bindgen]
     pub fn search_entries(&self, query: &str) -> Option<String> {
         if let Some(entries) = &self.vault_data {
@@ -206,21 +562,22 @@ impl PassMannWasm {
         }
     }
 
+    /// The vault as stored: KDF header, then nonce and ciphertext
     #[wasm_bindgen]
     pub fn encrypt_vault(&self) -> Option<Vec<u8>> {
         if let (Some(key), Some(entries)) = (&self.master_key, &self.vault_data) {
             let now = chrono::Utc::now().timestamp_millis();
-            let vault_data = VaultData {
-                entries: entries.clone(),
+            let vault_data = VaultDataRef {
+                entries,
                 created_at: now,
                 modified_at: now,
-                storage_mode: self.storage_mode.clone(),
+                storage_mode: &self.storage_mode,
             };
             
             match serde_json::to_string(&vault_data) {
                 Ok(json) => {
                     match self.encrypt_data_internal(key, json.as_bytes()) {
-                        Ok(encrypted) => Some(encrypted),
+                        Ok(encrypted) => Some([&self.kdf_params.header()[..], &encrypted].concat()),
                         Err(e) => {
                             console_log!("Encryption failed: {}", e);
                             None
@@ -238,46 +595,9 @@ impl PassMannWasm {
         }
     }
 
-    fn decrypt_vault_data(&self, encrypted_data: &[u8]) -> Option<Vec<Entry>> {
-        if let Some(key) = &self.master_key {
-            match self.decrypt_data_internal(key, encrypted_data) {
-                Ok(decrypted) => {
-                    match String::from_utf8(decrypted) {
-                        Ok(json) => {
-                            match serde_json::from_str::<VaultData>(&json) {
-                                Ok(vault_data) => Some(vault_data.entries),
-                                Err(e) => {
-                                    console_log!("Failed to deserialize vault: {}", e);
-                                    None
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            console_log!("UTF-8 conversion failed: {}", e);
-                            None
-                        }
-                    }
-                }
-                Err(e) => {
-                    console_log!("Decryption failed: {}", e);
-                    None
-                }
-            }
-        } else {
-            console_log!("Master key not available");
-            None
-        }
-    }
-
     // Crypto functions
-    fn derive_key(&self, password: &str, salt: &[u8]) -> Result<[u8; 32], String> {
-        let argon2 = Argon2::default();
-        let mut output = [0u8; 32];
-        
-        argon2.hash_password_into(password.as_bytes(), salt, &mut output)
-            .map_err(|e| format!("Argon2 error: {}", e))?;
-        
-        Ok(output)
+    fn derive_key(&self, password: &str, salt: &[u8]) -> Result<Zeroizing<[u8; 32]>, String> {
+        derive_key_with_params(password, salt, &self.kdf_params)
     }
 
     fn encrypt_data_internal(&self, key: &[u8; 32], data: &[u8]) -> Result<Vec<u8>, String> {
@@ -295,16 +615,7 @@ impl PassMannWasm {
     }
 
     fn decrypt_data_internal(&self, key: &[u8; 32], encrypted_data: &[u8]) -> Result<Vec<u8>, String> {
-        if encrypted_data.len() < 12 {
-            return Err("Invalid encrypted data".to_string());
-        }
-        
-        let cipher = ChaCha20Poly1305::new(GenericArray::from_slice(key));
-        let nonce = Nonce::from_slice(&encrypted_data[0..12]);
-        let ciphertext = &encrypted_data[12..];
-        
-        cipher.decrypt(nonce, ciphertext)
-            .map_err(|e| format!("Decryption failed: {}", e))
+        decrypt_with_key(key, encrypted_data)
     }
 
     #[wasm_bindgen]
@@ -316,7 +627,9 @@ impl PassMannWasm {
         
         match self.derive_key(master_password, salt) {
             Ok(key) => {
-                self.master_key = Some(key);
+                self.wipe_session();
+                self.master_key = Some(*key);
+                self.salt = Some(salt.to_vec());
                 console_log!("Successfully unlocked vault");
                 true
             }
@@ -376,6 +689,59 @@ impl PassMannWasm {
         salt.to_vec()
     }
 
+    #[wasm_bindgen]
+    pub fn generate_password(length: usize, symbols: bool) -> String {
+        let mut classes: Vec<&[u8]> = vec![LOWERCASE, UPPERCASE, DIGITS];
+        if symbols {
+            classes.push(SYMBOLS);
+        }
+
+        if length < classes.len() {
+            console_log!("Password length must be at least {}", classes.len());
+            return String::new();
+        }
+
+        match build_password(length, &classes) {
+            Ok(password) => password,
+            Err(e) => {
+                console_log!("Password generation failed: {}", e);
+                String::new()
+            }
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn generate_passphrase(words: usize) -> String {
+        if words == 0 {
+            console_log!("Passphrase must contain at least one word");
+            return String::new();
+        }
+
+        let mut chosen = Vec::with_capacity(words);
+        for _ in 0..words {
+            match random_index(PASSPHRASE_WORDS.len()) {
+                Ok(i) => chosen.push(PASSPHRASE_WORDS[i]),
+                Err(e) => {
+                    console_log!("Passphrase generation failed: {}", e);
+                    return String::new();
+                }
+            }
+        }
+        chosen.join("-")
+    }
+
+    #[wasm_bindgen]
+    pub fn estimate_strength(password: &str) -> JsValue {
+        let strength = estimate_password_strength(password);
+        match serde_wasm_bindgen::to_value(&strength) {
+            Ok(value) => value,
+            Err(e) => {
+                console_log!("Failed to serialize strength: {}", e);
+                JsValue::NULL
+            }
+        }
+    }
+
     #[wasm_bindgen]
     pub fn is_unlocked(&self) -> bool {
         self.master_key.is_some()
@@ -383,10 +749,50 @@ impl PassMannWasm {
 
     #[wasm_bindgen]
     pub fn lock(&mut self) {
-        self.master_key = None;
+        self.wipe_session();
         console_log!("Vault locked");
     }
 
+    /// Lock after `seconds` without activity, like the native `VaultLock`
+    /// timeout; 0 turns auto-lock off
+    #[wasm_bindgen]
+    pub fn set_auto_lock(&mut self, seconds: u32) {
+        self.auto_lock_seconds = seconds;
+        console_log!("Auto-lock set to {} seconds", seconds);
+    }
+
+    /// Lock the vault if it has been idle since `last_activity_ms` (a JS
+    /// `Date.now()` timestamp) for the auto-lock timeout. The key is zeroized
+    /// and the decrypted entries dropped. Returns whether it locked.
+    #[wasm_bindgen]
+    pub fn check_auto_lock(&mut self, last_activity_ms: f64) -> bool {
+        let now = chrono::Utc::now().timestamp_millis() as f64;
+        if !self.is_unlocked() || !auto_lock_due(self.auto_lock_seconds, last_activity_ms, now) {
+            return false;
+        }
+        self.wipe_session();
+        console_log!("Vault auto-locked after {} seconds idle", self.auto_lock_seconds);
+        true
+    }
+
+    /// Overwrite the key and the entries' passwords before dropping them.
+    ///
+    /// This is best-effort: WASM linear memory can't be locked or excluded from
+    /// dumps, and earlier copies (reallocated strings, values handed to JS, the
+    /// JS heap itself) are out of reach. It only guarantees the buffers this
+    /// instance still owns don't outlive the session in plaintext.
+    fn wipe_session(&mut self) {
+        if let Some(key) = self.master_key.as_mut() {
+            key.zeroize();
+        }
+        self.master_key = None;
+        self.salt = None;
+        if let Some(entries) = self.vault_data.as_mut() {
+            wipe_entries(entries);
+        }
+        self.vault_data = None;
+    }
+
     // Cloud sync functionality
     #[wasm_bindgen]
     pub fn set_cloud_mode(&mut self, server_url: &str) {
@@ -394,6 +800,94 @@ impl PassMannWasm {
         console_log!("Cloud mode enabled with server: {}", server_url);
     }
 
+    /// Encrypt the vault and upload it to `encrypted_vaults` as the CLI does.
+    /// Only the ciphertext and salt leave the browser. `vault_id` is generated
+    /// once by the page for this vault and kept; each upload replaces the row
+    /// stored under it. Requests are authorised with the signed-in user's
+    /// `access_token`. Resolves to the number of rows written.
+    #[wasm_bindgen]
+    pub fn upload_vault(&self, server_url: &str, anon_key: &str, access_token: &str, user_id: &str, device_id: &str, vault_id: &str) -> js_sys::Promise {
+        let row = match (self.encrypt_vault(), &self.salt) {
+            _ if access_token.is_empty() => Err(JsValue::from_str("Sign in before uploading")),
+            _ if vault_id.is_empty() => Err(JsValue::from_str("Vault id is required")),
+            (Some(encrypted), Some(salt)) => Ok(cloud_vault_row(user_id, device_id, vault_id, &encrypted, salt)),
+            _ => Err(JsValue::from_str("Vault not unlocked")),
+        };
+        // Upserting on the vault id makes retries and later uploads replace the row
+        let url = format!("{}?on_conflict=user_id,vault_id", vaults_url(server_url));
+        let (anon_key, access_token) = (anon_key.to_string(), access_token.to_string());
+
+        future_to_promise(async move {
+            let body = serde_json::to_string(&row?).map_err(|e| JsValue::from_str(&e.to_string()))?;
+            let rows = rest_request("POST", &url, &anon_key, &access_token, UPSERT, Some(&body)).await?;
+            let count = rows.as_array().map_or(0, |rows| rows.len());
+            console_log!("Vault uploaded ({} rows)", count);
+            Ok(JsValue::from(count as u32))
+        })
+    }
+
+    /// Fetch the user's newest vault. With `device_id` only that device's
+    /// uploads count, the same lookup as `passmann download` with its
+    /// PASSMANN_DEVICE_ID; without it the newest upload from any device is
+    /// used. Resolves to `{ encrypted_vault, salt }` to pass to
+    /// `unlock_vault_checked`, or `null` when nothing has been uploaded.
+    /// Only browser uploads can be opened: the CLI flags every row
+    /// `compression_enabled`, and those rows are rejected.
+    #[wasm_bindgen]
+    pub fn download_vault(&self, server_url: &str, anon_key: &str, access_token: &str, user_id: &str, device_id: Option<String>) -> js_sys::Promise {
+        let user_id = String::from(js_sys::encode_uri_component(user_id));
+        let device_id = device_id.map(|id| String::from(js_sys::encode_uri_component(&id)));
+        let url = vault_lookup_url(server_url, &user_id, device_id.as_deref());
+        let (anon_key, access_token) = (anon_key.to_string(), access_token.to_string());
+
+        future_to_promise(async move {
+            let rows: Vec<CloudVaultRow> = serde_json::from_value(rest_request("GET", &url, &anon_key, &access_token, "", None).await?)
+                .map_err(|e| JsValue::from_str(&format!("Invalid vault row: {}", e)))?;
+            let Some(row) = rows.into_iter().next() else {
+                return Ok(JsValue::NULL);
+            };
+            let (encrypted, salt) = open_cloud_vault_row(&row).map_err(|e| JsValue::from_str(&e))?;
+
+            let result = js_sys::Object::new();
+            js_sys::Reflect::set(&result, &"encrypted_vault".into(), &js_sys::Uint8Array::from(&encrypted[..]))?;
+            js_sys::Reflect::set(&result, &"salt".into(), &js_sys::Uint8Array::from(&salt[..]))?;
+            console_log!("Vault downloaded ({} bytes)", encrypted.len());
+            Ok(result.into())
+        })
+    }
+
+    /// Number of times `password` appears in known breaches (Have I Been
+    /// Pwned). Only the first five hex digits of its SHA-1 leave the browser.
+    /// Rejects with a message when the API can't be reached.
+    #[wasm_bindgen]
+    pub fn check_breach(&self, password: &str) -> js_sys::Promise {
+        let password = Zeroizing::new(password.to_string());
+        future_to_promise(async move {
+            let count = pwned_count(&password).await?;
+            Ok(JsValue::from_f64(count as f64))
+        })
+    }
+
+    /// Check every entry's password and resolve to the ids of the breached
+    /// entries. Each distinct password is looked up once.
+    #[wasm_bindgen]
+    pub fn audit_all_breaches(&self) -> js_sys::Promise {
+        let total = self.get_entries_count();
+        let lookups = self.vault_data.as_deref().map(breach_lookups);
+
+        future_to_promise(async move {
+            let lookups = lookups.ok_or_else(|| JsValue::from_str("Vault not unlocked"))?;
+            let mut breached = Vec::new();
+            for (password, ids) in &lookups {
+                if pwned_count(password).await? > 0 {
+                    breached.extend(ids.iter().cloned());
+                }
+            }
+            console_log!("Breach audit: {} of {} entries compromised", breached.len(), total);
+            serde_wasm_bindgen::to_value(&breached).map_err(|e| JsValue::from_str(&e.to_string()))
+        })
+    }
+
     #[wasm_bindgen]
     pub fn get_storage_mode(&self) -> String {
         self.storage_mode.clone()
@@ -405,6 +899,399 @@ impl PassMannWasm {
     }
 }
 
+impl Drop for PassMannWasm {
+    /// Runs when JS calls `free()` or the instance is garbage collected
+    fn drop(&mut self) {
+        self.wipe_session();
+    }
+}
+
+/// Overwrite the entries' passwords in place
+fn wipe_entries(entries: &mut [Entry]) {
+    for entry in entries {
+        entry.password.zeroize();
+    }
+}
+
+/// Each distinct password with the ids of the entries using it. The copies
+/// outlive the instance's borrow inside the breach audit's future, so they
+/// are wiped when it drops them.
+fn breach_lookups(entries: &[Entry]) -> Vec<(Zeroizing<String>, Vec<String>)> {
+    let mut lookups: Vec<(Zeroizing<String>, Vec<String>)> = Vec::new();
+    for entry in entries {
+        match lookups.iter_mut().find(|(password, _)| password.as_str() == entry.password) {
+            Some((_, ids)) => ids.push(entry.id.clone()),
+            None => lookups.push((Zeroizing::new(entry.password.clone()), vec![entry.id.clone()])),
+        }
+    }
+    lookups
+}
+
+/// A new entry stamped like the native `Entry::new`: a random v4 UUID and
+/// matching creation and modification times
+fn new_entry(service: &str, username: &str, password: &str, url: Option<String>, notes: Option<String>) -> Result<Entry, String> {
+    let now = chrono::Utc::now().timestamp_millis();
+    Ok(Entry {
+        id: new_entry_id()?,
+        service: service.to_string(),
+        username: username.to_string(),
+        password: password.to_string(),
+        url,
+        notes,
+        created_at: now,
+        modified_at: now,
+        is_favorite: false,
+    })
+}
+
+/// Random (version 4) UUID in its hyphenated form
+fn new_entry_id() -> Result<String, String> {
+    let mut bytes = [0u8; 16];
+    getrandom(&mut bytes).map_err(|e| format!("Random generation failed: {}", e))?;
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    Ok(format!("{}-{}-{}-{}-{}", &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32]))
+}
+
+// ============================================================================
+// CLOUD SYNC
+// ============================================================================
+
+/// A row of the CLI's `encrypted_vaults` table (`cloud::CloudVault`)
+#[derive(Debug, Serialize, Deserialize)]
+struct CloudVaultRow {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    user_id: String,
+    /// Base64 of nonce + ciphertext
+    encrypted_data: String,
+    salt: String,
+    device_id: String,
+    device_name: String,
+    version: i32,
+    created_at: Option<String>,
+    updated_at: Option<String>,
+    /// BLAKE3 hex of `encrypted_data`
+    checksum: String,
+    compression_enabled: bool,
+    size_bytes: i64,
+    /// Client-generated id of the vault the row holds; older rows have none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    vault_id: Option<String>,
+}
+
+const WEB_DEVICE_NAME: &str = "PassMann Web";
+
+fn vaults_url(server_url: &str) -> String {
+    format!("{}/rest/v1/encrypted_vaults", server_url.trim_end_matches('/'))
+}
+
+/// Query for the newest vault row of `user_id`, optionally from one device.
+/// Both ids must already be URI-encoded.
+fn vault_lookup_url(server_url: &str, user_id: &str, device_id: Option<&str>) -> String {
+    let mut url = format!("{}?user_id=eq.{}", vaults_url(server_url), user_id);
+    if let Some(device_id) = device_id {
+        url.push_str(&format!("&device_id=eq.{}", device_id));
+    }
+    url.push_str("&order=updated_at.desc&limit=1");
+    url
+}
+
+/// `Prefer` value for inserts that replace the row with the same key
+const UPSERT: &str = "resolution=merge-duplicates";
+
+fn cloud_vault_row(user_id: &str, device_id: &str, vault_id: &str, encrypted: &[u8], salt: &[u8]) -> CloudVaultRow {
+    let encrypted_data = general_purpose::STANDARD.encode(encrypted);
+    let now = chrono::Utc::now().to_rfc3339();
+    CloudVaultRow {
+        id: None,
+        user_id: user_id.to_string(),
+        checksum: blake3::hash(encrypted_data.as_bytes()).to_hex().to_string(),
+        size_bytes: encrypted_data.len() as i64,
+        encrypted_data,
+        salt: general_purpose::STANDARD.encode(salt),
+        device_id: device_id.to_string(),
+        device_name: WEB_DEVICE_NAME.to_string(),
+        version: 1,
+        created_at: Some(now.clone()),
+        updated_at: Some(now),
+        compression_enabled: false,
+        vault_id: Some(vault_id.to_string()),
+    }
+}
+
+/// Verify a downloaded row and decode it into `(encrypted vault, salt)`
+fn open_cloud_vault_row(row: &CloudVaultRow) -> Result<(Vec<u8>, Vec<u8>), String> {
+    // `blake3::Hash` compares in constant time
+    let checksum_ok = blake3::Hash::from_hex(&row.checksum)
+        .map(|expected| expected == blake3::hash(row.encrypted_data.as_bytes()))
+        .unwrap_or(false);
+    if !checksum_ok {
+        return Err("Cloud vault failed its integrity check".to_string());
+    }
+    // Every CLI upload carries the flag, whether or not its payload is gzipped
+    if row.compression_enabled {
+        return Err("Vaults uploaded from the CLI can't be opened in the browser".to_string());
+    }
+    let encrypted = general_purpose::STANDARD.decode(&row.encrypted_data).map_err(|e| format!("Invalid vault data: {}", e))?;
+    let salt = general_purpose::STANDARD.decode(&row.salt).map_err(|e| format!("Invalid vault salt: {}", e))?;
+    Ok((encrypted, salt))
+}
+
+/// Send a Supabase REST request with `fetch` as the signed-in user and
+/// parse the JSON reply. `prefer` adds to `return=representation`.
+async fn rest_request(method: &str, url: &str, anon_key: &str, access_token: &str, prefer: &str, body: Option<&str>) -> Result<serde_json::Value, JsValue> {
+    let init = web_sys::RequestInit::new();
+    init.set_method(method);
+    if let Some(body) = body {
+        init.set_body(&JsValue::from_str(body));
+    }
+    let request = web_sys::Request::new_with_str_and_init(url, &init)?;
+    let headers = request.headers();
+    headers.set("apikey", anon_key)?;
+    headers.set("Authorization", &format!("Bearer {}", access_token))?;
+    headers.set("Content-Type", "application/json")?;
+    let prefer = if prefer.is_empty() { "return=representation".to_string() } else { format!("return=representation,{}", prefer) };
+    headers.set("Prefer", &prefer)?;
+
+    let text = fetch_text(&request).await?;
+    serde_json::from_str(&text).map_err(|e| JsValue::from_str(&format!("Invalid cloud response: {}", e)))
+}
+
+/// `fetch` the request and return the body, failing on non-2xx statuses
+async fn fetch_text(request: &web_sys::Request) -> Result<String, JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("fetch is not available"))?;
+    let response: web_sys::Response = JsFuture::from(window.fetch_with_request(request)).await?.dyn_into()?;
+    let text = JsFuture::from(response.text()?).await?.as_string().unwrap_or_default();
+    if !response.ok() {
+        return Err(JsValue::from_str(&format!("Request failed ({}): {}", response.status(), text)));
+    }
+    Ok(text)
+}
+
+// ============================================================================
+// BREACH CHECKING
+// ============================================================================
+
+/// Have I Been Pwned k-anonymity range API, as used by the CLI
+const RANGE_API: &str = "https://api.pwnedpasswords.com/range/";
+
+/// Uppercase hex SHA-1 split into the 5-digit prefix sent to the API and the
+/// suffix looked up locally
+fn sha1_prefix_suffix(password: &str) -> (String, String) {
+    let digest: String = Sha1::digest(password.as_bytes()).iter().map(|b| format!("{:02X}", b)).collect();
+    let (prefix, suffix) = digest.split_at(5);
+    (prefix.to_string(), suffix.to_string())
+}
+
+async fn pwned_count(password: &str) -> Result<u64, JsValue> {
+    let (prefix, suffix) = sha1_prefix_suffix(password);
+    let request = web_sys::Request::new_with_str(&format!("{}{}", RANGE_API, prefix))?;
+    // Padding hides how many suffixes share the prefix
+    request.headers().set("Add-Padding", "true")?;
+    let body = fetch_text(&request).await
+        .map_err(|e| JsValue::from_str(&format!("Breach check failed: {}", e.as_string().unwrap_or_default())))?;
+    Ok(count_in_range(&body, &suffix))
+}
+
+/// Find `suffix` in a range response of `SUFFIX:COUNT` lines
+fn count_in_range(body: &str, suffix: &str) -> u64 {
+    body.lines()
+        .filter_map(|line| line.trim().split_once(':'))
+        .find(|(candidate, _)| candidate.eq_ignore_ascii_case(suffix))
+        .and_then(|(_, count)| count.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// Whether `timeout_seconds` have passed between `last_activity_ms` and `now_ms`
+fn auto_lock_due(timeout_seconds: u32, last_activity_ms: f64, now_ms: f64) -> bool {
+    timeout_seconds > 0 && now_ms - last_activity_ms >= timeout_seconds as f64 * 1000.0
+}
+
+// ============================================================================
+// KEY DERIVATION
+// ============================================================================
+
+fn decrypt_with_key(key: &[u8; 32], encrypted_data: &[u8]) -> Result<Vec<u8>, String> {
+    if encrypted_data.len() < 12 {
+        return Err("Invalid encrypted data".to_string());
+    }
+    
+    let cipher = ChaCha20Poly1305::new(GenericArray::from_slice(key));
+    let nonce = Nonce::from_slice(&encrypted_data[0..12]);
+    let ciphertext = &encrypted_data[12..];
+    
+    cipher.decrypt(nonce, ciphertext)
+        .map_err(|e| format!("Decryption failed: {}", e))
+}
+
+/// Decrypt and parse a vault blob. A failed AEAD check means the key is
+/// wrong; anything that authenticates but doesn't parse is corruption.
+fn decrypt_vault_entries(key: &[u8; 32], encrypted_data: &[u8]) -> Result<Vec<Entry>, UnlockError> {
+    if encrypted_data.len() < MIN_ENCRYPTED_LEN {
+        return Err(UnlockError::CorruptData);
+    }
+    let decrypted = decrypt_with_key(key, encrypted_data).map_err(|_| UnlockError::WrongPassword)?;
+    let json = String::from_utf8(decrypted).map_err(|_| UnlockError::CorruptData)?;
+    serde_json::from_str::<VaultData>(&json)
+        .map(|vault_data| vault_data.entries)
+        .map_err(|_| UnlockError::CorruptData)
+}
+
+fn argon2_for(params: &KdfParams) -> Result<Argon2<'static>, String> {
+    params.check_bounds()?;
+    let params = Params::new(params.memory_kib, params.iterations, params.parallelism, Some(32))
+        .map_err(|e| format!("Argon2 params error: {}", e))?;
+    Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+}
+
+/// The derived key is wiped when dropped, so a candidate that fails to open
+/// the vault doesn't linger
+fn derive_key_with_params(password: &str, salt: &[u8], params: &KdfParams) -> Result<Zeroizing<[u8; 32]>, String> {
+    let argon2 = argon2_for(params)?;
+    let mut output = Zeroizing::new([0u8; 32]);
+
+    argon2.hash_password_into(password.as_bytes(), salt, output.as_mut())
+        .map_err(|e| format!("Argon2 error: {}", e))?;
+
+    Ok(output)
+}
+
+// ============================================================================
+// PASSWORD GENERATION
+// ============================================================================
+
+const LOWERCASE: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+const UPPERCASE: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &[u8] = b"0123456789";
+const SYMBOLS: &[u8] = b"!@#$%^&*()-_=+[]{};:,.<>?";
+
+// 256 words, so each word contributes exactly 8 bits of entropy
+const PASSPHRASE_WORDS: [&str; 256] = [
+    "able", "acid", "aged", "also", "area", "army", "away", "baby",
+    "back", "ball", "band", "bank", "base", "bath", "bear", "beat",
+    "bell", "belt", "best", "bird", "blow", "blue", "boat", "body",
+    "bone", "book", "boot", "born", "boss", "both", "bowl", "bulk",
+    "burn", "bush", "busy", "cake", "call", "calm", "came", "camp",
+    "card", "care", "cart", "case", "cash", "cast", "cell", "chat",
+    "chip", "city", "clay", "club", "coal", "coat", "code", "cold",
+    "cook", "cool", "cope", "copy", "core", "corn", "cost", "crew",
+    "crop", "dark", "data", "date", "dawn", "deal", "dear", "deck",
+    "deep", "deer", "desk", "dial", "diet", "disk", "dock", "door",
+    "dose", "down", "draw", "drop", "drum", "duck", "dust", "duty",
+    "each", "earn", "ease", "east", "easy", "edge", "else", "even",
+    "ever", "exit", "face", "fact", "fair", "fall", "farm", "fast",
+    "fear", "feed", "feel", "file", "film", "find", "fire", "firm",
+    "fish", "flag", "flat", "flow", "folk", "food", "foot", "fork",
+    "form", "fort", "four", "free", "frog", "fuel", "full", "fund",
+    "gain", "game", "gate", "gear", "gift", "girl", "give", "glad",
+    "goal", "gold", "golf", "good", "gray", "grid", "grow", "gulf",
+    "hair", "half", "hall", "hand", "hard", "harp", "hawk", "head",
+    "heat", "help", "herb", "hero", "high", "hill", "hint", "hold",
+    "hole", "home", "hook", "hope", "horn", "host", "hour", "huge",
+    "hunt", "idea", "inch", "iron", "item", "jazz", "join", "joke",
+    "jump", "jury", "keen", "keep", "kick", "kind", "king", "kite",
+    "knee", "knot", "lace", "lake", "lamp", "land", "lane", "last",
+    "late", "lawn", "lead", "leaf", "lens", "life", "lift", "like",
+    "lime", "line", "link", "lion", "list", "load", "loan", "lock",
+    "loft", "logo", "long", "loop", "lord", "loud", "love", "luck",
+    "lung", "made", "mail", "main", "make", "mall", "many", "mark",
+    "mask", "mass", "meal", "meat", "menu", "mild", "milk", "mind",
+    "mine", "mint", "miss", "mode", "mood", "moon", "more", "moss",
+    "most", "move", "much", "myth", "nail", "name", "navy", "near",
+    "neck", "need", "nest", "news", "next", "nice", "node", "noon",
+];
+
+/// Uniform random index in `0..bound` using rejection sampling over the OS CSPRNG
+fn random_index(bound: usize) -> Result<usize, String> {
+    let bound = bound as u64;
+    let zone = (1u64 << 32) - ((1u64 << 32) % bound);
+    loop {
+        let mut buf = [0u8; 4];
+        getrandom(&mut buf).map_err(|e| format!("Random generation failed: {}", e))?;
+        let value = u32::from_le_bytes(buf) as u64;
+        if value < zone {
+            return Ok((value % bound) as usize);
+        }
+    }
+}
+
+/// Build a password with at least one character from every class, then shuffle
+fn build_password(length: usize, classes: &[&[u8]]) -> Result<String, String> {
+    let mut chars: Vec<u8> = Vec::with_capacity(length);
+    for class in classes {
+        chars.push(class[random_index(class.len())?]);
+    }
+
+    let all: Vec<u8> = classes.iter().flat_map(|c| c.iter().copied()).collect();
+    while chars.len() < length {
+        chars.push(all[random_index(all.len())?]);
+    }
+
+    // Fisher-Yates so the guaranteed characters don't sit at fixed positions
+    for i in (1..chars.len()).rev() {
+        let j = random_index(i + 1)?;
+        chars.swap(i, j);
+    }
+
+    Ok(String::from_utf8(chars).expect("charset is ASCII"))
+}
+
+// ============================================================================
+// PASSWORD STRENGTH
+// ============================================================================
+
+/// Offline attacker guessing rate used for the crack-time estimate
+const GUESSES_PER_SECOND: f64 = 1e10;
+
+/// The CLI's estimate with its built-in blocklist. Entropy and crack time
+/// are only shown alongside it.
+fn estimate_password_strength(password: &str) -> PasswordStrength {
+    let strength = passmann_strength::estimate_with_builtin_blocklist(password);
+    let entropy_bits = charset_entropy_bits(password);
+    PasswordStrength {
+        score: strength.score,
+        level: strength.level,
+        feedback: strength.feedback,
+        entropy_bits,
+        crack_time: format_crack_time(entropy_bits),
+    }
+}
+
+/// Length times the bits per character of the character classes used
+fn charset_entropy_bits(password: &str) -> f64 {
+    let mut pool = 0u32;
+    if password.chars().any(|c| c.is_ascii_lowercase()) { pool += 26; }
+    if password.chars().any(|c| c.is_ascii_uppercase()) { pool += 26; }
+    if password.chars().any(|c| c.is_ascii_digit()) { pool += 10; }
+    if password.chars().any(|c| !c.is_alphanumeric()) { pool += 33; }
+    if pool == 0 { 0.0 } else { password.chars().count() as f64 * (pool as f64).log2() }
+}
+
+/// Average time to find the password, i.e. half the keyspace at `GUESSES_PER_SECOND`
+fn format_crack_time(entropy_bits: f64) -> String {
+    let seconds = 2f64.powf(entropy_bits) / 2.0 / GUESSES_PER_SECOND;
+    const MINUTE: f64 = 60.0;
+    const HOUR: f64 = 60.0 * MINUTE;
+    const DAY: f64 = 24.0 * HOUR;
+    const YEAR: f64 = 365.0 * DAY;
+
+    if seconds < 1.0 {
+        "instant".to_string()
+    } else if seconds < HOUR {
+        format!("{:.0} minutes", (seconds / MINUTE).max(1.0))
+    } else if seconds < DAY {
+        format!("{:.0} hours", seconds / HOUR)
+    } else if seconds < YEAR {
+        format!("{:.0} days", seconds / DAY)
+    } else if seconds < 1000.0 * YEAR {
+        format!("{:.0} years", seconds / YEAR)
+    } else {
+        "centuries".to_string()
+    }
+}
+
 // Initialize WASM module
 #[wasm_bindgen(start)]
 pub fn main() {
@@ -412,3 +1299,198 @@ pub fn main() {
     console_log!("PassMann WASM module initialized");
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strength_matches_the_cli() {
+        for password in ["", "abc", "password", "Password", "letmein", "admin123", "hunter2",
+            "correct horse battery staple", "Tr0ub4dor&3", "aaaaaaaaaaaaaaaa", "x9$Lq!vR2#mZ8@pT"] {
+            let web = estimate_password_strength(password);
+            let native = passmann_strength::estimate_with_builtin_blocklist(password);
+            assert_eq!((web.score, web.level, web.feedback), (native.score, native.level, native.feedback),
+                "password {:?}", password);
+        }
+    }
+
+    #[test]
+    fn test_vault_sealed_with_old_default_kdf_still_opens() {
+        // Sealed by the previous release: Argon2::default() key, no KDF header
+        let blob = general_purpose::STANDARD
+            .decode(include_str!("../tests/fixtures/legacy_kdf_vault.b64").trim())
+            .expect("fixture");
+        let password = "correct horse battery staple";
+        let salt = b"passmann-interop-salt-0123456789";
+
+        let (params, ciphertext) = KdfParams::split_header(&blob).unwrap();
+        assert_eq!(params, KdfParams::LEGACY);
+        let key = derive_key_with_params(password, salt, &params).expect("derive");
+        let entries = decrypt_vault_entries(&key, ciphertext).expect("legacy vault opens");
+        assert_eq!(entries.len(), 1);
+        assert_eq!((entries[0].service.as_str(), entries[0].password.as_str()), ("github", "hunter2-legacy"));
+
+        let current = derive_key_with_params(password, salt, &KdfParams::default()).expect("derive");
+        assert_eq!(decrypt_vault_entries(&current, ciphertext).unwrap_err(), UnlockError::WrongPassword);
+    }
+
+    #[test]
+    fn test_kdf_header_round_trips() {
+        let params = KdfParams { memory_kib: 1024, iterations: 1, parallelism: 2 };
+        let sealed = [0x5a; MIN_ENCRYPTED_LEN];
+        let blob = [&params.header()[..], &sealed].concat();
+        assert_eq!(&blob[..4], b"PMK1");
+        assert_eq!(KdfParams::split_header(&blob), Ok((params, &sealed[..])));
+    }
+
+    #[test]
+    fn test_kdf_header_bounds_and_legacy_lookalikes() {
+        let sealed = [0x5a; MIN_ENCRYPTED_LEN];
+        let huge = KdfParams { memory_kib: u32::MAX, iterations: 1, parallelism: 1 };
+        assert_eq!(KdfParams::split_header(&[&huge.header()[..], &sealed].concat()), Err(UnlockError::CorruptData));
+        let slow = KdfParams { memory_kib: 1024, iterations: 1000, parallelism: 1 };
+        assert_eq!(KdfParams::split_header(&[&slow.header()[..], &sealed].concat()), Err(UnlockError::CorruptData));
+        assert!(derive_key_with_params("pw", b"passmann-salt-16", &huge).is_err());
+
+        // Legacy blobs are a random nonce then ciphertext; one that merely
+        // starts like a header, but is too short or has another version, stays legacy
+        let short = [&b"PMK1"[..], &[0x5a; MIN_ENCRYPTED_LEN]].concat();
+        assert_eq!(KdfParams::split_header(&short), Ok((KdfParams::LEGACY, &short[..])));
+        let mut other_version = [&KdfParams::default().header()[..], &sealed].concat();
+        other_version[3] = b'9';
+        assert_eq!(KdfParams::split_header(&other_version), Ok((KdfParams::LEGACY, &other_version[..])));
+    }
+
+    #[test]
+    fn test_decrypt_vault_entries_distinguishes_failures() {
+        let key = [7u8; 32];
+        let wasm = PassMannWasm::new();
+        let vault = VaultData { entries: Vec::new(), created_at: 0, modified_at: 0, storage_mode: "local".to_string() };
+        let sealed = wasm.encrypt_data_internal(&key, serde_json::to_string(&vault).unwrap().as_bytes()).unwrap();
+
+        assert!(decrypt_vault_entries(&key, &sealed).unwrap().is_empty());
+        assert_eq!(decrypt_vault_entries(&[8u8; 32], &sealed).unwrap_err(), UnlockError::WrongPassword);
+        assert_eq!(decrypt_vault_entries(&key, &sealed[..20]).unwrap_err(), UnlockError::CorruptData);
+
+        let not_a_vault = wasm.encrypt_data_internal(&key, b"[1, 2, 3]").unwrap();
+        assert_eq!(decrypt_vault_entries(&key, &not_a_vault).unwrap_err(), UnlockError::CorruptData);
+    }
+
+    #[test]
+    fn test_vault_lookup_matches_the_cli_or_spans_devices() {
+        assert_eq!(vault_lookup_url("https://db.example/", "user", Some("laptop")),
+            "https://db.example/rest/v1/encrypted_vaults?user_id=eq.user&device_id=eq.laptop&order=updated_at.desc&limit=1");
+        assert_eq!(vault_lookup_url("https://db.example", "user", None),
+            "https://db.example/rest/v1/encrypted_vaults?user_id=eq.user&order=updated_at.desc&limit=1");
+    }
+
+    #[test]
+    fn test_auto_lock_due_after_timeout() {
+        assert!(!auto_lock_due(0, 0.0, 1e12));
+        assert!(!auto_lock_due(60, 1_000.0, 60_999.0));
+        assert!(auto_lock_due(60, 1_000.0, 61_000.0));
+    }
+
+    #[test]
+    fn test_wipe_session_forgets_key_and_entries() {
+        let mut wasm = PassMannWasm::new();
+        wasm.master_key = Some([7u8; 32]);
+        wasm.vault_data = Some(Vec::new());
+        wasm.wipe_session();
+        assert!(!wasm.is_unlocked());
+        assert!(wasm.vault_data.is_none());
+    }
+
+    #[test]
+    fn test_wiped_entries_leave_no_password_bytes() {
+        let mut entries = vec![
+            new_entry("github", "me", "hunter2-secret", None, None).unwrap(),
+            new_entry("gitlab", "me", "hunter2-secret", None, None).unwrap(),
+            new_entry("bank", "me", "other-secret", None, None).unwrap(),
+        ];
+
+        // Reused passwords are looked up once, and every copy is zeroizing
+        let lookups = breach_lookups(&entries);
+        let grouped: Vec<(&str, usize)> = lookups.iter().map(|(password, ids)| (password.as_str(), ids.len())).collect();
+        assert_eq!(grouped, [("hunter2-secret", 2), ("other-secret", 1)]);
+
+        // The whole allocation is overwritten, not just the visible length
+        wipe_entries(&mut entries);
+        for entry in &entries {
+            assert!(entry.password.is_empty());
+            let buffer = unsafe { std::slice::from_raw_parts(entry.password.as_ptr(), entry.password.capacity()) };
+            assert!(buffer.iter().all(|&b| b == 0));
+        }
+    }
+
+    #[test]
+    fn test_cloud_vault_row_round_trips() {
+        let row = cloud_vault_row("user", "device", "vault", b"nonce-and-ciphertext", b"salt-salt-salt-salt");
+        assert_eq!(row.size_bytes, row.encrypted_data.len() as i64);
+        assert_eq!(serde_json::to_value(&row).unwrap()["vault_id"], "vault");
+        let (encrypted, salt) = open_cloud_vault_row(&row).unwrap();
+        assert_eq!(encrypted, b"nonce-and-ciphertext");
+        assert_eq!(salt, b"salt-salt-salt-salt");
+
+        let from_cli = CloudVaultRow { compression_enabled: true, ..cloud_vault_row("user", "device", "vault", b"nonce-and-ciphertext", b"salt") };
+        assert!(open_cloud_vault_row(&from_cli).is_err());
+
+        let tampered = CloudVaultRow { encrypted_data: general_purpose::STANDARD.encode(b"other"), ..row };
+        assert!(open_cloud_vault_row(&tampered).is_err());
+        assert_eq!(vaults_url("https://x.supabase.co/"), "https://x.supabase.co/rest/v1/encrypted_vaults");
+    }
+
+    #[test]
+    fn test_breach_lookup_sends_only_the_prefix() {
+        let (prefix, suffix) = sha1_prefix_suffix("password");
+        assert_eq!(prefix, "5BAA6");
+        assert_eq!(suffix, "1E4C9B93F3F0682250B6CF8331B7EE68FD8");
+
+        let body = "0018A45C4D1DEF81644B54AB7F969B88D65:1\r\n1E4C9B93F3F0682250B6CF8331B7EE68FD8:3861493\r\n";
+        assert_eq!(count_in_range(body, &suffix), 3861493);
+        assert_eq!(count_in_range(body, "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF"), 0);
+    }
+
+    #[test]
+    fn test_new_entry_matches_native_shape() {
+        let entry = new_entry("github", "me", "pw", None, None).unwrap();
+        assert_eq!(entry.created_at, entry.modified_at);
+        assert_eq!(entry.id.len(), 36);
+        assert_eq!(entry.id.as_bytes()[14], b'4');
+        assert!(matches!(entry.id.as_bytes()[19], b'8' | b'9' | b'a' | b'b'));
+        assert_ne!(entry.id, new_entry("github", "me", "pw", None, None).unwrap().id);
+    }
+
+    #[test]
+    fn test_borrowed_vault_data_serializes_like_owned() {
+        let entries = vec![new_entry("github", "me", "pw", Some("https://github.com".to_string()), None).unwrap()];
+        let owned = VaultData { entries: entries.clone(), created_at: 1, modified_at: 2, storage_mode: "local".to_string() };
+        let borrowed = VaultDataRef { entries: &entries, created_at: 1, modified_at: 2, storage_mode: "local" };
+        assert_eq!(serde_json::to_string(&owned).unwrap(), serde_json::to_string(&borrowed).unwrap());
+    }
+
+    #[test]
+    fn test_default_key_matches_native_derive_key() {
+        // crypto::derive_key's output for this password and salt: Argon2id
+        // v0x13, 64 MiB, 3 passes, 4 lanes, 32 bytes (argon2 0.5.3)
+        const NATIVE_KEY: &str = "debde3f7eded0d37ae2dbe10d883ceee7984bf4f9243bd9b91a62175626dec8b";
+        let key = derive_key_with_params("correct horse battery staple", b"passmann-interop-salt-0123456789", &KdfParams::default())
+            .expect("derive");
+        assert_eq!(key.iter().map(|b| format!("{:02x}", b)).collect::<String>(), NATIVE_KEY);
+    }
+
+    #[test]
+    fn test_kdf_params_change_derived_key() {
+        let password = "correct horse battery staple";
+        let salt = b"passmann-interop-salt-0123456789";
+        let low = KdfParams { memory_kib: 1024, iterations: 1, parallelism: 1 };
+        let other = KdfParams { memory_kib: 1024, iterations: 2, parallelism: 1 };
+
+        let first = derive_key_with_params(password, salt, &low).expect("derive");
+        let again = derive_key_with_params(password, salt, &low).expect("derive");
+        let different = derive_key_with_params(password, salt, &other).expect("derive");
+
+        assert_eq!(first, again);
+        assert_ne!(first, different);
+    }
+}